@@ -0,0 +1,67 @@
+//! Compares proximity queries on a database of 5000 asteroids with and without a spatial index
+//!
+//! Run with `cargo bench --bench spatial_index`
+
+use std::{hint::black_box, time::{Duration, Instant}};
+use game_orbits::{constants::f64::CONVERT_AU_TO_M, handles::HANDLE_SOL, Body, Database, DatabaseEntry, OrbitalElements};
+
+const NUM_ASTEROIDS: u32 = 5000;
+const NUM_QUERIES: u32 = 200;
+const TIME: f64 = 1e8;
+
+
+/// Small deterministic generator so every run benchmarks the same belt
+struct Lcg(u64);
+impl Lcg {
+	fn next(&mut self) -> f64 {
+		self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+		(self.0 >> 11) as f64 / (1u64 << 53) as f64
+	}
+	fn range(&mut self, min: f64, max: f64) -> f64 {
+		min + (max - min) * self.next()
+	}
+}
+
+fn asteroid_belt() -> Database<u32, f64> {
+	let mut database = Database::default();
+	database.add_sol();
+	let mut rng = Lcg(1);
+	for handle in 1000..1000 + NUM_ASTEROIDS {
+		let orbit = OrbitalElements::default()
+			.with_semimajor_axis_au(rng.range(2.1, 3.3))
+			.with_eccentricity(rng.range(0.0, 0.25))
+			.with_inclination_deg(rng.range(0.0, 20.0))
+			.with_long_of_ascending_node_deg(rng.range(0.0, 360.0))
+			.with_arg_of_periapsis_deg(rng.range(0.0, 360.0));
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1e15).with_radius_km(5.0), "Asteroid")
+			.with_parent(HANDLE_SOL as u32, orbit)
+			.with_mean_anomaly_deg(rng.range(0.0, 360.0));
+		database.add_entry(handle, entry);
+	}
+	database
+}
+
+/// Runs the nearest neighbour and radius queries from the position of every asteroid in turn
+fn run_queries(database: &Database<u32, f64>) -> Duration {
+	let points: Vec<_> = (1000..1000 + NUM_QUERIES).map(|handle| database.absolute_position_at_time(&handle, TIME)).collect();
+	let start = Instant::now();
+	for point in points {
+		black_box(database.bodies_by_distance_from(point, TIME, 10));
+		black_box(database.bodies_within(point, 0.05 * CONVERT_AU_TO_M, TIME));
+	}
+	start.elapsed()
+}
+
+fn main() {
+	let mut database = asteroid_belt();
+	let linear = run_queries(&database);
+	let start = Instant::now();
+	database.rebuild_spatial_index(TIME, 0.05 * CONVERT_AU_TO_M);
+	let build = start.elapsed();
+	let indexed = run_queries(&database);
+	println!("{} bodies, {} queries of each kind", NUM_ASTEROIDS + 1, NUM_QUERIES);
+	println!("linear scan:   {:>10.3?}", linear);
+	println!("index build:   {:>10.3?}", build);
+	println!("indexed:       {:>10.3?} ({:.1}x faster)", indexed, linear.as_secs_f64() / indexed.as_secs_f64());
+	assert!(indexed < linear, "Expected indexed queries to beat the linear scan");
+}
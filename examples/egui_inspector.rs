@@ -0,0 +1,17 @@
+//! Shows the solar system database in an egui window using the database inspector, without any
+//! game engine
+//!
+//! Run with `cargo run --example egui_inspector --features egui`
+
+use eframe::egui;
+use game_orbits::{inspect_database, Database};
+
+
+fn main() -> eframe::Result {
+	let database: Database<u16, f64> = Database::default().with_solar_system();
+	eframe::run_simple_native("Database inspector", eframe::NativeOptions::default(), move |ctx, _frame| {
+		egui::Window::new("Database").default_width(360.0).show(ctx, |ui| {
+			egui::ScrollArea::vertical().show(ui, |ui| inspect_database(ui, &database));
+		});
+	})
+}
@@ -3,7 +3,7 @@
 
 use std::f32::consts::PI;
 use bevy::{prelude::*, pbr::wireframe::{Wireframe, WireframePlugin}};
-use game_orbits::{constants::f32::*, Body};
+use game_orbits::{constants::f32::*, mean_motion, Body};
 
 const SCALE: f32 = 1.0 / 3_000_000.0;
 const ALTITUDE_KM: f32 = 400.0;
@@ -40,7 +40,7 @@ impl Orbit {
 		self
 	}
 	pub fn mean_motion(&self, body: &Body<f32>) -> f32 {
-		(body.gm() / self.semimajor_axis.powi(3)).sqrt()
+		mean_motion(body.gm(), self.semimajor_axis)
 	}
 	pub fn mean_anomaly(&self, body: &Body<f32>) -> f32 {
 		self.time * self.mean_motion(body)
@@ -0,0 +1,43 @@
+//! Propagates a few bodies of the solar system through time and prints their positions,
+//! velocities and periods, using only the library without any game engine
+//!
+//! Run with `cargo run --example propagate`
+
+use game_orbits::{constants::f64::{CONVERT_M_TO_AU, CONVERT_M_TO_KM}, handles::*, Database};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+const DAYS_PER_STEP: f64 = 30.0;
+const NUM_STEPS: u32 = 6;
+const BODIES: [u16; 4] = [HANDLE_EARTH, HANDLE_LUNA, HANDLE_MARS, HANDLE_IO];
+
+
+fn main() {
+	let database: Database<u16, f64> = Database::default().with_solar_system();
+
+	println!("Orbital periods");
+	for handle in BODIES {
+		let entry = database.get_entry(&handle);
+		let period_days = database.orbital_period_s(&handle).unwrap() / SECONDS_PER_DAY;
+		println!("  {:8} {:>10.3} days", entry.name, period_days);
+	}
+
+	for step in 0..=NUM_STEPS {
+		let days = step as f64 * DAYS_PER_STEP;
+		let time = days * SECONDS_PER_DAY;
+		println!();
+		println!("Day {}", days);
+		for handle in BODIES {
+			let entry = database.get_entry(&handle);
+			let parent = database.get_entry(entry.parent.as_ref().unwrap());
+			let (position, velocity) = database.state_vector_at_time(&handle, time).unwrap();
+			println!(
+				"  {:8} {:>12.0} km from {:8} moving at {:>6.3} km/s",
+				entry.name, position.norm() * CONVERT_M_TO_KM, parent.name, velocity.norm() * CONVERT_M_TO_KM,
+			);
+		}
+		let sun_to_earth = database.position_at_time(&HANDLE_EARTH, time);
+		let earth_to_mars = database.relative_position(&HANDLE_EARTH, &HANDLE_MARS, time).unwrap();
+		println!("  Sun to Earth:  ({:>7.4}, {:>7.4}, {:>7.4}) AU", sun_to_earth.x * CONVERT_M_TO_AU, sun_to_earth.y * CONVERT_M_TO_AU, sun_to_earth.z * CONVERT_M_TO_AU);
+		println!("  Earth to Mars: ({:>7.4}, {:>7.4}, {:>7.4}) AU", earth_to_mars.x * CONVERT_M_TO_AU, earth_to_mars.y * CONVERT_M_TO_AU, earth_to_mars.z * CONVERT_M_TO_AU);
+	}
+}
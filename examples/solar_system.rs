@@ -1,6 +1,6 @@
 use std::f32::consts::{PI, TAU};
 use bevy::prelude::*;
-use game_orbits::{BevyPlanetDatabase, handles::*};
+use game_orbits::{BevyPlanetDatabase, CameraOrbitInput, CameraZoomInput, OrbitalCamera, OrbitalCameraPlugin, OrbitalCameraSettings, handles::*, update_orbital_camera};
 
 
 const SCALE: f32 = 1.0 / 20_000_000.0;
@@ -15,11 +15,11 @@ const CAM_FOCUS_NEXT_PLANET: KeyCode = KeyCode::ArrowRight;
 const CAM_FOCUS_PREV_PLANET: KeyCode = KeyCode::ArrowLeft;
 const CAM_FOCUS_PARENT: KeyCode = KeyCode::ArrowUp;
 const CAM_FOCUS_SATELLITES: KeyCode = KeyCode::ArrowDown;
-const CAM_MAX_PITCH: f32 = 1.55; // rad
 const CAM_ROTATE_SPEED: f32 = 0.8; // rad/s
 const CAM_MIN_DISTANCE: f32 = 0.3;
 const CAM_MAX_DISTANCE: f32 = 1000000.0;
-const CAM_ZOOM_SPEED: f32 = 0.08;
+const CAM_ZOOM_SPEED: f32 = 1.5;
+const CAM_START_DISTANCE: f32 = 1000.0;
 const CAM_CENTERED_ON_DEFAULT: usize = HANDLE_EARTH as usize;
 const CHANGE_VIEW_ORBITS: KeyCode = KeyCode::Digit1;
 const TOGGLE_VIEW_SOI: KeyCode = KeyCode::Digit2;
@@ -30,7 +30,6 @@ const DECREASE_TIME: KeyCode = KeyCode::Comma;
 const TIME_CHANGE_SPEED: f32 = 2000.0;
 
 const ORBIT_SEGMENTS: usize = 100;
-const ORBIT_COLOR: Color = Color::srgb(0.5, 1.0, 0.0);
 const PERIAPSIS_COLOR: Color = Color::srgb(1.0, 0.5, 0.0);
 const APOAPSIS_COLOR: Color = Color::srgb(0.0, 0.5, 1.0);
 const PLANET_COLOR: Color = Color::srgb(1.0, 0.1, 0.5);
@@ -93,9 +92,6 @@ impl Default for SystemTime {
 #[derive(Component)]
 struct CameraParent {
 	pub centered_body: usize,
-	pub yaw: f32,
-	pub pitch: f32,
-	pub zoom: f32,
 	pub view_apsis: bool,
 	pub view_soi: bool,
 	pub view_axes: bool,
@@ -109,24 +105,20 @@ impl CameraParent {
 }
 impl Default for CameraParent {
 	fn default() -> Self {
-		Self{ yaw: 0.0, pitch: 0.0, zoom: 0.1, centered_body: 0, view_apsis: false, view_soi: true, view_axes: false, view_orbit: OrbitViewMode::All }
+		Self{ centered_body: 0, view_apsis: false, view_soi: true, view_axes: false, view_orbit: OrbitViewMode::All }
 	}
 }
 
 
 fn setup_camera(mut commands: Commands) {
 	// camera
-	let camera = commands.spawn((
-		Camera3d::default(),
-		Transform::from_xyz(0.0, 35.0, -100.0).looking_at(Vec3::ZERO, Vec3::Y),
-		InheritedVisibility::default(),
-	)).id();
-	// camera parent
 	commands.spawn((
+		Camera3d::default(),
 		Transform::default(),
-		Visibility::default(),
-		CameraParent::default().centered_on(CAM_CENTERED_ON_DEFAULT),
-	)).add_child(camera);
+		OrbitalCamera::new(CAM_CENTERED_ON_DEFAULT, CAM_START_DISTANCE),
+	));
+	// camera parent
+	commands.spawn(CameraParent::default().centered_on(CAM_CENTERED_ON_DEFAULT));
 }
 
 fn setup_ui(mut commands: Commands) {
@@ -278,26 +270,11 @@ fn update_planet_focus_ui(
 		text.0 = first_satellite.name.clone();
 	}
 	// prev/next planet
-	if let Some(parent_handle) = entry.parent {
-		let siblings = database.get_satellites(&parent_handle);
-		let index = siblings.binary_search(&camera_parent.centered_body).unwrap();
-		let prev_index;
-		if index > 0 {
-			prev_index = index - 1;
-		} else {
-			prev_index = siblings.len() - 1;
-		}
-		let next_index;
-		if index < siblings.len() - 1 {
-			next_index = index + 1;
-		} else {
-			next_index = 0;
-		}
-		let prev_handle = siblings[prev_index];
+	let prev_and_next = (prev_sibling(&database, camera_parent.centered_body), next_sibling(&database, camera_parent.centered_body));
+	if let (Some(prev_handle), Some(next_handle)) = prev_and_next {
 		let prev_entry = database.get_entry(&prev_handle);
 		text = elements.get_mut(handles.prev_planet_name).unwrap();
 		text.0 = prev_entry.name.clone();
-		let next_handle = siblings[next_index];
 		let next_entry = database.get_entry(&next_handle);
 		text = elements.get_mut(handles.next_planet_name).unwrap();
 		text.0 = next_entry.name.clone();
@@ -341,32 +318,50 @@ fn update_time_display(
 
 fn process_camera_input(
 	keyboard: Res<ButtonInput<KeyCode>>, time: Res<Time>,
-	mut camera_parents: Query<&mut CameraParent>,
+	mut orbit_inputs: EventWriter<CameraOrbitInput>,
+	mut zoom_inputs: EventWriter<CameraZoomInput>,
 ){
 	let delta = time.delta_secs();
-	let mut camera_parent = camera_parents.single_mut();
 	// handle rotation inputs
+	let mut orbit = CameraOrbitInput{ yaw: 0.0, pitch: 0.0 };
 	if keyboard.pressed(CAM_ROTATE_RIGHT) {
-		camera_parent.yaw += CAM_ROTATE_SPEED * delta;
+		orbit.yaw += CAM_ROTATE_SPEED * delta;
 	}
 	if keyboard.pressed(CAM_ROTATE_LEFT) {
-		camera_parent.yaw -= CAM_ROTATE_SPEED * delta;
+		orbit.yaw -= CAM_ROTATE_SPEED * delta;
 	}
 	if keyboard.pressed(CAM_ROTATE_UP) {
-		camera_parent.pitch += CAM_ROTATE_SPEED * delta;
+		orbit.pitch += CAM_ROTATE_SPEED * delta;
 	}
 	if keyboard.pressed(CAM_ROTATE_DOWN) {
-		camera_parent.pitch -= CAM_ROTATE_SPEED * delta;
+		orbit.pitch -= CAM_ROTATE_SPEED * delta;
 	}
-	camera_parent.pitch = camera_parent.pitch.clamp(-CAM_MAX_PITCH, CAM_MAX_PITCH);
+	orbit_inputs.send(orbit);
 	// handle zoom inputs
 	if keyboard.pressed(CAM_ZOOM_IN) {
-		camera_parent.zoom -= CAM_ZOOM_SPEED * delta;
+		zoom_inputs.send(CameraZoomInput{ amount: -CAM_ZOOM_SPEED * delta });
 	}
 	if keyboard.pressed(CAM_ZOOM_OUT) {
-		camera_parent.zoom += CAM_ZOOM_SPEED * delta;
+		zoom_inputs.send(CameraZoomInput{ amount: CAM_ZOOM_SPEED * delta });
 	}
-	camera_parent.zoom = camera_parent.zoom.clamp(0.0, 1.0);
+}
+
+/// Keeps the orbital camera centered on the focused body at the current system time
+fn sync_orbital_camera(
+	camera_parents: Query<&CameraParent>,
+	mut cameras: Query<&mut OrbitalCamera<usize>>,
+	mut settings: ResMut<OrbitalCameraSettings<usize>>,
+	system_time: Res<SystemTime>,
+){
+	let camera_parent = camera_parents.single();
+	settings.origin = camera_parent.centered_body;
+	settings.time_s = system_time.seconds;
+	cameras.single_mut().target_handle = camera_parent.centered_body;
+}
+
+/// How far the camera is zoomed out, from 0 at its closest to 1 at its furthest
+fn camera_zoom(camera: &OrbitalCamera<usize>) -> f32 {
+	((camera.distance - CAM_MIN_DISTANCE) / (CAM_MAX_DISTANCE - CAM_MIN_DISTANCE)).clamp(0.0, 1.0)
 }
 
 fn process_navigation_controls(
@@ -388,37 +383,35 @@ fn process_navigation_controls(
 		}
 	}
 	if keyboard.just_pressed(CAM_FOCUS_NEXT_PLANET) {
-		let entry = database.get_entry(&camera_parent.centered_body);
-		if let Some(parent_handle) = entry.parent {
-			let siblings = database.get_satellites(&parent_handle);
-			if siblings.len() > 0 {
-				let err_msg = format!("Siblings list did not contain handle {} (list: {:?})", camera_parent.centered_body, siblings);
-				let mut index = siblings.binary_search(&camera_parent.centered_body).expect(&err_msg);
-				index += 1;
-				if index >= siblings.len() {
-					index = 0;
-				}
-				camera_parent.centered_body = siblings[index];
-			}
+		if let Some(next_handle) = next_sibling(&database, camera_parent.centered_body) {
+			camera_parent.centered_body = next_handle;
 		}
 	}
 	if keyboard.just_pressed(CAM_FOCUS_PREV_PLANET) {
-		let entry = database.get_entry(&camera_parent.centered_body);
-		if let Some(parent_handle) = entry.parent {
-			let siblings = database.get_satellites(&parent_handle);
-			if siblings.len() > 0 {
-				let err_msg = format!("Siblings list did not contain handle {} (list: {:?})", camera_parent.centered_body, siblings);
-				let mut index = siblings.binary_search(&camera_parent.centered_body).expect(&err_msg);
-				if index <= 0 {
-					index = siblings.len();
-				}
-				index -= 1;
-				camera_parent.centered_body = siblings[index];
-			}
+		if let Some(prev_handle) = prev_sibling(&database, camera_parent.centered_body) {
+			camera_parent.centered_body = prev_handle;
 		}
 	}
 }
 
+/// Gets the sibling after the given body, wrapping around to the first one
+/// 
+/// A body with a parent but no siblings is its own next sibling, while the root body has none.
+fn next_sibling(database: &Database, handle: usize) -> Option<usize> {
+	database.get_entry(&handle).parent?;
+	let siblings = database.siblings(&handle);
+	Some(siblings.iter().find(|sibling| **sibling > handle).or(siblings.first()).copied().unwrap_or(handle))
+}
+
+/// Gets the sibling before the given body, wrapping around to the last one
+/// 
+/// A body with a parent but no siblings is its own previous sibling, while the root body has none.
+fn prev_sibling(database: &Database, handle: usize) -> Option<usize> {
+	database.get_entry(&handle).parent?;
+	let siblings = database.siblings(&handle);
+	Some(siblings.iter().rev().find(|sibling| **sibling < handle).or(siblings.last()).copied().unwrap_or(handle))
+}
+
 fn process_visibility_input(
 	mut camera_parents: Query<&mut CameraParent>,
 	keyboard: Res<ButtonInput<KeyCode>>,
@@ -438,46 +431,29 @@ fn process_visibility_input(
 	}
 }
 
-fn update_camera_position(
-	mut camera_parents: Query<(&mut Transform, &CameraParent), Without<Camera3d>>,
-	mut cameras: Query<&mut Transform, (With<Camera3d>, Without<CameraParent>)>,
-){
-	let (mut camera_parent_transform, camera_parent) = camera_parents.single_mut();
-	let mut camera_transform = cameras.single_mut();
-	let camera_rotation = Quat::from_axis_angle(Vec3::X, camera_parent.pitch);
-	let camera_direction = camera_rotation * -Vec3::Z;
-	let center_position = Vec3::ZERO;
-	// info!("Setting camera center position to {:?}", center_position);
-	let camera_distance = CAM_MIN_DISTANCE.lerp(CAM_MAX_DISTANCE, camera_parent.zoom.powf(3.0));
-	camera_parent_transform.translation = center_position;
-	camera_parent_transform.rotation = Quat::from_axis_angle(Vec3::Y, camera_parent.yaw);
-	camera_transform.translation = camera_direction * camera_distance;
-	camera_transform.look_at(Vec3::ZERO, Vec3::Y);
-}
-
 fn draw_orbits(
 	mut gizmos: Gizmos, camera_parents: Query<&CameraParent>,
+	cameras: Query<&OrbitalCamera<usize>>,
 	db: Res<Database>, system_time: Res<SystemTime>,
 ) {
 	let camera_parent = camera_parents.single();
+	let zoom = camera_zoom(cameras.single());
 	let origin_body = camera_parent.centered_body;
 	let step = TAU / (ORBIT_SEGMENTS-1) as f32;
 	for (handle, entry) in db.iter() {
 		let heirarchy = db.get_parents(&handle);
-		if let Some(parent_handle) = entry.parent {
+		if entry.parent.is_some() {
 			let view_all = camera_parent.view_orbit == OrbitViewMode::All;
 			let view_heirarchy = camera_parent.view_orbit == OrbitViewMode::Children && heirarchy.contains(&camera_parent.centered_body);
 			let view_selected = camera_parent.view_orbit == OrbitViewMode::Selected && *handle == camera_parent.centered_body;
 			if view_all || view_heirarchy || view_selected {
-				let failure_msg = format!("Failed to find relative position between origin body {} and relative body {}", origin_body, parent_handle);
-				let parent_pos = db.relative_position(&origin_body, &parent_handle, system_time.seconds).expect(&failure_msg) * SCALE;
+				let parent_pos = db.parent_position_relative_to(handle, &origin_body, system_time.seconds)
+					.unwrap_or_else(|| panic!("Failed to find position of {}'s parent", entry.name)) * SCALE;
 				let mut points: Vec<(f32, Vec3)> = Vec::new();
-				let starting_mean_anomaly = db.mean_anomaly_at_time(handle, system_time.seconds);
 				// get orbit path
 				for i in 0..ORBIT_SEGMENTS {
 					let mean_anomaly_offset = step * i as f32;
-					let m = starting_mean_anomaly + mean_anomaly_offset;
-					let pos = db.position_at_mean_anomaly(handle, m) * SCALE;
+					let pos = db.position_at_time_plus_anomaly(handle, system_time.seconds, mean_anomaly_offset) * SCALE;
 					points.push((mean_anomaly_offset, parent_pos + pos));
 				}
 				for i in 0..points.len()-1 {
@@ -485,15 +461,15 @@ fn draw_orbits(
 					let (m_1, p_1) = points[i+1];
 					let t_0 = m_0 / TAU;
 					let t_1 = m_1 / TAU;
-					let c_0 = ORBIT_COLOR.with_alpha(t_0.powi(2));
-					let c_1 = ORBIT_COLOR.with_alpha(t_1.powi(2));
+					let c_0 = entry.suggested_color().with_alpha(t_0.powi(2));
+					let c_1 = entry.suggested_color().with_alpha(t_1.powi(2));
 					gizmos.line_gradient(p_0, p_1, c_0, c_1);
 				}
 				if camera_parent.view_apsis {
 					// draw apoapsis/periapsis
 					let pos_periapsis = db.position_at_mean_anomaly(handle, 0.0) * SCALE;
 					let pos_apoapsis = db.position_at_mean_anomaly(handle, PI) * SCALE;
-					let apsis_size = APSIS_SIZE_MIN.lerp(APSIS_SIZE_MAX, camera_parent.zoom.powf(3.0));
+					let apsis_size = APSIS_SIZE_MIN.lerp(APSIS_SIZE_MAX, zoom);
 					gizmos.sphere(pos_periapsis + parent_pos, apsis_size, PERIAPSIS_COLOR);
 					gizmos.sphere(pos_apoapsis + parent_pos, apsis_size, APOAPSIS_COLOR);
 				}
@@ -506,12 +482,15 @@ fn draw_planets(
 	mut gizmos: Gizmos,
 	db: Res<Database>, time: Res<SystemTime>,
 	camera_parents: Query<&CameraParent>,
+	cameras: Query<&OrbitalCamera<usize>>,
 ) {
 	let camera_parent = camera_parents.single();
+	let zoom = camera_zoom(cameras.single());
 	let centered_body = camera_parent.centered_body;
+	let positions = db.all_positions_relative_to(&centered_body, time.seconds);
 	for (handle, entry) in db.iter() {
 
-		let pos = db.relative_position(&centered_body, handle, time.seconds).unwrap() * SCALE;
+		let pos = positions[handle] * SCALE;
 		let info = entry.info.clone();
 		let rot = Quat::from_axis_angle(Vec3::X, info.axial_tilt_rad());
 		let iso = Isometry3d::new(pos, rot);
@@ -522,7 +501,7 @@ fn draw_planets(
 			gizmos.sphere(pos, soi_radius * SCALE, SOI_COLOR); // sphere of influence
 		}
 		if camera_parent.view_axes {
-			let axis_size = AXIS_SIZE_MIN.lerp(AXIS_SIZE_MAX, camera_parent.zoom.powi(3));
+			let axis_size = AXIS_SIZE_MIN.lerp(AXIS_SIZE_MAX, zoom);
 			gizmos.axes(iso, axis_size);
 		}
 	}
@@ -533,13 +512,14 @@ fn main() {
 		.add_plugins(DefaultPlugins)
 		.insert_resource(Database::default().with_solar_system())
 		.insert_resource(SystemTime::default())
+		.add_plugins(OrbitalCameraPlugin::<usize>::new(CAM_CENTERED_ON_DEFAULT, SCALE).with_distance_limits(CAM_MIN_DISTANCE, CAM_MAX_DISTANCE))
 		.add_systems(Startup, (setup_camera, setup_ui))
 		.add_systems(Update, (
 			process_visibility_input,
 			draw_orbits.after(process_visibility_input), draw_planets.after(process_visibility_input),
-			process_navigation_controls.before(update_camera_position),
-			process_camera_input.before(update_camera_position),
-			update_camera_position,
+			process_navigation_controls.before(sync_orbital_camera),
+			process_camera_input,
+			sync_orbital_camera.after(increment_time).after(process_time_controls).before(update_orbital_camera::<usize>),
 			update_controls_ui.after(process_visibility_input),
 			update_planet_focus_ui.after(process_navigation_controls),
 			update_time_display,
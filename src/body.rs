@@ -14,12 +14,28 @@ pub struct Body<T> {
     radius_polar_km: T,
 	/// Axial tilt of the body relative to its orbital plane
 	axial_tilt_deg: T,
+	/// Effective surface temperature of the body in kelvin (K)
+	surface_temperature_k: T,
+	/// Measured *GM* in m³/s², used instead of mass times *G* when set
+	gm_m3_s2: Option<T>,
+	/// Second zonal harmonic *J2* of the body's gravity field, from its equatorial bulge
+	j2: T,
+	/// Sidereal rotation period of the body in seconds (s), if known
+	rotation_period_s: Option<T>,
+	/// Geometric albedo of the body, the fraction of light it reflects back towards its source
+	albedo: T,
+	/// Bond albedo of the body, the fraction of all incoming light it reflects in every direction,
+	/// if known
+	bond_albedo: Option<T>,
 }
 impl<T> Body<T> where T: Float + FromPrimitive
 {
     /// Create a new body with the given mass and radius properties
     pub fn new(mass_kg: T, radius_equator_km: T, radius_polar_km: T, axial_tilt_deg: T) -> Self {
-        Self{ mass_kg: mass_kg, radius_equator_km, radius_polar_km, axial_tilt_deg }
+        let surface_temperature_k = T::from_f32(0.0).unwrap();
+        let j2 = T::from_f32(0.0).unwrap();
+        let albedo = T::from_f32(0.3).unwrap();
+        Self{ mass_kg: mass_kg, radius_equator_km, radius_polar_km, axial_tilt_deg, surface_temperature_k, gm_m3_s2: None, j2, rotation_period_s: None, albedo, bond_albedo: None }
     }
     /// Create a new body with the properties of [the planet Earth](https://en.wikipedia.org/wiki/Earth)
     pub fn new_earth() -> Self where T: FromPrimitive {
@@ -28,7 +44,10 @@ impl<T> Body<T> where T: Float + FromPrimitive
 			T::from_f64(constants::RADIUS_EARTH_EQUATOR_KM).unwrap(),
 			T::from_f64(constants::RADIUS_EARTH_POLAR_KM).unwrap(),
 			T::from_f64(23.4392811).unwrap(),
-		)
+		).with_j2(T::from_f64(constants::J2_EARTH).unwrap())
+			.with_rotation_period_s(T::from_f64(86164.0905).unwrap())
+			.with_albedo(T::from_f64(0.434).unwrap())
+			.with_bond_albedo(T::from_f64(0.306).unwrap())
     }
 	/// Create a new body with the properties of [our sun]()
 	pub fn new_sol() -> Self where T: FromPrimitive {
@@ -38,14 +57,66 @@ impl<T> Body<T> where T: Float + FromPrimitive
 			T::from_f64(constants::RADIUS_SUN_M * constants::CONVERT_M_TO_KM).unwrap(),
 			T::from_f64(constants::RADIUS_SUN_M * constants::CONVERT_M_TO_KM * flattening_factor).unwrap(),
 			T::from_f32(0.0).unwrap(),
-		)
+		).with_surface_temperature_k(T::from_f64(constants::TEMPERATURE_SUN_K).unwrap())
+			.with_rotation_period_s(T::from_f64(25.38 * 86_400.0).unwrap())
 	}
+	/// Create a new spherical body with the given radius in kilometers (km) and mean density in
+	/// kilograms per cubic meter (kg/m³), with its mass worked out from its volume
+	pub fn from_density(radius_km: T, density_kg_m3: T) -> Self {
+		let zero = T::from_f32(0.0).unwrap();
+		let radius_m = radius_km * T::from_f64(constants::CONVERT_KM_TO_M).unwrap();
+		let volume_m3 = T::from_f64(4.0 / 3.0 * std::f64::consts::PI).unwrap() * radius_m.powi(3);
+		Self::new(volume_m3 * density_kg_m3, radius_km, radius_km, zero)
+	}
+	/// Sets the mass, radius and surface temperature of this body to those of a typical main
+	/// sequence star of the given spectral class
+	/// 
+	/// [`SpectralClass::Custom`] leaves the body unchanged.
+	pub fn with_spectral_class(self, class: SpectralClass) -> Self {
+		let (mass_suns, radius_suns, temperature_k) = match class {
+			SpectralClass::O => (40.0, 12.0, 41_000.0),
+			SpectralClass::B => (5.9, 3.9, 15_700.0),
+			SpectralClass::A => (1.86, 1.7, 8_180.0),
+			SpectralClass::F => (1.33, 1.47, 6_510.0),
+			SpectralClass::G => (1.0, 1.0, constants::TEMPERATURE_SUN_K),
+			SpectralClass::K => (0.69, 0.74, 4_440.0),
+			SpectralClass::M => (0.16, 0.2, 3_060.0),
+			SpectralClass::Custom => return self,
+		};
+		self.with_mass_kg(T::from_f64(mass_suns * constants::MASS_SUN_KG).unwrap())
+			.with_radius_m(T::from_f64(radius_suns * constants::RADIUS_SUN_M).unwrap())
+			.with_surface_temperature_k(T::from_f64(temperature_k).unwrap())
+	}
+	/// Sets the mass of the body in kilograms (kg)
+	/// 
+	/// This also clears any *GM* set with [`with_gm_m3_s2`](Self::with_gm_m3_s2) or
+	/// [`with_gm_km3_s2`](Self::with_gm_km3_s2), so the body's *GM* goes back to being its mass
+	/// times *G*.
 	pub fn with_mass_kg(mut self, mass: T) -> Self {
 		self.mass_kg = mass;
+		self.gm_m3_s2 = None;
 		self
 	}
 	pub fn with_mass_earths(mut self, mass: T) -> Self {
 		self.mass_kg = mass * T::from_f64(constants::CONVERT_EARTH_MASS_TO_KG).unwrap();
+		self.gm_m3_s2 = None;
+		self
+	}
+	/// Sets the body's *GM* directly in km³/s², the unit most ephemerides use
+	/// 
+	/// The *GM* of a body is usually measured far more precisely than its mass, especially for
+	/// small bodies, so this value is used for all orbital math in place of the mass times *G*.
+	/// The mass itself is left unchanged.
+	pub fn with_gm_km3_s2(mut self, gm: T) -> Self {
+		self.gm_m3_s2 = Some(gm * T::from_f64(constants::CONVERT_KM_TO_M.powi(3)).unwrap());
+		self
+	}
+	/// Sets the body's *GM* directly in m³/s², like the canonical `3.986004418e14` for Earth
+	/// 
+	/// Like [`with_gm_km3_s2`](Self::with_gm_km3_s2) this is returned exactly by
+	/// [`gm`](Self::gm) in place of the mass times *G*, and leaves the mass unchanged.
+	pub fn with_gm_m3_s2(mut self, gm: T) -> Self {
+		self.gm_m3_s2 = Some(gm);
 		self
 	}
 	/// Sets both the polar and equatorial radius to the given value
@@ -69,6 +140,34 @@ impl<T> Body<T> where T: Float + FromPrimitive
 		self.axial_tilt_deg = axial_tilt;
 		self
 	}
+	/// Sets the second zonal harmonic *J2* of the body's gravity field
+	pub fn with_j2(mut self, j2: T) -> Self {
+		self.j2 = j2;
+		self
+	}
+	/// Sets the body's sidereal rotation period in seconds (s), the time it takes to turn once
+	/// relative to the distant stars
+	pub fn with_rotation_period_s(mut self, period: T) -> Self {
+		self.rotation_period_s = Some(period);
+		self
+	}
+	/// Sets the body's geometric albedo, from 0 for a perfectly dark body to around 1 for a bright
+	/// icy one. Bodies default to an albedo of 0.3
+	pub fn with_albedo(mut self, albedo: T) -> Self {
+		self.albedo = albedo;
+		self
+	}
+	/// Sets the body's Bond albedo, the fraction of all the light falling on it that it reflects,
+	/// which sets how much it's heated by its star
+	pub fn with_bond_albedo(mut self, albedo: T) -> Self {
+		self.bond_albedo = Some(albedo);
+		self
+	}
+	/// Sets the effective surface temperature of the body in kelvin (K)
+	pub fn with_surface_temperature_k(mut self, temperature: T) -> Self {
+		self.surface_temperature_k = temperature;
+		self
+	}
     /// Gets the mass of this body in kilograms, *kg*
     pub fn mass_kg(&self) -> T {
         self.mass_kg
@@ -91,28 +190,172 @@ impl<T> Body<T> where T: Float + FromPrimitive
     pub fn radius_equator_m(&self) -> T {
         self.radius_equator_km * T::from_f64(constants::CONVERT_KM_TO_M).unwrap()
     }
-    /// Calculates the body's *GM*, its mass times the Gravitational Constant *G*
+    /// Calculates the body's *GM*, its mass times the Gravitational Constant *G*, in m³/s²
+    /// 
+    /// If the *GM* was set directly with [`with_gm_m3_s2`](Self::with_gm_m3_s2) or
+    /// [`with_gm_km3_s2`](Self::with_gm_km3_s2), that value is returned instead.
     pub fn gm(&self) -> T {
-        self.mass_kg * T::from_f64(constants::CONST_G).unwrap()
+        match self.gm_m3_s2 {
+            Some(gm) => gm,
+            None => self.mass_kg * T::from_f64(constants::CONST_G).unwrap(),
+        }
     }
+	/// Gets the body's *GM* in km³/s²
+	pub fn gm_km3_s2(&self) -> T {
+		self.gm() * T::from_f64(constants::CONVERT_M_TO_KM.powi(3)).unwrap()
+	}
 	/// Returns the distance at which the force of gravity equals the given value
 	/// 
 	/// d = sqrt(GM/F)
 	pub fn distance_of_gravity(&self, gravity: T) -> T {
-		let g = T::from_f64(constants::CONST_G).unwrap();
-		((g * self.mass_kg) / gravity).sqrt()
+		(self.gm() / gravity).sqrt()
 	}
 	/// Calculate the force of gravity towards this body at the given distance
 	/// 
 	/// F = GM/d^2
 	pub fn gravity_at_distance(&self, distance: T) -> T {
-		let g = T::from_f64(constants::CONST_G).unwrap();
-		(g * self.mass_kg) / distance.powi(2)
+		self.gm() / distance.powi(2)
+	}
+	/// Calculates the period of an orbit skimming this body's equator, in seconds
+	/// 
+	/// T = 2π·sqrt(r³/GM)
+	/// 
+	/// No orbit around the body can be faster than this, and a body spinning faster than it would
+	/// throw material off its equator, see [`rotation_breakup_check`](Self::rotation_breakup_check).
+	pub fn minimum_orbital_period_s(&self) -> T {
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		tau * (self.radius_equator_m().powi(3) / self.gm()).sqrt()
+	}
+	/// Checks whether a body rotating once every `rotation_period_s` seconds would spin faster than
+	/// its [minimum orbital period](Self::minimum_orbital_period_s) and fly apart
+	pub fn rotation_breakup_check(&self, rotation_period_s: T) -> bool {
+		rotation_period_s < self.minimum_orbital_period_s()
+	}
+	/// Roughly classifies this body by its mass, see [`BodyClass`] for the thresholds used
+	pub fn body_class(&self) -> BodyClass {
+		let mass_kg = self.mass_kg.to_f64().unwrap();
+		if mass_kg >= 0.075 * constants::MASS_SUN_KG {
+			BodyClass::Star
+		} else if mass_kg >= 10.0 * constants::MASS_EARTH_KG {
+			BodyClass::GasGiant
+		} else if mass_kg >= 0.05 * constants::MASS_EARTH_KG {
+			BodyClass::Terrestrial
+		} else if mass_kg >= 1e21 {
+			BodyClass::Moon
+		} else {
+			BodyClass::SmallBody
+		}
 	}
 	/// Returns this body's axial tilt in radians
 	pub fn axial_tilt_rad(&self) -> T {
 		self.axial_tilt_deg * T::from_f64(constants::CONVERT_DEG_TO_RAD).unwrap()
 	}
+	/// Gets the second zonal harmonic *J2* of this body's gravity field, which is zero unless it
+	/// was set with [`with_j2`](Self::with_j2)
+	pub fn j2(&self) -> T {
+		self.j2
+	}
+	/// Gets the body's sidereal rotation period in seconds (s), if it was set with
+	/// [`with_rotation_period_s`](Self::with_rotation_period_s)
+	pub fn rotation_period_s(&self) -> Option<T> {
+		self.rotation_period_s
+	}
+	/// Gets the body's *J2* if it was set, or otherwise estimates it from the body's shape
+	/// 
+	/// The estimate uses the first order relation *J2 ≈ (2f - q)/3* between the flattening
+	/// *f = (r_eq - r_polar)/r_eq* and the ratio *q = ω²r_eq³/GM* of the centrifugal force to
+	/// gravity at the equator, taking *q* as zero if the body's rotation period isn't known.
+	pub fn estimated_j2(&self) -> T {
+		let zero = T::from_f32(0.0).unwrap();
+		if self.j2 != zero {
+			return self.j2;
+		}
+		if self.radius_equator_km == zero {
+			return zero;
+		}
+		let two = T::from_f32(2.0).unwrap();
+		let three = T::from_f32(3.0).unwrap();
+		let flattening = (self.radius_equator_km - self.radius_polar_km) / self.radius_equator_km;
+		let centrifugal_ratio = match self.rotation_period_s {
+			Some(period) => {
+				let angular_velocity = T::from_f64(std::f64::consts::TAU).unwrap() / period;
+				angular_velocity.powi(2) * self.radius_equator_m().powi(3) / self.gm()
+			},
+			None => zero,
+		};
+		((two * flattening - centrifugal_ratio) / three).max(zero)
+	}
+	/// Converts a geodetic latitude in radians, measured from the normal to the body's surface, to
+	/// the geocentric latitude in radians measured from the body's center
+	/// 
+	/// Uses *tan(geocentric) = (1 - f)² tan(geodetic)* with the flattening *f* from the body's
+	/// equatorial and polar radii, so both latitudes are the same on a spherical body.
+	pub fn geodetic_to_geocentric_lat(&self, lat: T) -> T {
+		let ratio = self.polar_equator_ratio_squared();
+		Float::atan2(ratio * Float::sin(lat), Float::cos(lat))
+	}
+	/// Converts a geocentric latitude in radians to the geodetic latitude in radians, the inverse
+	/// of [`geodetic_to_geocentric_lat`](Self::geodetic_to_geocentric_lat)
+	pub fn geocentric_to_geodetic_lat(&self, lat: T) -> T {
+		let ratio = self.polar_equator_ratio_squared();
+		Float::atan2(Float::sin(lat), ratio * Float::cos(lat))
+	}
+	/// Gets *(1 - f)²*, the square of the body's polar radius over its equatorial radius
+	fn polar_equator_ratio_squared(&self) -> T {
+		if self.radius_equator_km == T::from_f32(0.0).unwrap() {
+			return T::from_f32(1.0).unwrap();
+		}
+		(self.radius_polar_km / self.radius_equator_km).powi(2)
+	}
+	/// Gets the body's geometric albedo
+	pub fn albedo(&self) -> T {
+		self.albedo
+	}
+	/// Gets the body's Bond albedo if it was set with
+	/// [`with_bond_albedo`](Self::with_bond_albedo), or otherwise its [geometric albedo](Self::albedo)
+	/// as a stand in
+	pub fn bond_albedo(&self) -> T {
+		self.bond_albedo.unwrap_or(self.albedo)
+	}
+	/// Gets the effective surface temperature of this body in kelvin (K)
+	pub fn surface_temperature_k(&self) -> T {
+		self.surface_temperature_k
+	}
+	/// Calculates the luminosity of this body in watts (W), treating it as a black body
+	/// 
+	/// L = 4πr²σT⁴
+	pub fn luminosity_w(&self) -> T {
+		let four_pi = T::from_f64(4.0 * std::f64::consts::PI).unwrap();
+		let sigma = T::from_f64(constants::CONST_STEFAN_BOLTZMANN).unwrap();
+		four_pi * self.radius_equator_m().powi(2) * sigma * self.surface_temperature_k.powi(4)
+	}
+}
+
+/// Spectral classes of main sequence stars, used to quickly create stars with typical properties
+/// 
+/// See [`Body::with_spectral_class`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpectralClass {
+	O, B, A, F, G, K, M,
+	/// A star with properties that don't follow from its spectral class
+	Custom,
+}
+/// Rough kind of a body, see [`Body::body_class`]
+/// 
+/// Bodies are classified only by their mass, without looking at what they orbit, so a large dwarf
+/// planet like Pluto counts as a moon and a small moon like Phobos counts as a small body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BodyClass {
+	/// At least 0.075 solar masses, enough to fuse hydrogen
+	Star,
+	/// At least 10 Earth masses
+	GasGiant,
+	/// At least 0.05 Earth masses
+	Terrestrial,
+	/// At least 10²¹ kg, a bit more than Ceres
+	Moon,
+	/// Dwarf planets, asteroids and comets
+	SmallBody,
 }
 impl<T> Default for Body<T> where T: Float + FromPrimitive {
 	fn default() -> Self {
@@ -125,11 +368,83 @@ impl<T> Default for Body<T> where T: Float + FromPrimitive {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f64::consts::FRAC_PI_2;
     use approx::assert_ulps_eq;
 
 	#[test]
 	fn gm() {
 		assert_ulps_eq!(3.986005e14, Body::new_earth().gm(), epsilon = 2000000.0);
+		let earth: Body<f64> = Body::new_earth().with_gm_m3_s2(3.986004418e14);
+		assert_eq!(3.986004418e14, earth.gm());
+		assert_eq!(constants::MASS_EARTH_KG, earth.mass_kg());
+		// setting the mass goes back to mass times G
+		assert_ne!(3.986004418e14, earth.with_mass_kg(constants::MASS_EARTH_KG).gm());
+	}
+
+	#[test]
+	fn gm_km3_s2() {
+		let earth: Body<f64> = Body::new_earth();
+		assert_ulps_eq!(earth.gm() / 1e9, earth.gm_km3_s2());
+		let ceres: Body<f64> = Body::default().with_mass_kg(9.38e20).with_gm_km3_s2(62.6284);
+		assert_ulps_eq!(62.6284, ceres.gm_km3_s2(), epsilon=1e-12);
+		assert_ulps_eq!(62.6284e9, ceres.gm());
+		assert_ulps_eq!(9.38e20, ceres.mass_kg());
+		assert_ulps_eq!(62.6284e9 / 1e6, ceres.gravity_at_distance(1000.0), epsilon=1e-9);
+		let reset = ceres.with_mass_kg(9.38e20);
+		assert_ulps_eq!(9.38e20 * constants::CONST_G, reset.gm());
+	}
+
+	#[test]
+	fn from_density() {
+		let earth: Body<f64> = Body::from_density(constants::RADIUS_EARTH_MEAN_KM, 5514.0);
+		assert_ulps_eq!(constants::MASS_EARTH_KG, earth.mass_kg(), epsilon=constants::MASS_EARTH_KG * 0.005);
+		assert_eq!(constants::RADIUS_EARTH_MEAN_KM, earth.radius_equator_km());
+		assert_eq!(constants::RADIUS_EARTH_MEAN_KM, earth.radius_polar_km());
+	}
+
+	#[test]
+	fn minimum_orbital_period() {
+		let earth: Body<f64> = Body::new_earth();
+		assert_ulps_eq!(5100.0, earth.minimum_orbital_period_s(), epsilon=100.0);
+		assert!(!earth.rotation_breakup_check(86_164.0));
+		assert!(earth.rotation_breakup_check(3600.0));
+	}
+
+	#[test]
+	fn body_class() {
+		assert_eq!(BodyClass::Star, Body::<f64>::new_sol().body_class());
+		assert_eq!(BodyClass::Star, Body::<f64>::default().with_spectral_class(SpectralClass::M).body_class());
+		assert_eq!(BodyClass::GasGiant, Body::<f64>::default().with_mass_earths(17.1).body_class());
+		assert_eq!(BodyClass::Terrestrial, Body::<f64>::new_earth().body_class());
+		assert_eq!(BodyClass::Terrestrial, Body::<f64>::default().with_mass_earths(0.055).body_class());
+		assert_eq!(BodyClass::Moon, Body::<f64>::default().with_mass_kg(7.342e22).body_class());
+		assert_eq!(BodyClass::SmallBody, Body::<f64>::default().with_mass_kg(9.38e20).body_class());
+	}
+
+	#[test]
+	fn estimated_j2() {
+		let earth: Body<f64> = Body::new_earth();
+		assert_eq!(constants::J2_EARTH, earth.estimated_j2());
+		// jupiter's measured J2 is 0.014736
+		let jupiter: Body<f64> = Body::default()
+			.with_mass_kg(1.8982e27)
+			.with_radii_km(71492.0, 66854.0)
+			.with_rotation_period_s(35_730.0);
+		assert_ulps_eq!(0.014736, jupiter.estimated_j2(), epsilon=0.002);
+		assert_eq!(0.0, Body::<f64>::default().with_mass_kg(1e20).with_radius_km(100.0).estimated_j2());
+	}
+
+	#[test]
+	fn geodetic_latitude() {
+		let earth: Body<f64> = Body::new_earth();
+		let geodetic = 45.0f64.to_radians();
+		let geocentric = earth.geodetic_to_geocentric_lat(geodetic);
+		// the geocentric latitude is about 11.5 arcminutes closer to the equator
+		assert_ulps_eq!(11.5, (geodetic - geocentric).to_degrees() * 60.0, epsilon=0.1);
+		assert_ulps_eq!(geodetic, earth.geocentric_to_geodetic_lat(geocentric), epsilon=1e-12);
+		// the poles and equator are the same in both systems
+		assert_ulps_eq!(FRAC_PI_2, earth.geodetic_to_geocentric_lat(FRAC_PI_2), epsilon=1e-12);
+		assert_eq!(0.0, earth.geocentric_to_geodetic_lat(0.0));
 	}
 
 	#[test]
@@ -140,6 +455,14 @@ mod tests {
 		assert_ulps_eq!(surface_altitude as f32, earth.distance_of_gravity(9.81), epsilon=5000.0);
 	}
 
+	#[test]
+	fn bond_albedo() {
+		let body: Body<f64> = Body::default().with_albedo(0.5);
+		assert_eq!(0.5, body.bond_albedo());
+		assert_eq!(0.2, body.with_bond_albedo(0.2).bond_albedo());
+		assert_eq!(0.306, Body::<f64>::new_earth().bond_albedo());
+	}
+
 	#[test]
 	fn sun_sphere_of_influence() {
 		let sun: Body<f32> = Body::new_sol();
@@ -149,4 +472,19 @@ mod tests {
 		let minimum_au = 100.0; // distance of heliopause
 		assert!(minimum_au < distance_au, "Expected distance of gravity to be greater than {:.2} AU, but {:.2} AU was returned", minimum_au, distance_au);
 	}
+
+	#[test]
+	fn spectral_class() {
+		let star: Body<f64> = Body::default().with_spectral_class(SpectralClass::G);
+		let luminosity_sun = constants::LUMINOSITY_SUN_W;
+		assert_ulps_eq!(luminosity_sun, star.luminosity_w(), epsilon=luminosity_sun * 0.05);
+		let sun: Body<f64> = Body::new_sol();
+		assert_ulps_eq!(sun.mass_kg(), star.mass_kg());
+		assert_ulps_eq!(sun.radius_equator_km(), star.radius_equator_km());
+		assert_ulps_eq!(sun.luminosity_w(), star.luminosity_w(), epsilon=luminosity_sun * 0.001);
+		let hot_star: Body<f64> = Body::default().with_spectral_class(SpectralClass::B);
+		assert!(hot_star.luminosity_w() > star.luminosity_w());
+		let custom: Body<f64> = Body::new_earth().with_spectral_class(SpectralClass::Custom);
+		assert_ulps_eq!(constants::MASS_EARTH_KG, custom.mass_kg());
+	}
 }
\ No newline at end of file
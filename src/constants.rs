@@ -35,10 +35,17 @@ pub mod f64 {
 	pub const CONST_GRAVITATION: f64 = 6.6743015e-11;
 	/// Gravitational Constant *G* (N * m ^ 2 / kg ^ 2)
 	pub const CONST_G: f64 = CONST_GRAVITATION;
+	/// Stefan-Boltzmann constant *σ* (W / m ^ 2 / K ^ 4)
+	pub const CONST_STEFAN_BOLTZMANN: f64 = 5.670374419e-8;
+	/// Speed of light in a vacuum *c* (m / s)
+	pub const SPEED_OF_LIGHT_M_PER_S: f64 = 299_792_458.0;
 
 	pub const CONVERT_AU_TO_KM: f64 = CONVERT_AU_TO_M * CONVERT_M_TO_KM;
 	pub const CONVERT_AU_TO_M: f64 = 149597870700.0;
 	pub const CONVERT_KM_TO_AU: f64 = 1.0 / CONVERT_AU_TO_KM;
+	pub const CONVERT_LIGHT_SECOND_TO_M: f64 = SPEED_OF_LIGHT_M_PER_S;
+	/// Uses the Julian year of 365.25 days
+	pub const CONVERT_LIGHT_YEAR_TO_M: f64 = SPEED_OF_LIGHT_M_PER_S * 365.25 * 86_400.0;
 	pub const CONVERT_DEG_TO_RAD: f64 = TAU / 360.0;
 	pub const CONVERT_RAD_TO_DEG: f64 = 360.0 / TAU;
 	pub const CONVERT_KM_TO_M: f64 = 1000.0;
@@ -53,6 +60,10 @@ pub mod f64 {
 	pub const RADIUS_SUN_M: f64 = 6.957e8;
 	pub const MASS_EARTH_KG: f64 = 5.972168e24;
 	pub const MASS_SUN_KG: f64 = 1.9885e30;
+	pub const LUMINOSITY_SUN_W: f64 = 3.828e26;
+	pub const TEMPERATURE_SUN_K: f64 = 5772.0;
+	/// Second zonal harmonic of Earth's gravity field, from its equatorial bulge
+	pub const J2_EARTH: f64 = 1.08262668e-3;
 }
 
 pub mod f32 {
@@ -0,0 +1,166 @@
+//! Sanity checks over the bodies bundled with [`Database::add_solar_system`]
+//!
+//! The bundled data was hand copied from several different sources, so these tests are here to
+//! catch data entry mistakes like a misplaced decimal point, a value in the wrong units or an angle
+//! that was never wrapped into a single turn, rather than to check the data is accurate. The
+//! periods only need to be within an order of magnitude of the real values.
+
+use crate::{Database, handles::*};
+
+
+/// Real sidereal orbital periods of every bundled body in days
+const KNOWN_PERIODS_DAYS: &[(u16, f64)] = &[
+	(HANDLE_MERCURY, 87.969),
+	(HANDLE_VENUS, 224.701),
+	(HANDLE_EARTH, 365.256),
+	(HANDLE_LUNA, 27.322),
+	(HANDLE_MARS, 686.98),
+	(HANDLE_PHOBOS, 0.3189),
+	(HANDLE_DEIMOS, 1.2624),
+	(HANDLE_JUPITER, 4332.59),
+	(HANDLE_IO, 1.769),
+	(HANDLE_EUROPA, 3.551),
+	(HANDLE_GANYMEDE, 7.155),
+	(HANDLE_CALLISTO, 16.689),
+	(HANDLE_AMALTHEA, 0.498),
+	(HANDLE_HIMALIA, 250.56),
+	(HANDLE_ELARA, 259.64),
+	(HANDLE_PASIPHAE, 743.6),
+	(HANDLE_SINOPE, 758.9),
+	(HANDLE_LYSITHEA, 259.2),
+	(HANDLE_CARME, 734.2),
+	(HANDLE_ANANKE, 629.8),
+	(HANDLE_LEDA, 240.9),
+	(HANDLE_THEBE, 0.6745),
+	(HANDLE_ADRASTEA, 0.2983),
+	(HANDLE_METIS, 0.2948),
+	(HANDLE_CALLIRHOE, 758.8),
+	(HANDLE_THEMISTO, 130.0),
+	(HANDLE_CARPO, 456.1),
+	(HANDLE_EIRENE, 729.0),
+	(HANDLE_PHILOPHROSYNE, 690.0),
+	(HANDLE_EUPHEME, 617.0),
+	(HANDLE_VALETUDO, 533.0),
+	(HANDLE_PANDIA, 252.0),
+	(HANDLE_ERSA, 250.0),
+	(HANDLE_S_2011_J_1, 580.7),
+	(HANDLE_SATURN, 10759.22),
+	(HANDLE_MIMAS, 0.942),
+	(HANDLE_ENCELADUS, 1.370),
+	(HANDLE_TETHYS, 1.888),
+	(HANDLE_DIONE, 2.737),
+	(HANDLE_RHEA, 4.518),
+	(HANDLE_TITAN, 15.945),
+	(HANDLE_HYPERION, 21.28),
+	(HANDLE_IAPETUS, 79.32),
+	(HANDLE_PHOEBE, 550.3),
+	(HANDLE_JANUS, 0.695),
+	(HANDLE_GEIRROD, 1212.0),
+	(HANDLE_URANUS, 30688.5),
+	(HANDLE_ARIEL, 2.520),
+	(HANDLE_UMBRIEL, 4.144),
+	(HANDLE_TITANIA, 8.706),
+	(HANDLE_OBERON, 13.46),
+	(HANDLE_MIRANDA, 1.413),
+	(HANDLE_CUPID, 0.618),
+	(HANDLE_NEPTUNE, 60182.0),
+	(HANDLE_TRITON, 5.877),
+	(HANDLE_NEREID, 360.1),
+	(HANDLE_NAIAD, 0.294),
+	(HANDLE_THALASSA, 0.311),
+	(HANDLE_DESPINA, 0.335),
+	(HANDLE_GALATEA, 0.429),
+	(HANDLE_LARISSA, 0.555),
+	(HANDLE_PROTEUS, 1.122),
+	(HANDLE_HALIMEDE, 1879.0),
+	(HANDLE_PSAMATHE, 9074.0),
+	(HANDLE_SAO, 2914.0),
+	(HANDLE_LAOMEDEIA, 3167.0),
+	(HANDLE_NESO, 9741.0),
+	(HANDLE_HIPPOCAMP, 0.950),
+	(HANDLE_PLUTO, 90560.0),
+	(HANDLE_ERIS, 203830.0),
+	(HANDLE_DYSNOMIA, 15.786),
+	(HANDLE_HAUMEA, 103410.0),
+	(HANDLE_HIIAKA, 49.12),
+	(HANDLE_NAMAKA, 18.28),
+];
+
+fn solar_system() -> Database<u16, f64> {
+	Database::default().with_solar_system()
+}
+
+#[test]
+fn eccentricities() {
+	let database = solar_system();
+	for (_, entry) in database.iter() {
+		if let Some(orbit) = &entry.orbit {
+			assert!((0.0..1.0).contains(&orbit.eccentricity), "{} has an eccentricity of {}", entry.name, orbit.eccentricity);
+		}
+	}
+}
+
+#[test]
+fn semimajor_axes() {
+	let database = solar_system();
+	for (_, entry) in database.iter() {
+		if let Some(orbit) = &entry.orbit {
+			assert!(orbit.semimajor_axis > 0.0, "{} has a semimajor axis of {} m", entry.name, orbit.semimajor_axis);
+		}
+	}
+}
+
+#[test]
+fn spheres_of_influence() {
+	let database = solar_system();
+	for (handle, entry) in database.iter() {
+		if let Some(parent) = &entry.parent {
+			let soi = database.radius_soi(handle);
+			let parent_soi = database.radius_soi(parent);
+			assert!(soi < parent_soi, "{}'s sphere of influence of {:.0} km is larger than its parent's of {:.0} km", entry.name, soi / 1000.0, parent_soi / 1000.0);
+		}
+	}
+}
+
+#[test]
+fn periods() {
+	let database = solar_system();
+	let mut mistakes = Vec::new();
+	for (handle, entry) in database.iter() {
+		if entry.orbit.is_none() {
+			continue;
+		}
+		let known_days = KNOWN_PERIODS_DAYS.iter()
+			.find(|(known_handle, _)| known_handle == handle)
+			.unwrap_or_else(|| panic!("No known period for {}", entry.name)).1;
+		let days = database.orbital_period_s(handle).unwrap() / 86_400.0;
+		if !(0.1..10.0).contains(&(days / known_days)) {
+			mistakes.push(format!("{} orbits every {:.3} days, expected about {:.3} days", entry.name, days, known_days));
+		}
+	}
+	assert!(mistakes.is_empty(), "{}", mistakes.join("\n"));
+}
+
+#[test]
+fn angles() {
+	let database = solar_system();
+	let mut mistakes = Vec::new();
+	for (_, entry) in database.iter() {
+		let Some(orbit) = &entry.orbit else {
+			continue;
+		};
+		let angles = [
+			("inclination", orbit.inclination),
+			("argument of periapsis", orbit.arg_of_periapsis),
+			("longitude of ascending node", orbit.long_of_ascending_node),
+			("mean anomaly at epoch", entry.mean_anomaly_at_epoch),
+		];
+		for (name, angle) in angles {
+			let degrees = angle.to_degrees();
+			if !(0.0..360.0).contains(&degrees) {
+				mistakes.push(format!("{}'s {} is {:.4}°", entry.name, name, degrees));
+			}
+		}
+	}
+	assert!(mistakes.is_empty(), "{}", mistakes.join("\n"));
+}
@@ -3,9 +3,9 @@ use std::{
 	fmt::{Debug, Display},
 	hash::Hash, ops::SubAssign
 };
-use nalgebra::{RealField, Rotation3, SimdRealField, SimdValue, Vector3};
-use num_traits::{Float, FromPrimitive};
-use crate::{constants::f64::CONVERT_DEG_TO_RAD, Body, OrbitalElements};
+use nalgebra::{Matrix3, RealField, Rotation3, SimdRealField, SimdValue, Vector3};
+use num_traits::{Float, FromPrimitive, ToPrimitive};
+use crate::{constants::f64::{CONST_G, CONST_STEFAN_BOLTZMANN, CONVERT_DEG_TO_RAD, CONVERT_KM_TO_M, CONVERT_M_TO_KM, SPEED_OF_LIGHT_M_PER_S}, mean_motion, solve_lambert, spatial::SpatialIndex, Body, OrbitError, OrbitalElements, RelativePositionError};
 
 #[cfg(feature="bevy")]
 use bevy::prelude::*;
@@ -100,6 +100,15 @@ pub mod handles {
 #[cfg_attr(feature="bevy", derive(Resource))]
 pub struct Database<H, T> {
 	bodies: HashMap<H, DatabaseEntry<H, T>>,
+	coordinate_convention: CoordinateConvention,
+	reference_plane: ReferencePlane,
+	/// Rotation from the ecliptic onto the chosen reference plane, if it isn't the ecliptic
+	reference_plane_rotation: Option<Rotation3<T>>,
+	/// Snapshot of body positions used to speed up proximity queries, if one has been built
+	spatial_index: Option<SpatialIndex<H, T>>,
+	/// Whether [`position_at_time`](Self::position_at_time) offsets bodies by the pull of their
+	/// satellites, see [`primary_wobble_at_time`](Self::primary_wobble_at_time)
+	barycentric_wobble: bool,
 }
 impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone + Float + FromPrimitive + SubAssign {
 	/// populates the database with celestial bodies from our solar system
@@ -108,7 +117,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 	/// orbits and the bodies exact position along the orbit are not necessarily accurate to real
 	/// life, especially with moons of giant planets. This will be corrected eventually but for now
 	/// it's enough that the planets have eccentrity and inclination at all, that are authentic to
-	/// real life even if they're not strictly perfectly accurate.
+	/// real life even if they're not strictly perfectly accurate. The plane those moons' orbits
+	/// really precess around can be found with [`laplace_plane_normal`](Self::laplace_plane_normal).
 	pub fn add_solar_system(&mut self) {
 		self.add_sol();
 		self.add_mercury();
@@ -127,7 +137,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 	/// orbits and the bodies exact position along the orbit are not necessarily accurate to real
 	/// life, especially with moons of giant planets. This will be corrected eventually but for now
 	/// it's enough that the planets have eccentrity and inclination at all, that are authentic to
-	/// real life even if they're not strictly perfectly accurate.
+	/// real life even if they're not strictly perfectly accurate. The plane those moons' orbits
+	/// really precess around can be found with [`laplace_plane_normal`](Self::laplace_plane_normal).
 	pub fn with_solar_system(mut self) -> Self {
 		self.add_solar_system();
 		self
@@ -152,7 +163,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 		let mercury_info: Body<T> = Body::default()
 			.with_mass_kg(T::from_f64(3.3011e23).unwrap())
 			.with_radii_km(mean_radius_km * equator_factor, mean_radius_km * polar_factor)
-			.with_axial_tilt_deg(T::from_f64(0.034).unwrap());
+			.with_axial_tilt_deg(T::from_f64(0.034).unwrap())
+			.with_rotation_period_s(T::from_f64(5_067_014.0).unwrap());
 		let mercury_orbit: OrbitalElements<T> = OrbitalElements::default()
 			.with_semimajor_axis_km(T::from_f64(5.791e7).unwrap())
 			.with_eccentricity(T::from_f64(0.205630).unwrap())
@@ -171,7 +183,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 		let venus_info: Body<T> = Body::default()
 			.with_mass_kg(T::from_f64(4.8675e24).unwrap())
 			.with_radius_km(T::from_f64(6051.8).unwrap())
-			.with_axial_tilt_deg(T::from_f64(177.36).unwrap());
+			.with_axial_tilt_deg(T::from_f64(177.36).unwrap())
+			.with_rotation_period_s(T::from_f64(20_997_360.0).unwrap());
 		let venus_orbit: OrbitalElements<T> = OrbitalElements::default()
 			.with_semimajor_axis_km(T::from_f64(1.0821e8).unwrap())
 			.with_eccentricity(T::from_f64(0.006772).unwrap())
@@ -193,7 +206,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_eccentricity(T::from_f64(0.0167086).unwrap())
 			.with_inclination_deg(T::from_f64(0.00005).unwrap())
 			.with_arg_of_periapsis_deg(T::from_f64(114.20783).unwrap())
-			.with_long_of_ascending_node_deg(T::from_f64(-11.26064).unwrap());
+			.with_long_of_ascending_node_deg(T::from_f64(348.73936).unwrap());
 		let earth_entry = DatabaseEntry::new(earth_info, "Earth")
 			.with_parent(sun_handle.clone(), earth_orbit)
 			.with_mean_anomaly_deg(T::from_f64(358.617).unwrap());
@@ -201,13 +214,14 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 		let moon_handle = H::from_u16(handles::HANDLE_LUNA).unwrap();
 		let moon_info: Body<T> = Body::default()
 			.with_mass_kg(T::from_f64(7.346e22).unwrap())
-			.with_radius_km(T::from_f64(1737.4).unwrap());
+			.with_radius_km(T::from_f64(1737.4).unwrap())
+			.with_rotation_period_s(T::from_f64(2_360_591.5).unwrap());
 		let moon_orbit: OrbitalElements<T> = OrbitalElements::default()
 			.with_semimajor_axis_km(T::from_f64(384_399.0).unwrap())
 			.with_eccentricity(T::from_f64(0.0549).unwrap())
-			.with_inclination_deg(T::from_f64(-18.294).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(114.20783).unwrap())
-			.with_long_of_ascending_node_deg(T::from_f64(-11.26064).unwrap());
+			.with_inclination_deg(T::from_f64(18.294).unwrap())
+			.with_arg_of_periapsis_deg(T::from_f64(294.20783).unwrap())
+			.with_long_of_ascending_node_deg(T::from_f64(168.73936).unwrap());
 		let moon_entry = DatabaseEntry::new(moon_info, "Luna")
 			.with_parent(earth_handle.clone(), moon_orbit)
 			.with_mean_anomaly_deg(T::from_f64(90.0).unwrap());
@@ -221,7 +235,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 		let mars_info: Body<T> = Body::default()
 			.with_mass_kg(T::from_f64(6.4171e23).unwrap())
 			.with_radii_km(T::from_f64(3396.2).unwrap(), T::from_f64(3376.2).unwrap())
-			.with_axial_tilt_deg(T::from_f64(25.19).unwrap());
+			.with_axial_tilt_deg(T::from_f64(25.19).unwrap())
+			.with_rotation_period_s(T::from_f64(88_642.66).unwrap());
 		let mars_orbit: OrbitalElements<T> = OrbitalElements::default()
 			.with_semimajor_axis_km(T::from_f64(227_939_366.0).unwrap())
 			.with_eccentricity(T::from_f64(0.0934).unwrap())
@@ -241,7 +256,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_semimajor_axis_km(T::from_f64(9376.0).unwrap())
 			.with_eccentricity(T::from_f64(0.0151).unwrap())
 			.with_inclination_deg(T::from_f64(1.093).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(381.5236635).unwrap())
+			.with_arg_of_periapsis_deg(T::from_f64(21.5236635).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(83.14323972).unwrap());
 		let phobos_entry = DatabaseEntry::new(phobos_info, "Phobos")
 			.with_parent(mars_handle.clone(), phobos_orbit)
@@ -256,7 +271,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_semimajor_axis_km(T::from_f64(23463.2).unwrap())
 			.with_eccentricity(T::from_f64(0.00033).unwrap())
 			.with_inclination_deg(T::from_f64(0.93).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(386.1935449).unwrap())
+			.with_arg_of_periapsis_deg(T::from_f64(26.1935449).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(80.97357149).unwrap());
 		let deimos_entry = DatabaseEntry::new(deimos_info, "Deimos")
 			.with_parent(mars_handle.clone(), deimos_orbit)
@@ -273,7 +288,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 		let jupiter_info: Body<T> = Body::default()
 			.with_mass_kg(T::from_f64(1.8982e27).unwrap())
 			.with_radii_km(T::from_f64(71492.0).unwrap(), T::from_f64(66854.0).unwrap())
-			.with_axial_tilt_deg(T::from_f64(3.13).unwrap());
+			.with_axial_tilt_deg(T::from_f64(3.13).unwrap())
+			.with_rotation_period_s(T::from_f64(35_730.0).unwrap());
 		let jupiter_orbit: OrbitalElements<T> = OrbitalElements::default()
 			.with_semimajor_axis_au(T::from_f64(5.2038).unwrap())
 			.with_eccentricity(T::from_f64(0.0489).unwrap())
@@ -293,8 +309,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_semimajor_axis_m(T::from_f64(422025278.692653).unwrap())
 			.with_eccentricity(T::from_f64(0.00418867166362767).unwrap())
 			.with_inclination_deg(T::from_f64(0.05).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(654.3518983).unwrap())
-			.with_long_of_ascending_node_deg(T::from_f64(737.1542087).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(294.3518983).unwrap())
+			.with_long_of_ascending_node_deg(T::from_f64(17.1542087).unwrap());
 		let io_entry = DatabaseEntry::new(io_info, "Io")
 			.with_parent(jupiter_handle.clone(), io_orbit)
 			.with_mean_anomaly_deg(T::from_f64(90.0).unwrap());
@@ -308,7 +324,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_semimajor_axis_m(T::from_f64(671193628.654398).unwrap())
 			.with_eccentricity(T::from_f64(0.00940288418380329).unwrap())
 			.with_inclination_deg(T::from_f64(0.47).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(468.8993005).unwrap())
+			.with_arg_of_periapsis_deg(T::from_f64(108.8993005).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(350.5260572).unwrap());
 		let europa_entry = DatabaseEntry::new(europa_info, "Europa")
 			.with_parent(jupiter_handle.clone(), europa_orbit)
@@ -323,7 +339,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_semimajor_axis_m(T::from_f64(1070615470.44541).unwrap())
 			.with_eccentricity(T::from_f64(0.00158762974782861).unwrap())
 			.with_inclination_deg(T::from_f64(0.2).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(621.291691).unwrap())
+			.with_arg_of_periapsis_deg(T::from_f64(261.291691).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(341.6959921).unwrap());
 		let ganymede_entry = DatabaseEntry::new(ganymede_info, "Ganymede")
 			.with_parent(jupiter_handle.clone(), ganymede_orbit)
@@ -338,11 +354,11 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_semimajor_axis_km(T::from_f64(1_882_700.0).unwrap())
 			.with_eccentricity(T::from_f64(0.0074).unwrap())
 			.with_inclination_deg(T::from_f64(0.192).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(698.8083584).unwrap())
+			.with_arg_of_periapsis_deg(T::from_f64(338.8083584).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(339.4829654).unwrap());
 		let callisto_entry = DatabaseEntry::new(callisto_info, "Callisto")
 			.with_parent(jupiter_handle.clone(), callisto_orbit)
-			.with_mean_anomaly_deg(T::from_f64(839.9757519).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(119.9757519).unwrap());
 		self.add_entry(callisto_handle, callisto_entry);
 		// Amalthea
 		let amalthea_handle = H::from_u16(handles::HANDLE_AMALTHEA).unwrap();
@@ -353,7 +369,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_semimajor_axis_km(T::from_f64(181365.84).unwrap())
 			.with_eccentricity(T::from_f64(0.000441428663648964).unwrap())
 			.with_inclination_deg(T::from_f64(0.374).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(414.339943282274).unwrap())
+			.with_arg_of_periapsis_deg(T::from_f64(54.339943282274).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(342.032315906764).unwrap());
 		let amalthea_entry = DatabaseEntry::new(amalthea_info, "Amalthea")
 			.with_parent(jupiter_handle.clone(), amalthea_orbit)
@@ -368,7 +384,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_semimajor_axis_m(T::from_f64(11394679431.4089).unwrap())
 			.with_eccentricity(T::from_f64(0.148020288964713).unwrap())
 			.with_inclination_deg(T::from_f64(28.1).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(405.592890277337).unwrap())
+			.with_arg_of_periapsis_deg(T::from_f64(45.592890277337).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(57.7865255776614).unwrap());
 		let himalia_entry = DatabaseEntry::new(himalia_info, "Himalia")
 			.with_parent(jupiter_handle.clone(), himalia_orbit)
@@ -399,7 +415,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_eccentricity(T::from_f64(0.36953258321634).unwrap())
 			.with_inclination_deg(T::from_f64(148.4).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(333.722656460893).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(529.781057110863).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(169.781057110863).unwrap());
 		let pasiphae_entry = DatabaseEntry::new(pasiphae_info, "Pasiphae")
 			.with_parent(jupiter_handle.clone(), pasiphae_orbit)
 			.with_mean_anomaly_deg(T::from_f64(270.0).unwrap());
@@ -417,7 +433,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_arg_of_periapsis_deg(T::from_f64(330.01471478535).unwrap());
 		let sinope_entry = DatabaseEntry::new(sinope_info, "Sinope")
 			.with_parent(jupiter_handle.clone(), sinope_orbit)
-			.with_mean_anomaly_deg(T::from_f64(578.187135014671).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(218.187135014671).unwrap());
 		self.add_entry(sinope_handle, sinope_entry);
 		// Lysithea
 		let lysithea_handle = H::from_u16(handles::HANDLE_LYSITHEA).unwrap();
@@ -447,7 +463,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_arg_of_periapsis_deg(T::from_f64(199.239805499578).unwrap());
 		let carme_entry = DatabaseEntry::new(carme_info, "Carma")
 			.with_parent(jupiter_handle.clone(), carme_orbit)
-			.with_mean_anomaly_deg(T::from_f64(545.059221473009).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(185.059221473009).unwrap());
 		self.add_entry(carme_handle, carme_entry);
 		// Ananke
 		let ananke_handle = H::from_u16(handles::HANDLE_ANANKE).unwrap();
@@ -462,7 +478,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_arg_of_periapsis_deg(T::from_f64(131.881909593109).unwrap());
 		let ananke_entry = DatabaseEntry::new(ananke_info, "Ananke")
 			.with_parent(jupiter_handle.clone(), ananke_orbit)
-			.with_mean_anomaly_deg(T::from_f64(365.178243021899).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(5.178243021899).unwrap());
 		self.add_entry(ananke_handle, ananke_entry);
 		// Leda
 		let leda_handle = H::from_u16(handles::HANDLE_LEDA).unwrap();
@@ -490,7 +506,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 		let saturn_info: Body<T> = Body::default()
 			.with_mass_kg(T::from_f64(5.6834e26).unwrap())
 			.with_radii_km(T::from_f64(60268.0).unwrap(), T::from_f64(54364.0).unwrap())
-			.with_axial_tilt_deg(T::from_f64(26.73).unwrap());
+			.with_axial_tilt_deg(T::from_f64(26.73).unwrap())
+			.with_rotation_period_s(T::from_f64(38_018.0).unwrap());
 		let saturn_orbit: OrbitalElements<T> = OrbitalElements::default()
 			.with_semimajor_axis_au(T::from_f64(9.5826).unwrap())
 			.with_eccentricity(T::from_f64(0.0565).unwrap())
@@ -511,10 +528,10 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_eccentricity(T::from_f64(0.0215133482144328).unwrap())
 			.with_inclination_deg(T::from_f64(1.6).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(167.3070822).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(449.3704298).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(89.3704298).unwrap());
 		let mimas_entry = DatabaseEntry::new(mimas_info, "Mimas")
 			.with_parent(saturn_handle.clone(), mimas_orbit)
-			.with_mean_anomaly_deg(T::from_f64(772.976419).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(52.976419).unwrap());
 		self.add_entry(mimas_handle, mimas_entry);
 		// Enceladus
 		let enceladus_handle = H::from_u16(handles::HANDLE_ENCELADUS).unwrap();
@@ -529,7 +546,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_arg_of_periapsis_deg(T::from_f64(264.6781976).unwrap());
 		let enceladus_entry = DatabaseEntry::new(enceladus_info, "Enceladus")
 			.with_parent(saturn_handle.clone(), enceladus_orbit)
-			.with_mean_anomaly_deg(T::from_f64(384.1198896).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(24.1198896).unwrap());
 		self.add_entry(enceladus_handle, enceladus_entry);
 		// Tethys
 		let tethys_handle = H::from_u16(handles::HANDLE_TETHYS).unwrap();
@@ -541,10 +558,10 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_eccentricity(T::from_f64(0.00107532665445937).unwrap())
 			.with_inclination_deg(T::from_f64(1.1).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(169.1532561).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(496.8246271).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(136.8246271).unwrap());
 		let tethys_entry = DatabaseEntry::new(tethys_info, "Tethys")
 			.with_parent(saturn_handle.clone(), tethys_orbit)
-			.with_mean_anomaly_deg(T::from_f64(502.6123366).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(142.6123366).unwrap());
 		self.add_entry(tethys_handle, tethys_entry);
 		// Dione
 		let dione_handle = H::from_u16(handles::HANDLE_DIONE).unwrap();
@@ -556,10 +573,10 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_eccentricity(T::from_f64(0.00273184023667722).unwrap())
 			.with_inclination_deg(T::from_f64(0.0).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(169.5723087).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(5080.2590124).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(40.2590124).unwrap());
 		let dione_entry = DatabaseEntry::new(dione_info, "Dione")
 			.with_parent(saturn_handle.clone(), dione_orbit)
-			.with_mean_anomaly_deg(T::from_f64(856.824114).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(136.824114).unwrap());
 		self.add_entry(dione_handle, dione_entry);
 		// Rhea
 		let rhea_handle = H::from_u16(handles::HANDLE_RHEA).unwrap();
@@ -571,10 +588,10 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_eccentricity(T::from_f64(0.000909561682184622).unwrap())
 			.with_inclination_deg(T::from_f64(0.3).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(168.8079837).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(360.9692475).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(0.9692475).unwrap());
 		let rhea_entry = DatabaseEntry::new(rhea_info, "Rhea")
 			.with_parent(saturn_handle.clone(), rhea_orbit)
-			.with_mean_anomaly_deg(T::from_f64(448.7342263).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(88.7342263).unwrap());
 		self.add_entry(rhea_handle, rhea_entry);
 		// Titan
 		let titan_handle = H::from_u16(handles::HANDLE_TITAN).unwrap();
@@ -616,10 +633,10 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_eccentricity(T::from_f64(0.0274067153032204).unwrap())
 			.with_inclination_deg(T::from_f64(7.6).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(139.3182554).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(369.2974058).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(9.2974058).unwrap());
 		let iapetus_entry = DatabaseEntry::new(iapetus_info, "Iapetus")
 			.with_parent(saturn_handle.clone(), iapetus_orbit)
-			.with_mean_anomaly_deg(T::from_f64(551.098555).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(191.098555).unwrap());
 		self.add_entry(iapetus_handle, iapetus_entry);
 		// Phoebe
 		let phoebe_handle = H::from_u16(handles::HANDLE_PHOEBE).unwrap();
@@ -662,7 +679,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 		let uranus_info: Body<T> = Body::default()
 			.with_mass_kg(T::from_f64(8.6810e25).unwrap())
 			.with_radii_km(T::from_f64(25559.0).unwrap(), T::from_f64(24973.0).unwrap())
-			.with_axial_tilt_deg(T::from_f64(97.77).unwrap());
+			.with_axial_tilt_deg(T::from_f64(97.77).unwrap())
+			.with_rotation_period_s(T::from_f64(62_064.0).unwrap());
 		let uranus_orbit: OrbitalElements<T> = OrbitalElements::default()
 			.with_semimajor_axis_au(T::from_f64(19.19126).unwrap())
 			.with_eccentricity(T::from_f64(0.04717).unwrap())
@@ -686,7 +704,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_arg_of_periapsis_deg(T::from_f64(236.6892802).unwrap());
 		let ariel_entry = DatabaseEntry::new(ariel_info, "Ariel")
 			.with_parent(uranus_handle.clone(), ariel_orbit)
-			.with_mean_anomaly_deg(T::from_f64(583.1923962).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(223.1923962).unwrap());
 		self.add_entry(ariel_handle, ariel_entry);
 		// Umbriel
 		let umbriel_handle = H::from_u16(handles::HANDLE_UMBRIEL).unwrap();
@@ -698,10 +716,10 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_eccentricity(T::from_f64(0.00436450298644918).unwrap())
 			.with_inclination_deg(T::from_f64(0.0796).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(167.7113413).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(521.5502336).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(161.5502336).unwrap());
 		let umbriel_entry = DatabaseEntry::new(umbriel_info, "Umbriel")
 			.with_parent(uranus_handle.clone(), umbriel_orbit)
-			.with_mean_anomaly_deg(T::from_f64(837.2597847).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(117.2597847).unwrap());
 		self.add_entry(umbriel_handle, umbriel_entry);
 		// Titania
 		let titania_handle = H::from_u16(handles::HANDLE_TITANIA).unwrap();
@@ -713,10 +731,10 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_eccentricity(T::from_f64(0.00275764018002836).unwrap())
 			.with_inclination_deg(T::from_f64(0.1129).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(167.6116584).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(399.5640193).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(39.5640193).unwrap());
 		let titania_entry = DatabaseEntry::new(titania_info, "Titania")
 			.with_parent(uranus_handle.clone(), titania_orbit)
-			.with_mean_anomaly_deg(T::from_f64(496.5752932).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(136.5752932).unwrap());
 		self.add_entry(titania_handle, titania_entry);
 		// Oberon
 		let oberon_handle = H::from_u16(handles::HANDLE_OBERON).unwrap();
@@ -731,7 +749,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_arg_of_periapsis_deg(T::from_f64(288.925047).unwrap());
 		let oberon_entry = DatabaseEntry::new(oberon_info, "Oberon")
 			.with_parent(uranus_handle.clone(), oberon_orbit)
-			.with_mean_anomaly_deg(T::from_f64(472.6703921).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(112.6703921).unwrap());
 		self.add_entry(oberon_handle, oberon_entry);
 		// Miranda
 		let miranda_handle = H::from_u16(handles::HANDLE_MIRANDA).unwrap();
@@ -759,7 +777,8 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 		let neptune_info: Body<T> = Body::default()
 			.with_mass_kg(T::from_f64(1.02409e26).unwrap())
 			.with_radii_km(T::from_f64(24764.0).unwrap(), T::from_f64(24341.0).unwrap())
-			.with_axial_tilt_deg(T::from_f64(28.32).unwrap());
+			.with_axial_tilt_deg(T::from_f64(28.32).unwrap())
+			.with_rotation_period_s(T::from_f64(57_996.0).unwrap());
 		let neptune_orbit: OrbitalElements<T> = OrbitalElements::default()
 			.with_semimajor_axis_au(T::from_f64(30.07).unwrap())
 			.with_eccentricity(T::from_f64(0.008678).unwrap())
@@ -783,7 +802,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_arg_of_periapsis_deg(T::from_f64(521.6797862 - 360.0).unwrap());
 		let triton_entry = DatabaseEntry::new(triton_info, "Triton")
 			.with_parent(neptune_handle.clone(), triton_orbit)
-			.with_mean_anomaly_deg(T::from_f64(829.2581612).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(109.2581612).unwrap());
 		self.add_entry(triton_handle, triton_entry);
 		// Nereid
 		let nereid_handle = H::from_u16(handles::HANDLE_NEREID).unwrap();
@@ -806,7 +825,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_mass_kg(T::from_f64(5.8e15).unwrap())
 			.with_radius_km(T::from_f64(2.0).unwrap());
 		let naiad_orbit: OrbitalElements<T> = OrbitalElements::default()
-			.with_semimajor_axis_km(T::from_f64(48227784.2).unwrap())
+			.with_semimajor_axis_m(T::from_f64(48227784.2).unwrap())
 			.with_eccentricity(T::from_f64(0.000000447511577606).unwrap())
 			.with_inclination_deg(T::from_f64(4.691).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(208.626701831817).unwrap())
@@ -821,7 +840,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_mass_kg(T::from_f64(5.8e15).unwrap())
 			.with_radius_km(T::from_f64(2.0).unwrap());
 		let thalassa_orbit: OrbitalElements<T> = OrbitalElements::default()
-			.with_semimajor_axis_km(T::from_f64(50141475.7560609).unwrap())
+			.with_semimajor_axis_m(T::from_f64(50141475.7560609).unwrap())
 			.with_eccentricity(T::from_f64(0.001370609133743).unwrap())
 			.with_inclination_deg(T::from_f64(0.135).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(49.1486489463042).unwrap())
@@ -836,7 +855,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_mass_kg(T::from_f64(2.21e16).unwrap())
 			.with_radius_km(T::from_f64(12.0).unwrap());
 		let despina_orbit: OrbitalElements<T> = OrbitalElements::default()
-			.with_semimajor_axis_km(T::from_f64(60227784.2).unwrap())
+			.with_semimajor_axis_m(T::from_f64(60227784.2).unwrap())
 			.with_eccentricity(T::from_f64(0.0000000244511577606).unwrap())
 			.with_inclination_deg(T::from_f64(0.068).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(208.626701831817).unwrap())
@@ -851,7 +870,7 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_mass_kg(T::from_f64(5.955e16).unwrap())
 			.with_radius_km(T::from_f64(79.1).unwrap());
 		let galatea_orbit: OrbitalElements<T> = OrbitalElements::default()
-			.with_semimajor_axis_km(T::from_f64(62097694.895992).unwrap())
+			.with_semimajor_axis_m(T::from_f64(62097694.895992).unwrap())
 			.with_eccentricity(T::from_f64(0.00176342814065272).unwrap())
 			.with_inclination_deg(T::from_f64(0.034).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(48.6938364381423).unwrap())
@@ -866,18 +885,33 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_mass_kg(T::from_f64(8.563e16).unwrap())
 			.with_radius_km(T::from_f64(99.96).unwrap());
 		let larissa_orbit: OrbitalElements<T> = OrbitalElements::default()
-			.with_semimajor_axis_km(T::from_f64(73591064.2683372).unwrap())
+			.with_semimajor_axis_m(T::from_f64(73591064.2683372).unwrap())
 			.with_eccentricity(T::from_f64(0.001696576604903).unwrap())
 			.with_inclination_deg(T::from_f64(0.205).unwrap())
 			.with_long_of_ascending_node_deg(T::from_f64(48.9078558843833).unwrap())
-			.with_arg_of_periapsis_deg(T::from_f64(378.844329275267).unwrap());
+			.with_arg_of_periapsis_deg(T::from_f64(18.844329275267).unwrap());
 		let larissa_entry = DatabaseEntry::new(larissa_info, "Larissa")
 			.with_parent(neptune_handle.clone(), larissa_orbit)
-			.with_mean_anomaly_deg(T::from_f64(428.613425343462).unwrap());
+			.with_mean_anomaly_deg(T::from_f64(68.613425343462).unwrap());
 		self.add_entry(larissa_handle, larissa_entry);
 	}
 	pub fn add_dwarf_planets(&mut self) {
 		let sun_handle = H::from_u16(handles::HANDLE_SOL).unwrap();
+		// Pluto
+		let pluto_handle = H::from_u16(handles::HANDLE_PLUTO).unwrap();
+		let pluto_orbit: OrbitalElements<T> = OrbitalElements::default()
+			.with_semimajor_axis_au(T::from_f64(39.482).unwrap())
+			.with_eccentricity(T::from_f64(0.2488).unwrap())
+			.with_inclination_deg(T::from_f64(17.16).unwrap())
+			.with_long_of_ascending_node_deg(T::from_f64(110.299).unwrap())
+			.with_arg_of_periapsis_deg(T::from_f64(113.834).unwrap());
+		let pluto_info: Body<T> = Body::default()
+			.with_mass_kg(T::from_f64(1.303e22).unwrap())
+			.with_radius_km(T::from_f64(1188.3).unwrap());
+		let pluto_entry = DatabaseEntry::new(pluto_info, "Pluto")
+			.with_parent(sun_handle.clone(), pluto_orbit)
+			.with_mean_anomaly_deg(T::from_f64(14.53).unwrap());
+		self.add_entry(pluto_handle, pluto_entry);
 		// Eris
 		let eris_handle = H::from_u16(handles::HANDLE_ERIS).unwrap();
 		let eris_orbit: OrbitalElements<T> = OrbitalElements::default()
@@ -954,116 +988,1019 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			.with_mean_anomaly_deg(T::from_f64(178.5).unwrap());
 		self.add_entry(namaka_handle, namaka_entry);
 	}
+	/// Sets which axis points "up" in the vectors passed into and returned from the database
+	pub fn with_coordinate_convention(mut self, convention: CoordinateConvention) -> Self {
+		self.set_coordinate_convention(convention);
+		self
+	}
+	/// Sets which axis points "up" in the vectors passed into and returned from the database
+	pub fn set_coordinate_convention(&mut self, convention: CoordinateConvention) {
+		self.spatial_index = None;
+		self.coordinate_convention = convention;
+	}
+	/// Gets which axis points "up" in the vectors passed into and returned from the database
+	pub fn coordinate_convention(&self) -> CoordinateConvention {
+		self.coordinate_convention
+	}
+	/// Sets the plane that vectors passed into and returned from the database are measured
+	/// against, see [`set_reference_plane`](Self::set_reference_plane)
+	pub fn with_reference_plane(mut self, plane: ReferencePlane) -> Self where H: Debug, T: RealField {
+		self.set_reference_plane(plane);
+		self
+	}
+	/// Sets the plane that vectors passed into and returned from the database are measured
+	/// against
+	/// 
+	/// The [invariable plane](Self::invariable_plane_normal) is found from the bodies in the
+	/// database when this is called, so set it again after adding or removing bodies to update it.
+	/// Orbital elements are always stored relative to the ecliptic.
+	pub fn set_reference_plane(&mut self, plane: ReferencePlane) where H: Debug, T: RealField {
+		self.spatial_index = None;
+		self.reference_plane = plane;
+		self.reference_plane_rotation = match plane {
+			ReferencePlane::Ecliptic => None,
+			ReferencePlane::Invariable => {
+				let zero = T::from_f32(0.0).unwrap();
+				let y_axis = Vector3::new(zero, T::from_f32(1.0).unwrap(), zero);
+				let momentum = self.total_orbital_angular_momentum();
+				match momentum.try_normalize(zero) {
+					Some(normal) => Rotation3::rotation_between(&normal, &y_axis),
+					None => None,
+				}
+			},
+		};
+	}
+	/// Sets whether [`position_at_time`](Self::position_at_time) includes the
+	/// [wobble](Self::primary_wobble_at_time) each body makes around its barycenter with its
+	/// satellites
+	pub fn with_barycentric_wobble(mut self, enabled: bool) -> Self {
+		self.set_barycentric_wobble(enabled);
+		self
+	}
+	/// Sets whether [`position_at_time`](Self::position_at_time) includes the
+	/// [wobble](Self::primary_wobble_at_time) each body makes around its barycenter with its
+	/// satellites
+	pub fn set_barycentric_wobble(&mut self, enabled: bool) {
+		self.spatial_index = None;
+		self.barycentric_wobble = enabled;
+	}
+	/// Gets whether [`position_at_time`](Self::position_at_time) includes barycentric wobble
+	pub fn barycentric_wobble(&self) -> bool {
+		self.barycentric_wobble
+	}
+	/// Gets the plane that vectors passed into and returned from the database are measured against
+	pub fn reference_plane(&self) -> ReferencePlane {
+		self.reference_plane
+	}
+	/// Calculates the normal of the invariable plane, the plane perpendicular to the total orbital
+	/// angular momentum of every body in the database
+	/// 
+	/// Each body's angular momentum is taken from its orbit around its parent, so for our solar
+	/// system this is dominated by Jupiter and Saturn and sits about 1.6° from the ecliptic.
+	/// Returns the +Y (or +Z) axis if nothing in the database orbits anything.
+	pub fn invariable_plane_normal(&self) -> Vector3<T> where H: Debug, T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let y_axis = Vector3::new(zero, T::from_f32(1.0).unwrap(), zero);
+		let normal = self.total_orbital_angular_momentum().try_normalize(zero).unwrap_or(y_axis);
+		self.apply_convention(normal)
+	}
+	/// Sums the orbital angular momentum *m·sqrt(GM·a(1 - e²))* of every body around its parent
+	/// in the internal ecliptic frame
+	fn total_orbital_angular_momentum(&self) -> Vector3<T> where H: Debug, T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let half_pi = T::from_f64(std::f64::consts::FRAC_PI_2).unwrap();
+		let mut total = Vector3::zeros();
+		for entry in self.bodies.values() {
+			let (Some(parent_handle), Some(orbit)) = (&entry.parent, &entry.orbit) else {
+				continue;
+			};
+			let normal = orbit.position_at_true_anomaly(zero).cross(&orbit.position_at_true_anomaly(half_pi)).normalize();
+			let semilatus_rectum = orbit.semimajor_axis * (one - Float::powi(orbit.eccentricity, 2));
+			let momentum = entry.info.mass_kg() * Float::sqrt(self.get_entry(parent_handle).gm() * semilatus_rectum);
+			total += self.parent_axis_rotation(entry) * normal * momentum;
+		}
+		total
+	}
+	/// Converts a vector from the Y-up ecliptic frame used internally to the database's reference
+	/// plane and coordinate convention
+	fn apply_convention(&self, vector: Vector3<T>) -> Vector3<T> where T: RealField {
+		let vector = match &self.reference_plane_rotation {
+			Some(rotation) => rotation * vector,
+			None => vector,
+		};
+		self.apply_up_axis(vector)
+	}
+	/// Converts a vector from Y-up to the database's coordinate convention, without changing
+	/// which plane it's measured against
+	fn apply_up_axis(&self, vector: Vector3<T>) -> Vector3<T> where T: RealField {
+		match self.coordinate_convention {
+			CoordinateConvention::YUp => vector,
+			CoordinateConvention::ZUp => Vector3::new(vector.x, -vector.z, vector.y),
+		}
+	}
+	/// Converts a vector from the database's reference plane and coordinate convention to the
+	/// Y-up ecliptic frame used internally
+	fn remove_convention(&self, vector: Vector3<T>) -> Vector3<T> where T: RealField {
+		let vector = match self.coordinate_convention {
+			CoordinateConvention::YUp => vector,
+			CoordinateConvention::ZUp => Vector3::new(vector.x, vector.z, -vector.y),
+		};
+		match &self.reference_plane_rotation {
+			Some(rotation) => rotation.inverse() * vector,
+			None => vector,
+		}
+	}
 	/// Adds a new entry to the database
 	pub fn add_entry(&mut self, handle: H, entry: DatabaseEntry<H, T>) {
+		self.spatial_index = None;
 		self.bodies.insert(handle, entry);
 	}
+	/// Adds a new entry to the database, with the semimajor axis of its orbit set so that it takes
+	/// `period_s` seconds to orbit its parent
+	/// 
+	/// The rest of the entry's orbit is kept as it is. Fails if the entry doesn't orbit anything or
+	/// its parent isn't in the database yet. See [`OrbitalElements::with_period_s`].
+	pub fn add_body_with_period(&mut self, handle: H, mut entry: DatabaseEntry<H, T>, period_s: T) -> Result<(), OrbitError<H>> {
+		let (Some(parent_handle), Some(orbit)) = (&entry.parent, entry.orbit) else {
+			return Err(OrbitError::NoOrbit(handle));
+		};
+		let parent = self.bodies.get(parent_handle).ok_or_else(|| OrbitError::UnknownBody(parent_handle.clone()))?;
+		entry.orbit = Some(orbit.with_period_s(period_s, parent.info.gm()));
+		self.add_entry(handle, entry);
+		Ok(())
+	}
 	/// Gets the entry from the database with the given handle
 	pub fn get_entry(&self, handle: &H) -> &DatabaseEntry<H, T> where H: Debug {
 		let error_msg = format!("No body in database with ID {:?}", handle);
 		self.bodies.get(handle).expect(&error_msg)
 	}
+	/// Gets the entry from the database with the given handle, or `None` if there isn't one
+	pub fn try_get_entry(&self, handle: &H) -> Option<&DatabaseEntry<H, T>> {
+		self.bodies.get(handle)
+	}
 	/// Gets the position of the given body at the given time since epoch in seconds
+	/// 
+	/// Kepler's equation is solved with Newton's method rather than approximated with a series in
+	/// the eccentricity, so eccentric orbits like comets' land exactly where their state vectors
+	/// and Lambert transfers expect them. Hyperbolic orbits are handled through the hyperbolic
+	/// anomaly, see [`OrbitalElements::true_anomaly_at_mean_anomaly`]. The orbit is oriented by the textbook
+	/// rotation through *Ω*, *i* and *ω* in [`OrbitalElements::position_at_true_anomaly`], the
+	/// same one used for velocities, so positions and velocities always describe the same orbit.
 	pub fn position_at_mean_anomaly(&self, handle: &H, mean_anomaly: T) -> Vector3<T> where H: Debug, T: RealField + SimdValue + SimdRealField {
 		let zero = T::from_f32(0.0).unwrap();
-		let one = T::from_f32(1.0).unwrap();
-		let two = T::from_f32(2.0).unwrap();
-		let x_axis = Vector3::new(one, zero, zero);
-		let y_axis = Vector3::new(zero, one, zero);
 		let orbiting_body = self.bodies.get(&handle).unwrap();
 		if let Some(orbit) = &orbiting_body.orbit {
-			let parent = self.get_entry(&orbiting_body.parent.clone().unwrap());
-			let parent_axis_rot: Rotation3<T> = Rotation3::new(x_axis * parent.info.axial_tilt_rad());
-			let parent_up: Vector3<T> = parent_axis_rot * y_axis;
-			let true_anomaly = mean_anomaly + two * orbit.eccentricity * Float::sin(mean_anomaly) + T::from_f64(1.25).unwrap() * Float::powi(orbit.eccentricity, 2) * Float::sin(two * mean_anomaly);
-			let radius = orbit.semimajor_axis * (one - Float::powi(orbit.eccentricity, 2)) / (one + orbit.eccentricity * Float::cos(true_anomaly));
-			let rot_true_anomaly = Rotation3::new(parent_up * true_anomaly);
-			let rot_long_of_ascending_node = Rotation3::new(parent_up * orbit.long_of_ascending_node);
-			let dir_ascending_node = rot_long_of_ascending_node * x_axis;
-			let dir_normal = x_axis.cross(&dir_ascending_node);
-			let rot_inclination = Rotation3::new(dir_ascending_node * orbit.inclination);
-			let rot_arg_of_periapsis = Rotation3::new(dir_normal * orbit.arg_of_periapsis);
-			let direction = rot_inclination * rot_arg_of_periapsis * rot_true_anomaly * x_axis;
-			return direction * radius;
+			let parent_axis_rot = self.parent_axis_rotation(orbiting_body);
+			let mean_anomaly = Self::wrap_mean_anomaly(orbit, ToPrimitive::to_f64(&mean_anomaly).unwrap());
+			let true_anomaly = orbit.true_anomaly_at_mean_anomaly(mean_anomaly);
+			return self.apply_convention(parent_axis_rot * orbit.position_at_true_anomaly(true_anomaly));
 		} else {
 			return Vector3::new(zero, zero, zero);
 		}
 	}
+	/// Wraps a mean anomaly on a closed orbit into `[0, 2π)` before converting it to `T`
+	/// 
+	/// Trig functions and Kepler's equation lose precision on large angles, badly so in `f32`,
+	/// so the extra whole orbits are dropped while the angle is still in `f64`. Hyperbolic orbits
+	/// don't repeat, so their mean anomaly is left alone.
+	fn wrap_mean_anomaly(orbit: &OrbitalElements<T>, mean_anomaly: f64) -> T {
+		if orbit.eccentricity < T::from_f32(1.0).unwrap() {
+			T::from_f64(mean_anomaly.rem_euclid(std::f64::consts::TAU)).unwrap()
+		} else {
+			T::from_f64(mean_anomaly).unwrap()
+		}
+	}
+	/// Finds the true anomaly *ν* in radians, in `(-π, π]`, of the point on the given body's orbit
+	/// closest in direction to a position relative to its parent
+	/// 
+	/// This is the inverse of [`OrbitalElements::position_at_true_anomaly`], useful for working out
+	/// where along its orbit a body is from where it is, like after moving it to a new parent. The
+	/// position is in the database's coordinate convention and reference plane, and is projected
+	/// onto the orbit's plane before measuring its angle from periapsis. Returns `None` if the
+	/// body doesn't orbit anything.
+	pub fn true_anomaly_at_position(&self, handle: &H, parent_relative_position: Vector3<T>) -> Option<T> where H: Debug, T: RealField {
+		let entry = self.bodies.get(handle)?;
+		let orbit = entry.orbit?;
+		let zero = T::from_f32(0.0).unwrap();
+		let half_pi = T::from_f64(std::f64::consts::FRAC_PI_2).unwrap();
+		let position = self.parent_axis_rotation(entry).inverse() * self.remove_convention(parent_relative_position);
+		let dir_periapsis = orbit.position_at_true_anomaly(zero).normalize();
+		let dir_semilatus_rectum = orbit.position_at_true_anomaly(half_pi).normalize();
+		Some(Float::atan2(position.dot(&dir_semilatus_rectum), position.dot(&dir_periapsis)))
+	}
+	/// Gets the position and velocity of the given body relative to its parent at the given time
+	/// since epoch in seconds
+	/// 
+	/// Returns `None` if the body isn't in the database or doesn't orbit anything.
+	pub fn state_vector_at_time(&self, handle: &H, time: T) -> Option<(Vector3<T>, Vector3<T>)> where H: Debug, T: RealField {
+		let entry = self.bodies.get(handle)?;
+		let orbit = entry.orbit?;
+		let parent_gm = self.effective_parent_gm(entry)?;
+		let parent_axis_rot = self.parent_axis_rotation(entry);
+		let true_anomaly = orbit.true_anomaly_at_mean_anomaly(self.mean_anomaly_at_time(handle, time));
+		let position = parent_axis_rot * orbit.position_at_true_anomaly(true_anomaly);
+		let velocity = parent_axis_rot * orbit.velocity_at_true_anomaly(true_anomaly, parent_gm);
+		Some((self.apply_convention(position), self.apply_convention(velocity)))
+	}
+	/// Calculates the osculating orbital elements of the given body's motion around one of its
+	/// ancestors at the given time, like the heliocentric orbit of a moon
+	/// 
+	/// The body's position and velocity relative to `ancestor` are the sums of the
+	/// [state vectors](Self::state_vector_at_time) of every body on the way up, and the elements
+	/// are the Keplerian orbit they'd follow around `ancestor` alone, measured against its
+	/// equatorial plane like the orbits of its own satellites. Returns `None` if `ancestor` isn't
+	/// one of the body's parents, grandparents and so on.
+	pub fn elements_relative_to(&self, handle: &H, ancestor: &H, time: T) -> Option<OrbitalElements<T>> where H: Debug, T: RealField {
+		let mut position = Vector3::zeros();
+		let mut velocity = Vector3::zeros();
+		let mut current = handle.clone();
+		while current != *ancestor {
+			let (body_position, body_velocity) = self.state_vector_at_time(&current, time)?;
+			position += body_position;
+			velocity += body_velocity;
+			current = self.get_entry(&current).parent.clone()?;
+		}
+		if current == *handle {
+			return None;
+		}
+		let ancestor_entry = self.get_entry(ancestor);
+		let world_to_orbit = Self::axis_rotation(ancestor_entry).inverse();
+		let position = world_to_orbit * self.remove_convention(position);
+		let velocity = world_to_orbit * self.remove_convention(velocity);
+		Some(OrbitalElements::from_state_vectors(position, velocity, ancestor_entry.gm(), time))
+	}
+	/// Gets the GM of the given entry's parent that its orbital motion follows, or `None` if it
+	/// doesn't orbit anything
+	fn effective_parent_gm(&self, entry: &DatabaseEntry<H, T>) -> Option<T> where H: Debug {
+		let orbit = entry.orbit?;
+		// bodies with a fixed mean motion move as if their parent had whatever GM gives that motion
+		match entry.fixed_mean_motion {
+			Some(n) => Some(n * n * Float::powi(Float::abs(orbit.semimajor_axis), 3)),
+			None => Some(self.get_entry(entry.parent.as_ref()?).gm()),
+		}
+	}
+	/// Gets the velocity of the given body relative to its parent as it passes through the
+	/// ascending node of its orbit, or the descending node if `ascending` is `false`
+	/// 
+	/// The nodes are where the orbit crosses its parent's equatorial plane, at a true anomaly of
+	/// *-ω* for the ascending node and *π - ω* for the descending node, swapped for orbits with a
+	/// negative inclination like Luna's so that the body always heads north through the ascending
+	/// node. Handy for drawing node markers with an arrow showing which way the body crosses.
+	/// Returns `None` if the body doesn't orbit anything, or if it's on a hyperbolic trajectory
+	/// that never reaches the node.
+	pub fn velocity_at_node(&self, handle: &H, ascending: bool) -> Option<Vector3<T>> where H: Debug, T: RealField {
+		let entry = self.bodies.get(handle)?;
+		let orbit = entry.orbit?;
+		let parent_gm = self.effective_parent_gm(entry)?;
+		// a negative inclination flips the orbit over, so the ascending node of the elements is
+		// where the body actually heads south through the plane
+		let zero = T::from_f32(0.0).unwrap();
+		let true_anomaly = match ascending == (Float::sin(orbit.inclination) >= zero) {
+			true => -orbit.arg_of_periapsis,
+			false => T::from_f64(std::f64::consts::PI).unwrap() - orbit.arg_of_periapsis,
+		};
+		if orbit.eccentricity >= T::from_f32(1.0).unwrap() && T::from_f32(1.0).unwrap() + orbit.eccentricity * Float::cos(true_anomaly) <= zero {
+			return None;
+		}
+		let velocity = self.parent_axis_rotation(entry) * orbit.velocity_at_true_anomaly(true_anomaly, parent_gm);
+		Some(self.apply_convention(velocity))
+	}
+	/// Calculates how fast the true anomaly *ν* of the given body is changing at the given time,
+	/// in radians per second
+	/// 
+	/// This is *dν/dt = h/r²* for the specific angular momentum *h = sqrt(GM·a(1 - e²))* and the
+	/// body's current distance *r* from its parent, so it's fastest at periapsis and slowest at
+	/// apoapsis. Returns `None` if the body doesn't orbit anything.
+	pub fn true_anomaly_rate_at_time(&self, handle: &H, time: T) -> Option<T> where H: Debug, T: RealField {
+		let entry = self.bodies.get(handle)?;
+		let orbit = entry.orbit?;
+		let parent_gm = self.effective_parent_gm(entry)?;
+		let semilatus_rectum = orbit.semimajor_axis * (T::from_f32(1.0).unwrap() - Float::powi(orbit.eccentricity, 2));
+		let angular_momentum = Float::sqrt(parent_gm * semilatus_rectum);
+		let true_anomaly = orbit.true_anomaly_at_mean_anomaly(self.mean_anomaly_at_time(handle, time));
+		let radius = orbit.position_at_true_anomaly(true_anomaly).norm();
+		Some(angular_momentum / Float::powi(radius, 2))
+	}
+	/// Calculates the specific orbital energy *ε = -GM / 2a* of the given body's orbit around its
+	/// parent in joules per kilogram (J/kg), or `None` if the body doesn't orbit anything
+	/// 
+	/// This is negative for closed orbits and positive for escape trajectories.
+	pub fn specific_orbital_energy(&self, handle: &H) -> Option<T> where H: Debug {
+		let entry = self.bodies.get(handle)?;
+		let orbit = entry.orbit?;
+		let parent_gm = self.get_entry(entry.parent.as_ref()?).gm();
+		Some(-parent_gm / (T::from_f32(2.0).unwrap() * orbit.semimajor_axis))
+	}
+	/// Splits the specific orbital energy of the given body at the given time into its specific
+	/// kinetic energy *v²/2* and potential energy *-GM/r*, both in joules per kilogram (J/kg)
+	/// 
+	/// Energy is traded back and forth between the two along an eccentric orbit, but their sum
+	/// always equals [`specific_orbital_energy`](Self::specific_orbital_energy). Returns `None` if
+	/// the body doesn't orbit anything.
+	pub fn energy_components_at_time(&self, handle: &H, time: T) -> Option<(T, T)> where H: Debug, T: RealField {
+		let entry = self.bodies.get(handle)?;
+		let parent_gm = self.get_entry(entry.parent.as_ref()?).gm();
+		let (position, velocity) = self.state_vector_at_time(handle, time)?;
+		let kinetic = velocity.norm_squared() / T::from_f32(2.0).unwrap();
+		let potential = -parent_gm / position.norm();
+		Some((kinetic, potential))
+	}
+	/// Calculates how much faster in meters per second the given body would need to be moving at
+	/// the given time to coast out to the edge of its parent's sphere of influence
+	/// 
+	/// This is the extra speed needed to raise its specific orbital energy to *-GM/r_soi*, the
+	/// energy of something that just barely reaches [`radius_soi`](Self::radius_soi) before
+	/// falling back. That's a little less than the speed to escape the parent to infinity, since
+	/// past the sphere of influence the parent's gravity is ignored. Returns zero if the body is
+	/// already moving fast enough, or `None` if it doesn't orbit anything.
+	pub fn escape_delta_v(&self, handle: &H, time: T) -> Option<T> where H: Debug + Ord, T: RealField {
+		let entry = self.bodies.get(handle)?;
+		let parent_handle = entry.parent.as_ref()?;
+		let parent_gm = self.get_entry(parent_handle).gm();
+		let (position, velocity) = self.state_vector_at_time(handle, time)?;
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let radius = position.norm();
+		let radius_soi = self.radius_soi(parent_handle);
+		let required_speed = Float::sqrt(Float::max(two * parent_gm * (one / radius - one / radius_soi), zero));
+		Some(Float::max(required_speed - velocity.norm(), zero))
+	}
+	/// Calculates the change in speed in meters per second needed to circularize the given body's
+	/// orbit at its apoapsis, or at its periapsis if `at_apoapsis` is `false`
+	/// 
+	/// This is the circular speed *sqrt(GM/r)* at that radius minus the speed the body already has
+	/// there from the vis-viva equation, so it's positive for the prograde burn at apoapsis and
+	/// negative for the retrograde burn at periapsis. Returns `None` if the body doesn't orbit
+	/// anything, or for the apoapsis of an orbit that doesn't have one.
+	pub fn circularize_delta_v(&self, handle: &H, at_apoapsis: bool) -> Option<T> where H: Debug {
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let entry = self.bodies.get(handle)?;
+		let orbit = entry.orbit?;
+		let parent_gm = self.effective_parent_gm(entry)?;
+		let radius = match at_apoapsis {
+			true if orbit.eccentricity >= one => return None,
+			true => orbit.semimajor_axis * (one + orbit.eccentricity),
+			false => orbit.semimajor_axis * (one - orbit.eccentricity),
+		};
+		let circular_speed = Float::sqrt(parent_gm / radius);
+		let speed = Float::sqrt(parent_gm * (two / radius - one / orbit.semimajor_axis));
+		Some(circular_speed - speed)
+	}
+	/// Gets the point on the auxiliary circle of the given body's orbit that matches its eccentric
+	/// anomaly *E* at the given time, relative to its parent
+	/// 
+	/// The auxiliary circle has a radius of the semimajor axis *a* and shares its center with the
+	/// orbit's ellipse, so the point is *a·cos(E)* along the periapsis direction and *a·sin(E)*
+	/// perpendicular to it from the center. Squashing it towards the major axis by *b/a* gives the
+	/// body's actual position, which is the classic construction used to derive Kepler's equation.
+	/// Returns `None` if the body doesn't orbit anything or isn't on a closed orbit.
+	pub fn eccentric_anomaly_position(&self, handle: &H, time: T) -> Option<Vector3<T>> where H: Debug, T: RealField {
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let entry = self.bodies.get(handle)?;
+		entry.parent.as_ref()?;
+		let orbit = entry.orbit?;
+		let e = orbit.eccentricity;
+		if e >= one {
+			return None;
+		}
+		let true_anomaly = orbit.true_anomaly_at_mean_anomaly(self.mean_anomaly_at_time(handle, time));
+		let eccentric_anomaly = two * Float::atan2(Float::sqrt(one - e) * Float::sin(true_anomaly / two), Float::sqrt(one + e) * Float::cos(true_anomaly / two));
+		let dir_periapsis = orbit.position_at_true_anomaly(T::from_f32(0.0).unwrap()).normalize();
+		let dir_perpendicular = orbit.position_at_true_anomaly(T::from_f64(std::f64::consts::FRAC_PI_2).unwrap()).normalize();
+		let a = orbit.semimajor_axis;
+		let center = dir_periapsis * (-a * e);
+		let point = center + dir_periapsis * (a * Float::cos(eccentric_anomaly)) + dir_perpendicular * (a * Float::sin(eccentric_anomaly));
+		Some(self.apply_convention(self.parent_axis_rotation(entry) * point))
+	}
+	/// Gets the direction of the given body's rotation axis in world space, which is the normal of
+	/// its equatorial plane
+	/// 
+	/// This is the plane that rings and the orbits of the body's satellites are aligned to, as
+	/// opposed to the plane of the body's own orbit. The axis is tilted from +Y about the X axis
+	/// by the body's axial tilt, within the equatorial frame of its parent that its orbit is laid
+	/// out in. Precession of the axis isn't modelled yet, so `time` is currently unused and the
+	/// result is the same at any time.
+	pub fn equatorial_normal(&self, handle: &H, _time: T) -> Vector3<T> where H: Debug, T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let y_axis = Vector3::new(zero, one, zero);
+		let entry = self.get_entry(handle);
+		self.apply_convention(self.parent_axis_rotation(entry) * Self::axis_rotation(entry) * y_axis)
+	}
+	/// Gets the orientation of the given body in world space at the given time, combining its
+	/// axial tilt with how far it has spun around its axis
+	/// 
+	/// The rotation turns the up axis of the [coordinate convention](CoordinateConvention) onto
+	/// the body's [rotation axis](Self::equatorial_normal), so it can be used directly as the
+	/// rotation of the body's model. The body spins counter-clockwise about its axis once every
+	/// [rotation period](Body::rotation_period_s), starting from no spin at time zero. Bodies
+	/// without a rotation period are only tilted.
+	pub fn orientation_at_time(&self, handle: &H, time: T) -> Rotation3<T> where H: Debug, T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let entry = self.get_entry(handle);
+		let spin_angle = match entry.info.rotation_period_s() {
+			Some(period) => Float::fract(time / period) * T::from_f64(std::f64::consts::TAU).unwrap(),
+			None => zero,
+		};
+		let spin = Rotation3::new(Vector3::new(zero, one, zero) * spin_angle);
+		let convention = self.convention_rotation();
+		convention * self.parent_axis_rotation(entry) * Self::axis_rotation(entry) * spin * convention.inverse()
+	}
+	/// Rotation that does the same as [`apply_convention`](Self::apply_convention), for turning
+	/// rotations in the internal frame into world space
+	fn convention_rotation(&self) -> Rotation3<T> where T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let reference_plane = self.reference_plane_rotation.unwrap_or_else(Rotation3::identity);
+		let up_axis = match self.coordinate_convention {
+			CoordinateConvention::YUp => Rotation3::identity(),
+			CoordinateConvention::ZUp => Rotation3::from_matrix_unchecked(Matrix3::new(
+				one, zero, zero,
+				zero, zero, -one,
+				zero, one, zero,
+			)),
+		};
+		up_axis * reference_plane
+	}
+	/// Gets the position of the given body relative to its parent at the given time, measured
+	/// against the parent's equatorial plane instead of the database's reference plane
+	/// 
+	/// The position is untilted by the parent's axial tilt, so the parent's rotation axis is the
+	/// up axis of the [coordinate convention](CoordinateConvention). This is the frame to use for
+	/// rendering a system centered on and aligned with a planet. Returns `None` if the body isn't
+	/// in the database or doesn't orbit anything.
+	pub fn position_in_parent_equatorial(&self, handle: &H, time: T) -> Option<Vector3<T>> where H: Debug, T: RealField {
+		let entry = self.bodies.get(handle)?;
+		if entry.parent.is_none() || entry.orbit.is_none() {
+			return None;
+		}
+		let position = self.remove_convention(self.position_at_time(handle, time));
+		Some(self.apply_up_axis(self.parent_axis_rotation(entry).inverse() * position))
+	}
+	/// Gets the normal of the Laplace plane of the given satellite in world space, the plane its
+	/// orbit precesses around
+	/// 
+	/// Close to the parent, the pull of the parent's equatorial bulge dominates and the Laplace
+	/// plane lines up with the parent's equator. Far from the parent, the pull of the body the
+	/// parent orbits dominates and it lines up with the parent's own orbit. In between it's tilted
+	/// from the equator by the angle *φ* where
+	/// 
+	/// tan 2φ = sin 2ε / (cos 2ε + 2(r_L/a)⁵)
+	/// 
+	/// with *ε* the parent's obliquity, *a* the satellite's semimajor axis, and *r_L* the Laplace
+	/// radius given by *r_L⁵ = J2·R²·a_p³·(1 - e_p²)^(3/2)·m_p/M* from the parent's *J2*, radius,
+	/// orbit and mass, and the mass of the body it orbits. The parent's *J2* comes from
+	/// [`Body::estimated_j2`].
+	/// 
+	/// Returns `None` if the body doesn't orbit anything. If its parent doesn't orbit anything
+	/// either, the parent's equator is the only plane there is and its normal is returned.
+	pub fn laplace_plane_normal(&self, handle: &H) -> Option<Vector3<T>> where H: Debug, T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let entry = self.bodies.get(handle)?;
+		let orbit = entry.orbit?;
+		let parent_handle = entry.parent.as_ref()?;
+		let parent = self.get_entry(parent_handle);
+		let equator_normal = self.equatorial_normal(parent_handle, zero);
+		let (grandparent_handle, parent_orbit) = match (&parent.parent, parent.orbit) {
+			(Some(grandparent_handle), Some(parent_orbit)) => (grandparent_handle, parent_orbit),
+			_ => return Some(equator_normal),
+		};
+		let (position, velocity) = self.state_vector_at_time(parent_handle, zero)?;
+		let orbit_normal = position.cross(&velocity).normalize();
+		let obliquity = Float::acos(Float::max(-one, Float::min(one, equator_normal.dot(&orbit_normal))));
+		if obliquity < T::from_f64(1e-9).unwrap() {
+			return Some(equator_normal);
+		}
+		let laplace_radius_5 = parent.info.estimated_j2()
+			* Float::powi(parent.info.radius_equator_m(), 2)
+			* Float::powi(parent_orbit.semimajor_axis, 3)
+			* Float::powf(one - Float::powi(parent_orbit.eccentricity, 2), T::from_f64(1.5).unwrap())
+			* parent.info.mass_kg() / self.get_entry(grandparent_handle).info.mass_kg();
+		let ratio_5 = laplace_radius_5 / Float::powi(orbit.semimajor_axis, 5);
+		let tilt = Float::atan2(Float::sin(two * obliquity), Float::cos(two * obliquity) + two * ratio_5) / two;
+		// turn the equator's normal towards the orbit's normal by the tilt
+		let normal = (equator_normal * Float::sin(obliquity - tilt) + orbit_normal * Float::sin(tilt)) / Float::sin(obliquity);
+		Some(normal.normalize())
+	}
+	/// Rotation from the parent's reference plane to world space, which tilts the orbits of
+	/// satellites along with their parent's axial tilt
+	fn parent_axis_rotation(&self, entry: &DatabaseEntry<H, T>) -> Rotation3<T> where H: Debug, T: RealField {
+		match &entry.parent {
+			Some(parent_handle) => Self::axis_rotation(self.get_entry(parent_handle)),
+			None => Rotation3::identity(),
+		}
+	}
+	/// Rotation that tilts the +Y axis onto the given body's rotation axis
+	fn axis_rotation(entry: &DatabaseEntry<H, T>) -> Rotation3<T> where T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let x_axis = Vector3::new(one, zero, zero);
+		Rotation3::new(x_axis * entry.info.axial_tilt_rad())
+	}
+	/// Gets the position of the given body relative to its parent at the given time
+	/// 
+	/// If [barycentric wobble](Self::with_barycentric_wobble) is enabled, this includes the
+	/// [offset](Self::primary_wobble_at_time) caused by the body's own satellites.
 	pub fn position_at_time(&self, handle: &H, time: T) -> Vector3<T> where H: Debug, T: RealField {
 		let orbiting_body = self.bodies.get(handle).unwrap();
 		if orbiting_body.orbit.is_some() {
 			let mean_anomaly = self.mean_anomaly_at_time(handle, time);
-			return self.position_at_mean_anomaly(handle, mean_anomaly);
+			let position = self.position_at_mean_anomaly(handle, mean_anomaly);
+			if self.barycentric_wobble {
+				return position + self.primary_wobble_at_time(handle, time);
+			}
+			return position;
 		} else {
 			let zero = T::from_f32(0.0).unwrap();
 			return Vector3::new(zero, zero, zero);
 		}
 	}
-	pub fn relative_position(&self, origin: &H, relative: &H, time: T) -> Option<Vector3<T>> where H: Debug + Display + Ord, T: RealField + SimdValue + SimdRealField {
-		// println!("Finding relative position between origin body {} and relative body {}", origin, relative);
-		let relative_heirarchy: Vec<H> = self.get_parents(relative);
-		// println!("Relative heirarchy: {:?}", relative_heirarchy);
-		let zero = T::from_f32(0.0).unwrap();
-		let mut relative_position = Vector3::new(zero, zero, zero);
-		let mut entry = self.get_entry(origin);
-		// println!("\tSubtracting orbital position of {}", origin);
-		relative_position -= self.position_at_time(origin, time);
-		// if origin body is already in the parent heirarchy of the relative body, find the relative body position
-		if let Ok(parent_relative_index) = relative_heirarchy.binary_search(origin) {
-			// println!("Reached heirarchy intersection at body {}", parent_handle);
-			let mut index = parent_relative_index;
-			let mut handle;
-			while index < relative_heirarchy.len() {
-				handle = &relative_heirarchy[index];
-				entry = self.get_entry(handle);
-				// println!("\tAdding orbital position of {}", handle);
-				relative_position += self.position_at_time(handle, time);
-				// println!("Checking if body at index {} ({}) is the relative body {}", index, handle, relative);
-				if handle == relative {
-					return Some(relative_position);
-				}
-				// println!("Body at index {} ({}) is not the relative body {}. Incrementing index and trying again", index, handle, relative);
-				index += 1;
-				
+	/// Gets the offset of the given body from its idealized orbit caused by its satellites
+	/// 
+	/// Orbits in the database are really followed by the barycenter of a body and its satellites,
+	/// so the body itself circles that barycenter opposite its satellites. This is
+	/// *-Σ(mᵢ·rᵢ) / (M + Σmᵢ)* for satellites of mass *mᵢ* at positions *rᵢ* relative to the body.
+	/// For most planets it's well inside the planet, but Pluto and Charon's barycenter lies
+	/// outside Pluto entirely.
+	pub fn primary_wobble_at_time(&self, handle: &H, time: T) -> Vector3<T> where H: Debug, T: RealField {
+		let mut total_mass = self.get_entry(handle).info.mass_kg();
+		let mut weighted_position = Vector3::zeros();
+		for (satellite_handle, satellite) in self.bodies.iter() {
+			if satellite.parent.as_ref() != Some(handle) || satellite.orbit.is_none() || satellite.is_marker {
+				continue;
 			}
+			let mass = satellite.info.mass_kg();
+			let mean_anomaly = self.mean_anomaly_at_time(satellite_handle, time);
+			weighted_position += self.position_at_mean_anomaly(satellite_handle, mean_anomaly) * mass;
+			total_mass += mass;
 		}
-		while let Some(parent_handle) = &entry.parent {
-			entry = self.get_entry(parent_handle);
-			// println!("\tSubtracting orbital position of {}", parent_handle);
-			relative_position -= self.position_at_time(parent_handle, time);
-			// if the heirarchy of the relative body contains this body, start summing the orbits in that heirarchy
-			if let Ok(parent_relative_index) = relative_heirarchy.binary_search(&parent_handle) {
-				// println!("Reached heirarchy intersection at body {}", parent_handle);
-				let mut index = parent_relative_index;
-				let mut handle;
-				while index < relative_heirarchy.len() {
-					handle = &relative_heirarchy[index];
-					entry = self.get_entry(handle);
-					// println!("\tAdding orbital position of {}", handle);
-					relative_position += self.position_at_time(handle, time);
-					// println!("Checking if body at index {} ({}) is the relative body {}", index, handle, relative);
-					if handle == relative {
-						return Some(relative_position);
-					}
-					// println!("Body at index {} ({}) is not the relative body {}. Incrementing index and trying again", index, handle, relative);
-					index += 1;
-					
-				}
+		if total_mass <= T::from_f32(0.0).unwrap() {
+			return Vector3::zeros();
+		}
+		-weighted_position / total_mass
+	}
+	/// Gets the position of the given body relative to its parent at the given time, moved a
+	/// further `extra_anomaly` radians of mean anomaly along its orbit
+	/// 
+	/// Sweeping `extra_anomaly` from zero to *2π* traces out the whole orbit starting from the
+	/// body's current position, which is handy for drawing orbit paths.
+	pub fn position_at_time_plus_anomaly(&self, handle: &H, time: T, extra_anomaly: T) -> Vector3<T> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		let mean_anomaly = self.mean_anomaly_at_time(handle, time) + extra_anomaly;
+		self.position_at_mean_anomaly(handle, mean_anomaly)
+	}
+	/// Lazily samples `segments + 1` points around the orbit of the given body relative to its
+	/// parent, evenly spaced in mean anomaly from periapsis, for drawing its orbit path
+	/// 
+	/// The last point repeats the first so the points can be drawn as a closed line strip. They're
+	/// computed one at a time as the iterator is advanced, so they can be streamed straight into a
+	/// vertex buffer without allocating. Each point matches
+	/// [`position_at_mean_anomaly`](Self::position_at_mean_anomaly), and bodies that don't orbit
+	/// anything yield no points.
+	pub fn orbit_point_iter(&self, handle: &H, segments: usize) -> impl Iterator<Item = Vector3<T>> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let entry = self.get_entry(handle);
+		let orbit = entry.orbit;
+		// in-plane basis towards periapsis and the semi-latus rectum, rotated into the output frame
+		let (dir_periapsis, dir_semilatus_rectum) = match &orbit {
+			Some(orbit) => {
+				let parent_axis_rot = self.parent_axis_rotation(entry);
+				let half_pi = T::from_f64(std::f64::consts::FRAC_PI_2).unwrap();
+				(
+					self.apply_convention(parent_axis_rot * orbit.position_at_true_anomaly(zero).normalize()),
+					self.apply_convention(parent_axis_rot * orbit.position_at_true_anomaly(half_pi).normalize()),
+				)
+			},
+			None => (Vector3::zeros(), Vector3::zeros()),
+		};
+		let step = T::from_f64(std::f64::consts::TAU).unwrap() / T::from_usize(segments.max(1)).unwrap();
+		let count = if orbit.is_some() { segments + 1 } else { 0 };
+		(0..count).map(move |i| {
+			let orbit = orbit.unwrap();
+			let true_anomaly = orbit.true_anomaly_at_mean_anomaly(step * T::from_usize(i).unwrap());
+			let radius = orbit.semimajor_axis * (one - Float::powi(orbit.eccentricity, 2)) / (one + orbit.eccentricity * Float::cos(true_anomaly));
+			(dir_periapsis * Float::cos(true_anomaly) + dir_semilatus_rectum * Float::sin(true_anomaly)) * radius
+		})
+	}
+	/// Samples `segments + 1` points around the orbit of the given body relative to its parent,
+	/// flattened onto the reference plane for drawing on 2D maps
+	/// 
+	/// These are the points from [`orbit_point_iter`](Self::orbit_point_iter) with the out of
+	/// plane component dropped like in [`position_2d_at_time`](Self::position_2d_at_time), ready to
+	/// be drawn as an SVG polyline or an egui path.
+	pub fn orbit_path_2d(&self, handle: &H, segments: usize) -> Vec<(T, T)> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		self.orbit_point_iter(handle, segments).map(|point| self.project_2d(point)).collect()
+	}
+	/// Samples `segments + 1` points along the arc the given body travels between `from_time` and
+	/// `to_time`, relative to its parent
+	/// 
+	/// The first and last points are the body's [positions](Self::position_at_time) at the two
+	/// times, so passing a short window ending at the current time gives a motion trail that
+	/// follows the body. Points are evenly spaced in time, so they bunch up where the body moves
+	/// slowly. A window longer than the orbital period wraps around the orbit more than once.
+	pub fn recent_trail(&self, handle: &H, from_time: T, to_time: T, segments: usize) -> Vec<Vector3<T>> where H: Debug, T: RealField {
+		let one = T::from_f32(1.0).unwrap();
+		let segments = segments.max(1);
+		(0..=segments).map(|i| {
+			let fraction = T::from_usize(i).unwrap() / T::from_usize(segments).unwrap();
+			let time = from_time * (one - fraction) + to_time * fraction;
+			self.position_at_time(handle, time)
+		}).collect()
+	}
+	/// Gets the position of the body `relative` as seen from the body `origin` at the given time,
+	/// or `None` if it can't be found
+	/// 
+	/// See [`try_relative_position`](Self::try_relative_position) for why it can't be found.
+	pub fn relative_position(&self, origin: &H, relative: &H, time: T) -> Option<Vector3<T>> where H: Debug + Display + Ord, T: RealField + SimdValue + SimdRealField {
+		self.try_relative_position(origin, relative, time).ok()
+	}
+	/// Gets the position of the body `relative` as seen from the body `origin` at the given time
+	/// 
+	/// Fails if either body isn't in the database, or if the two bodies don't share a common
+	/// ancestor to measure both positions from.
+	pub fn try_relative_position(&self, origin: &H, relative: &H, time: T) -> Result<Vector3<T>, RelativePositionError<H>> where H: Debug + Display + Ord, T: RealField + SimdValue + SimdRealField {
+		let (origin_branch, relative_branch) = self.branches_below_common_ancestor(origin, relative)?;
+		let mut relative_position = Vector3::zeros();
+		for handle in &relative_branch {
+			relative_position += self.position_at_time(handle, time);
+		}
+		for handle in &origin_branch {
+			relative_position -= self.position_at_time(handle, time);
+		}
+		Ok(relative_position)
+	}
+	/// Splits the hierarchies of two bodies at their closest common ancestor, returning the bodies
+	/// below it down to and including `origin` and `relative`
+	/// 
+	/// Summing the positions along each branch gives each body's position relative to the common
+	/// ancestor, with every position on the way only needed once.
+	fn branches_below_common_ancestor(&self, origin: &H, relative: &H) -> Result<(Vec<H>, Vec<H>), RelativePositionError<H>> where H: Debug {
+		for handle in [origin, relative] {
+			if !self.bodies.contains_key(handle) {
+				return Err(RelativePositionError::UnknownHandle(handle.clone()));
 			}
 		}
-		return None;
+		let mut origin_hierarchy = self.get_parents(origin);
+		let mut relative_hierarchy = self.get_parents(relative);
+		let shared = origin_hierarchy.iter().zip(&relative_hierarchy).take_while(|(a, b)| a == b).count();
+		if shared == 0 {
+			return Err(RelativePositionError::DifferentSystems(origin.clone(), relative.clone()));
+		}
+		Ok((origin_hierarchy.split_off(shared), relative_hierarchy.split_off(shared)))
+	}
+	/// Gets the position of the parent of the body `handle` as seen from the body `origin` at the
+	/// given time, which is where the body's orbit is centered when drawing it
+	/// 
+	/// Returns `None` if the body doesn't orbit anything, or in the same cases as
+	/// [`relative_position`](Self::relative_position).
+	pub fn parent_position_relative_to(&self, handle: &H, origin: &H, time: T) -> Option<Vector3<T>> where H: Debug + Display + Ord, T: RealField + SimdValue + SimdRealField {
+		let parent_handle = self.bodies.get(handle)?.parent.as_ref()?;
+		self.relative_position(origin, parent_handle, time)
+	}
+	/// Gets the position of the body `handle` relative to the body `origin` flattened onto the
+	/// reference plane, for drawing top down 2D maps
+	/// 
+	/// The out of plane component is dropped, leaving the `(x, z)` coordinates with the default
+	/// Y-up [coordinate convention](CoordinateConvention) or the `(x, y)` coordinates with Z-up.
+	/// Returns `None` in the same cases as [`relative_position`](Self::relative_position).
+	pub fn position_2d_at_time(&self, origin: &H, handle: &H, time: T) -> Option<(T, T)> where H: Debug + Display + Ord, T: RealField + SimdValue + SimdRealField {
+		let position = self.relative_position(origin, handle, time)?;
+		Some(self.project_2d(position))
+	}
+	/// Drops the out of plane component of a vector in the database's coordinate convention
+	fn project_2d(&self, position: Vector3<T>) -> (T, T) where T: RealField {
+		match self.coordinate_convention {
+			CoordinateConvention::YUp => (position.x, position.z),
+			CoordinateConvention::ZUp => (position.x, position.y),
+		}
 	}
+	/// Gets the position of the given body relative to the root of its hierarchy at the given time
+	/// 
+	/// The positions of the body and each of its ancestors relative to their parents are summed in
+	/// `f64` before being converted back to `T`, so in an `f32` database a moon's small offset
+	/// from its planet isn't rounded away by adding it to the planet's huge distance from the sun
+	/// one step at a time. Returns the origin for bodies that aren't in the database.
 	pub fn absolute_position_at_time(&self, handle: &H, time: T) -> Vector3<T> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		self.absolute_position_at_time_f64(handle, time).map(|value| T::from_f64(value).unwrap())
+	}
+	/// Sums the positions of the given body and its ancestors relative to their parents in `f64`,
+	/// see [`absolute_position_at_time`](Self::absolute_position_at_time)
+	fn absolute_position_at_time_f64(&self, handle: &H, time: T) -> Vector3<f64> where H: Debug, T: RealField {
+		match self.bodies.get(handle) {
+			Some(entry) => {
+				let parent_position = match &entry.parent {
+					Some(parent_handle) => self.absolute_position_at_time_f64(parent_handle, time),
+					None => Vector3::zeros(),
+				};
+				let position = self.position_at_time(handle, time).map(|value| ToPrimitive::to_f64(&value).unwrap());
+				position + parent_position
+			},
+			None => Vector3::zeros(),
+		}
+	}
+	/// Gets the positions of every body in the same system as `origin` relative to it at the given
+	/// time, for drawing a whole system around one body
+	/// 
+	/// Each body's position is only worked out once, so this is much faster than calling
+	/// [`relative_position`](Self::relative_position) for every body. Bodies that aren't in the
+	/// same hierarchy as `origin` are left out, and the map is empty if `origin` isn't in the
+	/// database.
+	pub fn all_positions_relative_to(&self, origin: &H, time: T) -> HashMap<H, Vector3<T>> where H: Debug, T: RealField {
+		let mut absolute_positions = HashMap::with_capacity(self.bodies.len());
+		for handle in self.bodies.keys() {
+			self.absolute_position_and_root(handle, time, &mut absolute_positions);
+		}
+		let Some((origin_root, origin_position)) = absolute_positions.get(origin).cloned() else {
+			return HashMap::new();
+		};
+		absolute_positions.into_iter()
+			.filter(|(_, (root, _))| *root == origin_root)
+			.map(|(handle, (_, position))| (handle, (position - origin_position).map(|value| T::from_f64(value).unwrap())))
+			.collect()
+	}
+	/// Gets the position relative to `origin` and the [orientation](Self::orientation_at_time) of
+	/// every body in the same system as `origin` at the given time, for updating the transforms
+	/// of everything in a scene with one call per frame
+	/// 
+	/// The positions come from [`all_positions_relative_to`](Self::all_positions_relative_to),
+	/// so the same bodies are left out, and the bodies are in no particular order.
+	pub fn frame_update(&self, origin: &H, time: T) -> Vec<(H, Vector3<T>, Rotation3<T>)> where H: Debug, T: RealField {
+		self.all_positions_relative_to(origin, time).into_iter()
+			.map(|(handle, position)| {
+				let orientation = self.orientation_at_time(&handle, time);
+				(handle, position, orientation)
+			})
+			.collect()
+	}
+	/// Works out the root of the given body's hierarchy and its position relative to that root,
+	/// reusing and filling in the positions of its ancestors in `known`
+	fn absolute_position_and_root(&self, handle: &H, time: T, known: &mut HashMap<H, (H, Vector3<f64>)>) -> (H, Vector3<f64>) where H: Debug, T: RealField {
+		if let Some(found) = known.get(handle) {
+			return found.clone();
+		}
+		let (root, parent_position) = match &self.get_entry(handle).parent {
+			Some(parent_handle) => self.absolute_position_and_root(parent_handle, time, known),
+			None => (handle.clone(), Vector3::zeros()),
+		};
+		let position = self.position_at_time(handle, time).map(|value| ToPrimitive::to_f64(&value).unwrap());
+		let found = (root, parent_position + position);
+		known.insert(handle.clone(), found.clone());
+		found
+	}
+	/// Builds a spatial index over the absolute positions of every body at the given time, which
+	/// [`bodies_within`](Self::bodies_within) and
+	/// [`bodies_by_distance_from`](Self::bodies_by_distance_from) use for queries at that time
+	/// 
+	/// Without an index those queries work out the position of every body in the database each
+	/// time they're called. Building the index costs about the same as one of those queries, but
+	/// afterwards each query only has to look at the bodies in nearby cells of a grid, so it's
+	/// worth it when making several queries at the same time on a database with lots of bodies,
+	/// like thousands of asteroids. `cell_size` is the width of the grid's cubic cells in meters,
+	/// and works best close to the typical query radius.
+	/// 
+	/// The index is a snapshot, so rebuild it whenever the time being queried changes. Queries at
+	/// any other time ignore it, and anything that changes the database, like adding a body,
+	/// [stepping](Self::step) or a [burn](Self::apply_impulsive_burn), clears it.
+	pub fn rebuild_spatial_index(&mut self, time: T, cell_size: T) where H: Debug, T: RealField + SimdValue + SimdRealField {
+		let positions: Vec<(H, Vector3<T>)> = self.iter()
+			.filter(|(_, entry)| !entry.is_marker)
+			.map(|(handle, _)| (handle.clone(), self.absolute_position_at_time(handle, time)))
+			.collect();
+		self.spatial_index = Some(SpatialIndex::new(time, cell_size, positions));
+	}
+	/// Removes the spatial index built by [`rebuild_spatial_index`](Self::rebuild_spatial_index)
+	pub fn clear_spatial_index(&mut self) {
+		self.spatial_index = None;
+	}
+	/// Spatial index built for the given time, if there is one
+	fn spatial_index_at(&self, time: T) -> Option<&SpatialIndex<H, T>> {
+		self.spatial_index.as_ref().filter(|index| index.time == time)
+	}
+	/// Gets the distance from the given point to every body other than markers at the given time
+	/// as `(handle, distance)` pairs
+	fn distances_from(&self, point: &Vector3<T>, time: T) -> Vec<(H, T)> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		self.iter()
+			.filter(|(_, entry)| !entry.is_marker)
+			.map(|(handle, _)| (handle.clone(), (self.absolute_position_at_time(handle, time) - point).norm()))
+			.collect()
+	}
+	/// Finds every body whose center is within `radius` meters of the given point in world space at
+	/// the given time, as `(handle, distance)` pairs sorted nearest first
+	/// 
+	/// [Markers](DatabaseEntry::is_marker) aren't bodies you can run into, so they're skipped.
+	/// Uses the [spatial index](Self::rebuild_spatial_index) if one was built for this time.
+	pub fn bodies_within(&self, point: Vector3<T>, radius: T, time: T) -> Vec<(H, T)> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		let mut found = match self.spatial_index_at(time) {
+			Some(index) => index.within(&point, radius),
+			None => {
+				let mut found = self.distances_from(&point, time);
+				found.retain(|(_, distance)| *distance <= radius);
+				found
+			},
+		};
+		found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+		found
+	}
+	/// Finds the `count` bodies whose centers are closest to the given point in world space at the
+	/// given time, as `(handle, distance)` pairs sorted nearest first
+	/// 
+	/// [Markers](DatabaseEntry::is_marker) are skipped like in
+	/// [`bodies_within`](Self::bodies_within). Uses the [spatial index](Self::rebuild_spatial_index)
+	/// if one was built for this time.
+	pub fn bodies_by_distance_from(&self, point: Vector3<T>, time: T, count: usize) -> Vec<(H, T)> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		if let Some(index) = self.spatial_index_at(time) {
+			return index.nearest(&point, count);
+		}
+		let mut found = self.distances_from(&point, time);
+		found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+		found.truncate(count);
+		found
+	}
+	/// Calculates the total gravitational acceleration from every body in the database at the given
+	/// point in world space, in meters per second squared (m/s²)
+	/// 
+	/// With no `softening_length` each body pulls with *GM/r²*, which is exact but blows up near a
+	/// body's center. Passing a softening length *ε* in meters (m) uses the Plummer softened pull
+	/// *GM·r/(r² + ε²)^(3/2)* instead, which matches *GM/r²* far from the body, peaks at
+	/// *r = ε/√2* and goes smoothly to zero at the body's center. `None` is the same as *ε = 0*. [Markers](DatabaseEntry::is_marker) don't pull on anything.
+	pub fn gravity_at_point(&self, point: Vector3<T>, time: T, softening_length: Option<T>) -> Vector3<T> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let softening_squared = Float::powi(softening_length.unwrap_or(zero), 2);
+		let mut acceleration = Vector3::new(zero, zero, zero);
+		for (handle, entry) in self.iter().filter(|(_, entry)| !entry.is_marker) {
+			let offset = self.absolute_position_at_time(handle, time) - point;
+			let distance_squared = offset.norm_squared() + softening_squared;
+			// a³ from the softened distance, so the direction vector doesn't need normalizing
+			acceleration += offset * (entry.gm() / (distance_squared * Float::sqrt(distance_squared)));
+		}
+		acceleration
+	}
+	/// Checks whether the straight line between two points in world space is clear of every body in
+	/// the database at the given time
+	/// 
+	/// See [`los_blocking_body`](Self::los_blocking_body) for details.
+	pub fn can_see(&self, point_a: Vector3<T>, point_b: Vector3<T>, time: T) -> bool where H: Debug, T: RealField + SimdValue + SimdRealField {
+		self.los_blocking_body(point_a, point_b, time).is_none()
+	}
+	/// Finds the body that blocks the straight line from `point_a` to `point_b` at the given time,
+	/// or `None` if the line of sight is clear
+	/// 
+	/// Bodies are treated as ellipsoids with their equatorial and polar radii, flattened along
+	/// their [rotation axis](Self::equatorial_normal). Points sitting right on a body's surface,
+	/// like an observer on the ground, can see anything above their horizon. If several bodies are
+	/// in the way, the one closest to `point_a` is returned. [Markers](DatabaseEntry::is_marker)
+	/// never block anything.
+	pub fn los_blocking_body(&self, point_a: Vector3<T>, point_b: Vector3<T>, time: T) -> Option<H> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		self.los_blocking_body_ignoring(point_a, point_b, time, &[])
+	}
+	/// Checks whether the bodies `a` and `b` can see each other at the given time, with the line
+	/// between their centers clear of every other body
+	/// 
+	/// Bodies are treated as ellipsoids like in [`los_blocking_body`](Self::los_blocking_body).
+	/// Both bodies are ignored as occluders themselves, so a satellite can always see the planet
+	/// it orbits, but not a satellite on the far side of it.
+	/// 
+	/// # Panics
+	/// 
+	/// Panics if either body isn't in the database
+	pub fn has_line_of_sight(&self, a: &H, b: &H, time: T) -> bool where H: Debug, T: RealField + SimdValue + SimdRealField {
+		self.get_entry(a);
+		self.get_entry(b);
+		let point_a = self.absolute_position_at_time(a, time);
+		let point_b = self.absolute_position_at_time(b, time);
+		self.los_blocking_body_ignoring(point_a, point_b, time, &[a, b]).is_none()
+	}
+	/// Works out the shape of the shadow that `occluder` casts away from `light` at the given time
+	/// 
+	/// Both bodies are treated as spheres of their average radius. Returns `None` if either body
+	/// isn't in the database or they're in different systems, or if the occluder is at least as
+	/// big as the light, since then its umbra never ends.
+	pub fn shadow_geometry(&self, occluder: &H, light: &H, time: T) -> Option<ShadowCone<T>> where H: Debug + Display + Ord, T: RealField + SimdValue + SimdRealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let offset = self.relative_position(light, occluder, time)?;
+		let distance = offset.norm();
+		let occluder_radius = self.get_entry(occluder).info.radius_avg_m();
+		let light_radius = self.get_entry(light).info.radius_avg_m();
+		if distance <= zero || light_radius <= occluder_radius {
+			return None;
+		}
+		Some(ShadowCone{
+			axis: offset / distance,
+			occluder_radius,
+			umbra_length: distance * occluder_radius / (light_radius - occluder_radius),
+			penumbra_apex_distance: distance * occluder_radius / (light_radius + occluder_radius),
+		})
+	}
+	/// Finds the body closest to `point_a` that blocks the line to `point_b`, skipping the bodies
+	/// in `ignored`
+	fn los_blocking_body_ignoring(&self, point_a: Vector3<T>, point_b: Vector3<T>, time: T, ignored: &[&H]) -> Option<H> where H: Debug, T: RealField + SimdValue + SimdRealField {
 		let zero = T::from_f32(0.0).unwrap();
-		if let Some(entry) = self.bodies.get(&handle) {
-			let parent_position = match &entry.parent {
-				Some(parent_handle) => self.absolute_position_at_time(parent_handle, time),
-				None => Vector3::new(zero, zero, zero),
+		let one = T::from_f32(1.0).unwrap();
+		let segment = point_b - point_a;
+		if segment.norm() == zero {
+			return None;
+		}
+		let mut closest: Option<(T, H)> = None;
+		for (handle, entry) in self.iter() {
+			let radius = entry.info.radius_equator_m();
+			if radius <= zero || entry.is_marker || ignored.contains(&handle) {
+				continue;
+			}
+			// stretch space along the body's axis so it becomes a sphere with its equatorial radius
+			let axis = self.equatorial_normal(handle, time);
+			let polar_stretch = match entry.info.radius_polar_km() > zero {
+				true => entry.info.radius_equator_km() / entry.info.radius_polar_km() - one,
+				false => zero,
 			};
-			return self.position_at_time(handle, time) + parent_position;
-		} else {
-			return Vector3::new(zero, zero, zero);
+			let stretch = |vector: Vector3<T>| vector + axis * (vector.dot(&axis) * polar_stretch);
+			let to_center = stretch(self.absolute_position_at_time(handle, time) - point_a);
+			let segment = stretch(segment);
+			let length = segment.norm();
+			let direction = segment / length;
+			// distances along the line where it enters and leaves the body's sphere
+			let along = to_center.dot(&direction);
+			let half_chord_squared = radius * radius - (to_center.norm_squared() - along * along);
+			if half_chord_squared <= zero {
+				continue;
+			}
+			let half_chord = Float::sqrt(half_chord_squared);
+			let enter = Float::max(along - half_chord, zero);
+			let exit = Float::min(along + half_chord, length);
+			// ignore lines that only graze the surface, which is where ground observers sit
+			if exit - enter <= radius * T::from_f64(1e-6).unwrap() {
+				continue;
+			}
+			// compare bodies by the fraction of the way along the line, since each is stretched
+			// differently
+			let enter_fraction = enter / length;
+			if closest.as_ref().is_none_or(|(fraction, _)| enter_fraction < *fraction) {
+				closest = Some((enter_fraction, handle.clone()));
+			}
 		}
+		closest.map(|(_, handle)| handle)
+	}
+	/// Approximates how bright the body `target` appears from the body `observer` at the given
+	/// time, as the flux of `illuminator`'s light it reflects towards the observer in watts per
+	/// square meter (W/m²)
+	/// 
+	/// The illuminator's [luminosity](Body::luminosity_w) falls off with the inverse square of its
+	/// distance to the target, which reflects the fraction given by its [albedo](Body::albedo)
+	/// over its cross section. The reflected light falls off with the inverse square of the
+	/// distance to the observer, and is scaled by the Lambert phase function
+	/// *(sin α + (π - α)cos α)/π* of the phase angle *α* between the illuminator and observer as
+	/// seen from the target, so a fully lit target is brightest. Bodies in the way aren't taken
+	/// into account.
+	/// 
+	/// Returns `None` if any of the bodies aren't in the database or part of the same system.
+	pub fn apparent_brightness(&self, observer: &H, target: &H, illuminator: &H, time: T) -> Option<T> where H: Debug + Display + Ord, T: RealField + SimdValue + SimdRealField {
+		let pi = T::from_f64(std::f64::consts::PI).unwrap();
+		let four_pi = T::from_f64(4.0 * std::f64::consts::PI).unwrap();
+		let to_observer = -self.relative_position(observer, target, time)?;
+		let to_illuminator = self.relative_position(target, illuminator, time)?;
+		let target_info = &self.bodies.get(target)?.info;
+		let illuminator_info = &self.bodies.get(illuminator)?.info;
+		let illuminator_distance_squared = to_illuminator.norm_squared();
+		let observer_distance_squared = to_observer.norm_squared();
+		let cos_phase = Float::max(Float::min(to_observer.dot(&to_illuminator) / Float::sqrt(illuminator_distance_squared * observer_distance_squared), T::one()), -T::one());
+		let phase = Float::acos(cos_phase);
+		let phase_function = (Float::sin(phase) + (pi - phase) * cos_phase) / pi;
+		let incoming_flux = illuminator_info.luminosity_w() / (four_pi * illuminator_distance_squared);
+		let cross_section = pi * Float::powi(target_info.radius_avg_m(), 2);
+		Some(incoming_flux * target_info.albedo() * cross_section * phase_function / (pi * observer_distance_squared))
+	}
+	/// Calculates the flux of `star`'s light falling on `body` at the given time, in watts per
+	/// square meter (W/m²)
+	/// 
+	/// The star's [luminosity](Body::luminosity_w), worked out from its radius and surface
+	/// temperature, spread over a sphere as far out as the body currently is. Earth gets about
+	/// 1361 W/m². Returns `None` if either body isn't in the database or they're in separate
+	/// systems.
+	pub fn insolation_w_m2(&self, body: &H, star: &H, time: T) -> Option<T> where H: Debug + Display + Ord, T: RealField + SimdValue + SimdRealField {
+		let four_pi = T::from_f64(4.0 * std::f64::consts::PI).unwrap();
+		let distance_squared = self.relative_position(body, star, time)?.norm_squared();
+		Some(self.bodies.get(star)?.info.luminosity_w() / (four_pi * distance_squared))
+	}
+	/// Calculates the equilibrium temperature of `body` heated by `star` at the given time, in
+	/// kelvin (K)
+	/// 
+	/// This is the temperature of a black body that absorbs the [insolation](Self::insolation_w_m2)
+	/// not reflected by its [Bond albedo](Body::bond_albedo) and radiates it away evenly over its
+	/// whole surface, *T = (S(1 - A)/4σ)^(1/4)*. It leaves out greenhouse warming and internal
+	/// heat, so Earth comes out at about 255 K rather than its actual average of 288 K. Returns
+	/// `None` if either body isn't in the database or they're in separate systems.
+	pub fn equilibrium_temperature_k(&self, body: &H, star: &H, time: T) -> Option<T> where H: Debug + Display + Ord, T: RealField + SimdValue + SimdRealField {
+		let one = T::from_f32(1.0).unwrap();
+		let sigma = T::from_f64(CONST_STEFAN_BOLTZMANN).unwrap();
+		let insolation = self.insolation_w_m2(body, star, time)?;
+		let absorbed = insolation * (one - self.bodies.get(body)?.info.bond_albedo());
+		Some(Float::powf(absorbed / (T::from_f32(4.0).unwrap() * sigma), T::from_f64(0.25).unwrap()))
 	}
 	/// Get a list of handles for satellites of the body with the input handle.
 	pub fn get_satellites(&self, body: &H) -> Vec<H> where H: Ord {
@@ -1078,6 +2015,14 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 		satellites.sort();
 		satellites
 	}
+	/// Get a sorted list of handles for the other satellites of the input body's parent, which is
+	/// empty for root bodies
+	pub fn siblings(&self, handle: &H) -> Vec<H> where H: Ord {
+		match self.bodies.get(handle).and_then(|entry| entry.parent.as_ref()) {
+			Some(parent_handle) => self.get_satellites(parent_handle).into_iter().filter(|sibling| sibling != handle).collect(),
+			None => Vec::new(),
+		}
+	}
 	/// Get the heirarchy of parent bodies of the input body
 	pub fn get_parents(&self, body: &H) -> Vec<H> where H: Debug {
 		let body_entry = self.get_entry(&body);
@@ -1089,52 +2034,1500 @@ impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone +
 			return vec![body.clone()];
 		}
 	}
+	/// Finds the closest body that both of the given bodies are in the heirarchy of, which is one
+	/// of the bodies themselves if it orbits the other
+	/// 
+	/// Returns `None` if either body isn't in the database, or if they're in separate heirarchies
+	/// with different root bodies.
+	pub fn common_ancestor(&self, a: &H, b: &H) -> Option<H> where H: Debug {
+		if !self.bodies.contains_key(a) || !self.bodies.contains_key(b) {
+			return None;
+		}
+		self.get_parents(a).into_iter()
+			.zip(self.get_parents(b))
+			.take_while(|(parent_a, parent_b)| parent_a == parent_b)
+			.last()
+			.map(|(ancestor, _)| ancestor)
+	}
+	/// Gets every body in the database paired with its number of satellites, sorted from most to
+	/// fewest satellites
+	pub fn satellite_count_by_body(&self) -> Vec<(H, usize)> where H: Ord {
+		let mut counts: HashMap<H, usize> = self.bodies.keys().map(|handle| (handle.clone(), 0)).collect();
+		for entry in self.bodies.values() {
+			if let Some(count) = entry.parent.as_ref().and_then(|parent| counts.get_mut(parent)) {
+				*count += 1;
+			}
+		}
+		let mut counts: Vec<(H, usize)> = counts.into_iter().collect();
+		counts.sort_by(|(handle_a, count_a), (handle_b, count_b)| count_b.cmp(count_a).then_with(|| handle_a.cmp(handle_b)));
+		counts
+	}
+	/// Gets the body with the most satellites along with its number of satellites, or `None` if
+	/// the database is empty
+	pub fn most_moons_body(&self) -> Option<(H, usize)> where H: Ord {
+		self.satellite_count_by_body().into_iter().next()
+	}
+	/// Counts every body in the subtree under the given body, its satellites along with their
+	/// own satellites and so on, not including the body itself
+	/// 
+	/// Walks up the parents of each body in the database once, so unlike counting with repeated
+	/// calls to [`get_satellites`](Self::get_satellites) it doesn't sort or allocate anything.
+	pub fn descendant_count(&self, body: &H) -> usize {
+		self.bodies.values()
+			.filter(|entry| {
+				let mut parent = entry.parent.as_ref();
+				while let Some(parent_handle) = parent {
+					if parent_handle == body {
+						return true;
+					}
+					parent = self.bodies.get(parent_handle).and_then(|parent_entry| parent_entry.parent.as_ref());
+				}
+				false
+			})
+			.count()
+	}
 	/// Gets the combined mass of a body and all its satellites
+	/// 
+	/// [Markers](DatabaseEntry::is_marker) add no mass of their own, but the satellites of a
+	/// marker, like the pair of stars orbiting a barycenter, are still counted.
 	pub fn get_combined_mass_kg(&self, body: &H) -> T where H: Debug + Ord {
 		let body_entry = self.get_entry(body);
-		let mut total_mass = body_entry.info.mass_kg();
+		let mut total_mass = if body_entry.is_marker { T::from_f32(0.0).unwrap() } else { body_entry.info.mass_kg() };
 		for satellite_handle in self.get_satellites(body) {
 			total_mass = total_mass + self.get_combined_mass_kg(&satellite_handle);
 		}
 		return total_mass;
 	}
 	/// Calculate the radius of the sphere of influence of the body with the given handle
+	/// 
+	/// Root bodies without an orbit, like the Sun or a free-floating rogue planet, have no parent
+	/// to compete with, so their sphere of influence extends out to where their gravity drops
+	/// below a small threshold. It's always made large enough to contain the orbits of all of
+	/// their satellites other than [markers](DatabaseEntry::is_marker), so that a light root body
+	/// doesn't end up with moons outside its own sphere of influence. Bodies orbiting a massless
+	/// parent or a marker, like a placeholder barycenter, are treated the same way since their
+	/// parent's gravity doesn't compete with theirs either.
 	pub fn radius_soi(&self, handle: &H) -> T where H: Debug + Ord {
+		let zero = T::from_f32(0.0).unwrap();
 		let orbiting_body = self.bodies.get(&handle).unwrap();
 		let orbiting_body_info = orbiting_body.info.clone();
 		let orbiting_body_mass = self.get_combined_mass_kg(handle);
-		if let Some(orbit) = &orbiting_body.orbit {
-			let parent_body = self.bodies.get(&orbiting_body.parent.clone().unwrap()).unwrap();
-			let parent_body_info = parent_body.info.clone();
+		let parent_mass = match orbiting_body.parent.as_ref().map(|parent_handle| self.get_entry(parent_handle)) {
+			Some(parent) if !parent.is_marker => parent.info.mass_kg(),
+			_ => zero,
+		};
+		if let (Some(orbit), true) = (&orbiting_body.orbit, parent_mass > zero) {
 			let exponent = T::from_f64(2.0 / 5.0).unwrap();
-			return orbit.semimajor_axis * (orbiting_body_mass / parent_body_info.mass_kg()).powf(exponent);
+			return orbit.semimajor_axis * (orbiting_body_mass / parent_mass).powf(exponent);
 		} else {
+			let one = T::from_f32(1.0).unwrap();
 			let minimum_gravity = T::from_f64(0.0000005).unwrap();
-			return orbiting_body_info.distance_of_gravity(minimum_gravity);
+			let mut radius = orbiting_body_info.distance_of_gravity(minimum_gravity);
+			for satellite_handle in self.get_satellites(handle) {
+				let satellite = self.get_entry(&satellite_handle);
+				if satellite.is_marker {
+					continue;
+				}
+				if let Some(orbit) = &satellite.orbit {
+					if orbit.eccentricity < one {
+						let apoapsis = orbit.semimajor_axis * (one + orbit.eccentricity);
+						radius = Float::max(radius, apoapsis + self.radius_soi(&satellite_handle));
+					}
+				}
+			}
+			return radius;
+		}
+	}
+	/// Calculates the radius of the Hill sphere of the body with the given handle, the region in
+	/// which it can hold on to satellites against its parent's tides
+	/// 
+	/// *r = a(1 - e)·cbrt(m / 3M)*, using the combined mass of the body and its satellites.
+	/// Bodies that don't orbit anything have an unbounded Hill sphere, so this returns infinity
+	/// for them. A parent with no mass, like a placeholder barycenter or a
+	/// [marker](DatabaseEntry::is_marker), raises no tides to compete with, so the body's
+	/// [sphere of influence](Self::radius_soi) is returned instead, which is finite.
+	pub fn radius_hill_sphere(&self, handle: &H) -> T where H: Debug + Ord {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let entry = self.get_entry(handle);
+		match (&entry.parent, &entry.orbit) {
+			(Some(parent_handle), Some(orbit)) => {
+				let parent = self.get_entry(parent_handle);
+				let parent_mass = if parent.is_marker { zero } else { parent.info.mass_kg() };
+				if parent_mass <= zero {
+					return self.radius_soi(handle);
+				}
+				let mass_ratio = self.get_combined_mass_kg(handle) / (T::from_f32(3.0).unwrap() * parent_mass);
+				orbit.semimajor_axis * (one - orbit.eccentricity) * Float::cbrt(mass_ratio)
+			},
+			_ => T::infinity(),
+		}
+	}
+	/// Gets the range of distances from the given body, as `(inner, outer)` in meters, where a
+	/// satellite could orbit it for a long time, for placing procedurally generated moons
+	/// 
+	/// The inner bound is the fluid Roche limit *2.44·R·cbrt(ρ/ρ_s)* for a rocky satellite with a
+	/// nominal density *ρ_s* of 3000 kg/m³, inside which it would be torn apart by tides. The
+	/// outer bound is a third of the body's [Hill sphere](Self::radius_hill_sphere), past which
+	/// prograde orbits are pulled away by the body's parent within a few orbits. Retrograde
+	/// satellites can stay bound out to about half the Hill radius. Returns `None` if the body
+	/// doesn't orbit anything, is massless, or is so close to its parent that there's no stable
+	/// range at all.
+	pub fn stable_satellite_range(&self, body: &H) -> Option<(T, T)> where H: Debug + Ord {
+		let info = &self.bodies.get(body)?.info;
+		let satellite_density = T::from_f32(3000.0).unwrap();
+		let radius = info.radius_avg_m();
+		let volume = T::from_f64(4.0 / 3.0 * std::f64::consts::PI).unwrap() * radius * radius * radius;
+		let density = info.mass_kg() / volume;
+		if Float::is_nan(density) || density <= T::from_f32(0.0).unwrap() {
+			return None;
+		}
+		let inner = T::from_f64(2.44).unwrap() * radius * Float::cbrt(density / satellite_density);
+		let outer = self.radius_hill_sphere(body) / T::from_f32(3.0).unwrap();
+		if Float::is_infinite(outer) || outer <= inner {
+			return None;
+		}
+		Some((inner, outer))
+	}
+	/// Calculates the radii of the Roche lobes of a binary pair, where `secondary` orbits
+	/// `primary`, returned as `(primary_lobe, secondary_lobe)` in meters
+	/// 
+	/// Each body's Roche lobe is the teardrop shaped region around it where material is bound to
+	/// it rather than to its companion, the binary equivalent of a
+	/// [sphere of influence](Self::radius_soi). Uses Eggleton's approximation for the radius of a
+	/// sphere with the same volume as the lobe, *r = a·0.49q^(2/3) / (0.6q^(2/3) + ln(1 + q^(1/3)))*
+	/// with *q* the mass of the body over the mass of its companion and *a* the secondary's
+	/// semimajor axis. Returns `None` if `secondary` doesn't orbit `primary` or either body is
+	/// massless.
+	pub fn roche_lobe_radius(&self, primary: &H, secondary: &H) -> Option<(T, T)> {
+		let zero = T::from_f32(0.0).unwrap();
+		let secondary_entry = self.bodies.get(secondary)?;
+		if secondary_entry.parent.as_ref() != Some(primary) {
+			return None;
+		}
+		let separation = secondary_entry.orbit?.semimajor_axis;
+		let primary_mass = self.bodies.get(primary)?.info.mass_kg();
+		let secondary_mass = secondary_entry.info.mass_kg();
+		if primary_mass <= zero || secondary_mass <= zero {
+			return None;
+		}
+		let eggleton = |mass_ratio: T| {
+			let q_two_thirds = Float::powf(mass_ratio, T::from_f64(2.0 / 3.0).unwrap());
+			T::from_f32(0.49).unwrap() * q_two_thirds / (T::from_f32(0.6).unwrap() * q_two_thirds + Float::ln(T::from_f32(1.0).unwrap() + Float::cbrt(mass_ratio)))
+		};
+		Some((separation * eggleton(primary_mass / secondary_mass), separation * eggleton(secondary_mass / primary_mass)))
+	}
+	/// Calculates the Schwarzschild radius *2GM/c²* of the body with the given handle in meters
+	/// (m), the radius its mass would need to be squeezed into to become a black hole
+	pub fn schwarzschild_radius_m(&self, handle: &H) -> T where H: Debug {
+		let c = T::from_f64(SPEED_OF_LIGHT_M_PER_S).unwrap();
+		T::from_f32(2.0).unwrap() * self.get_entry(handle).gm() / (c * c)
+	}
+	/// Checks whether the body with the given handle is a black hole, with its radius inside its
+	/// [Schwarzschild radius](Self::schwarzschild_radius_m)
+	pub fn is_black_hole(&self, handle: &H) -> bool where H: Debug {
+		self.get_entry(handle).info.radius_equator_m() < self.schwarzschild_radius_m(handle)
+	}
+	/// Checks whether the body with the given handle orbits its parent retrograde, against the
+	/// direction its parent rotates, with an inclination of more than 90° to its parent's equator
+	/// 
+	/// Returns `None` if the body isn't in the database or doesn't orbit anything.
+	pub fn is_retrograde_orbit(&self, handle: &H) -> Option<bool> {
+		let orbit = self.bodies.get(handle)?.orbit.as_ref()?;
+		Some(orbit.inclination > T::from_f64(std::f64::consts::FRAC_PI_2).unwrap())
+	}
+	/// Checks whether the body with the given handle rotates retrograde, against the direction it
+	/// orbits in, with an axial tilt of more than 90°
+	/// 
+	/// Returns `None` if the body isn't in the database.
+	pub fn is_retrograde_rotation(&self, handle: &H) -> Option<bool> {
+		let info = &self.bodies.get(handle)?.info;
+		Some(info.axial_tilt_rad() > T::from_f64(std::f64::consts::FRAC_PI_2).unwrap())
+	}
+	/// Suggests an RGB color for drawing the orbit of the body with the given handle, based on the
+	/// shape of its orbit
+	/// 
+	/// Retrograde orbits, inclined more than 90° to their parent's equator, are blue. Otherwise
+	/// eccentric orbits with *e > 0.3* are orange, near circular orbits with *e < 0.01* are green
+	/// and everything else, including bodies that don't orbit anything, is yellow.
+	pub fn orbit_color_suggestion(&self, handle: &H) -> (f32, f32, f32) where H: Debug {
+		let Some(orbit) = &self.get_entry(handle).orbit else {
+			return (1.0, 0.85, 0.2);
+		};
+		if self.is_retrograde_orbit(handle) == Some(true) {
+			(0.3, 0.5, 1.0)
+		} else if orbit.eccentricity > T::from_f64(0.3).unwrap() {
+			(1.0, 0.55, 0.1)
+		} else if orbit.eccentricity < T::from_f64(0.01).unwrap() {
+			(0.3, 0.9, 0.3)
+		} else {
+			(1.0, 0.85, 0.2)
 		}
 	}
+	/// Gets the mean anomaly *M* of the given body at the given time, which is zero for bodies
+	/// that don't orbit anything
+	/// 
+	/// For closed orbits this is wrapped into `[0, 2π)`, while hyperbolic orbits keep counting up
+	/// from periapsis. Bodies orbiting a massless parent have no mean motion, so they stay at
+	/// their mean anomaly at epoch forever.
 	pub fn mean_anomaly_at_time(&self, handle: &H, time: T) -> T where H: Debug {
 		let orbiting_entry = self.get_entry(handle);
 		if let Some(parent_handle) = &orbiting_entry.parent {
 			let orbit = orbiting_entry.orbit.clone().unwrap();
 			let parent_entry = self.get_entry(parent_handle);
-			let n = Float::sqrt(parent_entry.gm() / Float::powi(orbit.semimajor_axis, 3));
-			let mean_anomaly = orbiting_entry.mean_anomaly_at_epoch + n * time; 
-			return mean_anomaly;
+			// worked out in f64 so that a long time in an f32 database doesn't leave only a few
+			// bits of precision for the angle within the current orbit
+			let to_f64 = |value: T| value.to_f64().unwrap();
+			let n = match orbiting_entry.fixed_mean_motion {
+				Some(n) => to_f64(n),
+				None => mean_motion(to_f64(parent_entry.gm()), to_f64(orbit.semimajor_axis)),
+			};
+			let mean_anomaly = to_f64(orbiting_entry.mean_anomaly_at_epoch) + n * to_f64(time);
+			return Self::wrap_mean_anomaly(&orbit, mean_anomaly);
 		} else {
 			return T::from_f32(0.0).unwrap();
 		}
 	}
-	pub fn iter(&self) -> Iter<'_, H, DatabaseEntry<H, T>> {
-		self.bodies.iter()
+	/// Calculates the distance in meters (m) between the given body and its parent at the given
+	/// time, or `None` if the body doesn't orbit anything
+	pub fn orbital_radius_at_time(&self, handle: &H, time: T) -> Option<T> where H: Debug, T: RealField {
+		let one = T::from_f32(1.0).unwrap();
+		let orbit = self.bodies.get(handle)?.orbit?;
+		let e = orbit.eccentricity;
+		let true_anomaly = orbit.true_anomaly_at_mean_anomaly(self.mean_anomaly_at_time(handle, time));
+		Some(orbit.semimajor_axis * (one - e * e) / (one + e * Float::cos(true_anomaly)))
 	}
-}
-impl<H, T> Default for Database<H, T> {
-	fn default() -> Self {
-		Self{ bodies: HashMap::new() }
+	/// Calculates the optical libration in longitude of the given body at the given time, in
+	/// radians
+	/// 
+	/// A tidally locked body spins at a constant rate that keeps pace with its mean anomaly, while
+	/// it moves along its orbit according to its true anomaly. The difference between the two, the
+	/// equation of center *ν - M*, is how far the body appears to turn east or west as seen from
+	/// its parent. Returns `None` if the body doesn't orbit anything.
+	pub fn libration_longitude_at_time(&self, handle: &H, time: T) -> Option<T> where H: Debug, T: RealField {
+		let pi = T::from_f64(std::f64::consts::PI).unwrap();
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		let orbit = self.bodies.get(handle)?.orbit?;
+		let mean_anomaly = self.mean_anomaly_at_time(handle, time);
+		let true_anomaly = orbit.true_anomaly_at_mean_anomaly(mean_anomaly);
+		let mut libration = (true_anomaly - mean_anomaly) % tau;
+		if libration > pi {
+			libration -= tau;
+		} else if libration < -pi {
+			libration += tau;
+		}
+		Some(libration)
 	}
-}
-
+	/// Gets the satellites of the given body that are closer to it than `max_radius` meters at the
+	/// given time, sorted by handle
+	pub fn satellites_within_radius(&self, parent: &H, max_radius: T, time: T) -> Vec<H> where H: Debug + Ord, T: RealField {
+		self.get_satellites(parent).into_iter()
+			.filter(|handle| self.orbital_radius_at_time(handle, time).is_some_and(|radius| radius < max_radius))
+			.collect()
+	}
+	/// Calculates the next time at or after `from_time` that the body crosses out of its parent's
+	/// sphere of influence
+	/// 
+	/// If the body is already outside its parent's sphere of influence at `from_time`, that's
+	/// returned as is. Returns `None` if the body has no parent or if its orbit stays entirely
+	/// inside the parent's sphere of influence.
+	pub fn time_to_soi_exit(&self, handle: &H, from_time: T) -> Option<T> where H: Debug + Ord, T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		let entry = self.bodies.get(handle)?;
+		let parent_handle = entry.parent.as_ref()?;
+		let orbit = entry.orbit.as_ref()?;
+		let radius_soi = self.radius_soi(parent_handle);
+		if self.orbital_radius_at_time(handle, from_time)? >= radius_soi {
+			return Some(from_time);
+		}
+		let e = orbit.eccentricity;
+		if e < one && orbit.semimajor_axis * (one + e) <= radius_soi {
+			return None;
+		}
+		let (exit_true_anomaly, _) = orbit.true_anomaly_at_radius(radius_soi)?;
+		let exit_mean_anomaly = orbit.mean_anomaly_at_true_anomaly(exit_true_anomaly);
+		let n = self.mean_motion_of(handle)?;
+		let current_mean_anomaly = self.mean_anomaly_at_time(handle, from_time);
+		if e < one {
+			let mut delta = (exit_mean_anomaly - current_mean_anomaly) % tau;
+			if delta < zero {
+				delta += tau;
+			}
+			Some(from_time + delta / n)
+		} else if current_mean_anomaly <= exit_mean_anomaly {
+			Some(from_time + (exit_mean_anomaly - current_mean_anomaly) / n)
+		} else {
+			None
+		}
+	}
+	/// Calculates the mean longitude *λ = Ω + ω + M* of the given body at the given time
+	pub fn mean_longitude_at_time(&self, handle: &H, time: T) -> T where H: Debug {
+		let entry = self.get_entry(handle);
+		match &entry.orbit {
+			Some(orbit) => orbit.long_of_ascending_node + orbit.arg_of_periapsis + self.mean_anomaly_at_time(handle, time),
+			None => T::from_f32(0.0).unwrap(),
+		}
+	}
+	/// Calculates a generalized resonance angle *φ = Σ kᵢλᵢ* in radians, wrapped to `[0, 2π)`
+	/// 
+	/// `handles` and `coefficients` are paired up one to one, with each coefficient *kᵢ*
+	/// multiplying the mean longitude *λᵢ* of the matching body. For a true mean-motion resonance
+	/// the angle librates around a fixed value instead of circulating. Returns `None` if there
+	/// isn't exactly one coefficient per handle.
+	pub fn resonance_angle(&self, handles: &[H], coefficients: &[i32], time: T) -> Option<T> where H: Debug {
+		if handles.len() != coefficients.len() {
+			return None;
+		}
+		let zero = T::from_f32(0.0).unwrap();
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		let mut angle = zero;
+		for (handle, coefficient) in handles.iter().zip(coefficients) {
+			angle = angle + T::from_i32(*coefficient).unwrap() * self.mean_longitude_at_time(handle, time);
+		}
+		angle = angle % tau;
+		if angle < zero {
+			angle = angle + tau;
+		}
+		Some(angle)
+	}
+	/// Calculates the Laplace resonance angle *φ = λ₁ - 3λ₂ + 2λ₃* in radians for the Galilean
+	/// moons Io, Europa and Ganymede
+	/// 
+	/// The real moons pull on each other enough to hold this angle near 180°, but orbits here are
+	/// unperturbed, so it doesn't librate. Instead it circulates at the rate *n₁ - 3n₂ + 2n₃* that
+	/// the moons' mean motions miss the exact resonance by, which is a slow drift of a fraction of
+	/// a degree per day with the bundled elements.
+	pub fn laplace_resonance_angle_rad(&self, io: &H, europa: &H, ganymede: &H, time: T) -> T where H: Debug {
+		self.resonance_angle(&[io.clone(), europa.clone(), ganymede.clone()], &[1, -3, 2], time).unwrap()
+	}
+	/// Finds the small integer ratio *p:q* between the mean motions of two bodies, if they're
+	/// close enough to one to be in a mean-motion resonance
+	/// 
+	/// `tolerance` is how far off the ratio is allowed to be, as a fraction of the ratio itself,
+	/// and *p* and *q* go up to 10 with the simplest ratio that fits returned first. Io completes
+	/// two orbits for every one of Europa's, so `mean_motion_resonance(io, europa, 0.01)` gives
+	/// `(2, 1)`. Returns `None` if either body doesn't orbit anything or no ratio is close enough.
+	pub fn mean_motion_resonance(&self, a: &H, b: &H, tolerance: T) -> Option<(u32, u32)> where H: Debug {
+		let ratio = self.mean_motion_of(a)? / self.mean_motion_of(b)?;
+		let max_integer = 10;
+		// search in order of p + q so that e.g. 2:1 is found before 4:2
+		for sum in 2..=2 * max_integer {
+			for q in 1..sum {
+				let p = sum - q;
+				if p > max_integer || q > max_integer {
+					continue;
+				}
+				let candidate = T::from_u32(p).unwrap() / T::from_u32(q).unwrap();
+				if Float::abs(ratio / candidate - T::from_f32(1.0).unwrap()) <= tolerance {
+					return Some((p, q));
+				}
+			}
+		}
+		None
+	}
+	/// Calculates the ecliptic longitude of the given body as seen from the root of its hierarchy
+	/// at the given time, in radians wrapped to `[0, 2π)`
+	/// 
+	/// The longitude is measured around the ecliptic from the +X axis, which stands in for the
+	/// vernal equinox, in the direction bodies orbit. Since the Sun is at 0° as seen from Earth at
+	/// the March equinox, Earth itself is at 180° then, 270° at the June solstice, 0° at the
+	/// September equinox and 90° at the December solstice. Unlike the
+	/// [true longitude](Self::true_longitude_at_time) this is always measured from the root body
+	/// against the ecliptic, whatever the database's [reference plane](ReferencePlane). Returns
+	/// `None` if the body isn't in the database or is the root itself.
+	pub fn heliocentric_longitude_at_time(&self, handle: &H, time: T) -> Option<T> where H: Debug, T: RealField + SimdValue + SimdRealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		self.bodies.get(handle)?.parent.as_ref()?;
+		let position = self.remove_convention(self.absolute_position_at_time(handle, time));
+		// orbits run from +X towards -Z around +Y
+		let mut longitude = Float::atan2(-position.z, position.x);
+		if longitude < zero {
+			longitude += tau;
+		}
+		Some(longitude)
+	}
+	/// Calculates the true longitude *Ω + ω + ν* of the given body at the given time
+	pub fn true_longitude_at_time(&self, handle: &H, time: T) -> T where H: Debug, T: RealField {
+		let entry = self.get_entry(handle);
+		match &entry.orbit {
+			Some(orbit) => {
+				let true_anomaly = orbit.true_anomaly_at_mean_anomaly(self.mean_anomaly_at_time(handle, time));
+				orbit.long_of_ascending_node + orbit.arg_of_periapsis + true_anomaly
+			},
+			None => T::from_f32(0.0).unwrap(),
+		}
+	}
+	/// Finds the velocities at both ends of the orbit around `central` that travels from position
+	/// `r1` to position `r2` in `tof` seconds
+	/// 
+	/// Positions are relative to `central` in meters and in the database's
+	/// [coordinate convention](CoordinateConvention) and [reference plane](ReferencePlane), and the
+	/// returned `(departure, arrival)` velocities are in meters per second in the same frame. This
+	/// is [`solve_lambert`] using the *GM* of `central`, see it for what `prograde` means and when
+	/// no transfer is found. Returns `None` if `central` isn't in the database.
+	pub fn lambert(&self, central: &H, r1: Vector3<T>, r2: Vector3<T>, tof: T, prograde: bool) -> Option<(Vector3<T>, Vector3<T>)> where T: RealField {
+		let central_gm = self.bodies.get(central)?.gm();
+		let (departure, arrival) = solve_lambert(self.remove_convention(r1), self.remove_convention(r2), tof, central_gm, prograde)?;
+		Some((self.apply_convention(departure), self.apply_convention(arrival)))
+	}
+	/// Calculates the time of flight of a Hohmann transfer between the orbits of two bodies that
+	/// share the same parent, or `None` if they orbit different parents
+	/// 
+	/// Uses the semimajor axes of both orbits, so this is an approximation for eccentric orbits.
+	pub fn hohmann_transfer_time(&self, from: &H, to: &H) -> Option<T> where H: Debug {
+		let from_entry = self.bodies.get(from)?;
+		let to_entry = self.bodies.get(to)?;
+		let parent_handle = from_entry.parent.as_ref()?;
+		if to_entry.parent.as_ref() != Some(parent_handle) {
+			return None;
+		}
+		let two = T::from_f32(2.0).unwrap();
+		let pi = T::from_f64(std::f64::consts::PI).unwrap();
+		let a_transfer = (from_entry.orbit?.semimajor_axis + to_entry.orbit?.semimajor_axis) / two;
+		let parent_gm = self.get_entry(parent_handle).gm();
+		Some(pi * Float::sqrt(Float::powi(a_transfer, 3) / parent_gm))
+	}
+	/// Finds the next time at or after `after_time` that the phase angle between two bodies
+	/// orbiting the same parent matches the ideal phase angle for a Hohmann transfer between them
+	/// 
+	/// The ideal phase angle is the angle the target body needs to lead the departure body by so
+	/// that it arrives at the transfer orbit's apoapsis at the same time the spacecraft does.
+	/// Windows repeat roughly every synodic period. Returns `None` if the bodies don't share a
+	/// parent or have identical mean motions.
+	pub fn next_transfer_window(&self, from: &H, to: &H, after_time: T) -> Option<T> where H: Debug, T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let pi = T::from_f64(std::f64::consts::PI).unwrap();
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		let transfer_time = self.hohmann_transfer_time(from, to)?;
+		let n_from = self.mean_motion_of(from)?;
+		let n_to = self.mean_motion_of(to)?;
+		let relative_rate = n_to - n_from;
+		if relative_rate == zero {
+			return None;
+		}
+		let ideal_phase = pi - n_to * transfer_time;
+		// phase error wrapped to (-π, π]
+		let phase_error = |time: T| {
+			let phase = self.true_longitude_at_time(to, time) - self.true_longitude_at_time(from, time);
+			let mut error = (phase - ideal_phase) % tau;
+			if error > pi {
+				error -= tau;
+			} else if error <= -pi {
+				error += tau;
+			}
+			error
+		};
+		let synodic_period = tau / Float::abs(relative_rate);
+		// step forward to the first time the mean phase error reaches zero, then refine it using
+		// the true longitudes of both bodies
+		let mut offset = -phase_error(after_time) / relative_rate;
+		if offset < zero {
+			offset += synodic_period;
+		}
+		let mut time = after_time + offset;
+		for _ in 0..20 {
+			let correction = phase_error(time) / relative_rate;
+			time -= correction;
+			if Float::abs(correction) < T::from_f64(1e-3).unwrap() {
+				break;
+			}
+		}
+		if time < after_time {
+			time += synodic_period;
+		}
+		Some(time)
+	}
+	/// Computes a grid of total transfer delta-v between two bodies orbiting the same parent, for
+	/// plotting a "porkchop plot" of launch windows
+	/// 
+	/// Rows of the returned grid are departure times evenly spaced across `t_departure_range` and
+	/// columns are arrival times evenly spaced across `t_arrival_range`, with `grid_size` giving
+	/// the number of `(departure, arrival)` samples. Each value is the sum of the departure and
+	/// arrival burns in meters per second (m/s), matching the origin body's velocity to the
+	/// transfer orbit found by [`solve_lambert`] and then the transfer orbit to the target's.
+	/// Combinations with no solution, such as arriving before departing, are infinite.
+	/// 
+	/// The burns are relative to the bodies themselves, so they don't include the cost of escaping
+	/// the origin body's gravity well or capturing into orbit around the target.
+	pub fn porkchop_grid(&self, origin: &H, target: &H, t_departure_range: (T, T), t_arrival_range: (T, T), grid_size: (usize, usize)) -> Vec<Vec<T>> where H: Debug, T: RealField {
+		let infinity = T::infinity();
+		let parent_gm = match self.bodies.get(origin).and_then(|entry| entry.parent.as_ref()) {
+			Some(parent_handle) if self.bodies.get(target).and_then(|entry| entry.parent.as_ref()) == Some(parent_handle) => {
+				self.get_entry(parent_handle).gm()
+			},
+			_ => return vec![vec![infinity; grid_size.1]; grid_size.0],
+		};
+		let sample = |range: (T, T), count: usize, index: usize| {
+			if count > 1 {
+				range.0 + (range.1 - range.0) * T::from_usize(index).unwrap() / T::from_usize(count - 1).unwrap()
+			} else {
+				range.0
+			}
+		};
+		let mut grid = Vec::with_capacity(grid_size.0);
+		for i in 0..grid_size.0 {
+			let t_departure = sample(t_departure_range, grid_size.0, i);
+			let (r_origin, v_origin) = self.state_vector_at_time(origin, t_departure).unwrap();
+			let mut row = Vec::with_capacity(grid_size.1);
+			for j in 0..grid_size.1 {
+				let t_arrival = sample(t_arrival_range, grid_size.1, j);
+				row.push(self.lambert_transfer_delta_v(target, (r_origin, v_origin), t_departure, t_arrival, parent_gm));
+			}
+			grid.push(row);
+		}
+		grid
+	}
+	/// Sums the delta-v in meters per second (m/s) of the transfers between each consecutive pair
+	/// of bodies in `waypoints`, for planning missions that visit several bodies in turn
+	/// 
+	/// `departure_times` gives the time each waypoint is left, with the last entry being the time
+	/// the final waypoint is reached, and each leg is flown on the transfer orbit found by
+	/// [`solve_lambert`] like in [`porkchop_grid`](Self::porkchop_grid). If `departure_times` is
+	/// empty every leg is estimated as a Hohmann transfer between the circular orbits with the same
+	/// semimajor axes as the two bodies instead. Legs between bodies that don't share a parent, or
+	/// with no Lambert solution such as arriving before departing, make the total infinite.
+	/// 
+	/// Like [`porkchop_grid`](Self::porkchop_grid) the burns are relative to the bodies
+	/// themselves, so they don't include escaping or capturing at each waypoint.
+	/// 
+	/// # Panics
+	/// 
+	/// Panics if `departure_times` isn't empty and has a different length than `waypoints`
+	pub fn multi_hop_transfer_dv(&self, waypoints: &[H], departure_times: &[T]) -> T where H: Debug, T: RealField {
+		assert!(departure_times.is_empty() || departure_times.len() == waypoints.len(), "Expected one departure time per waypoint, got {} times for {} waypoints", departure_times.len(), waypoints.len());
+		let mut total = T::from_f32(0.0).unwrap();
+		for (leg, pair) in waypoints.windows(2).enumerate() {
+			let (origin, target) = (&pair[0], &pair[1]);
+			let parent_gm = match self.bodies.get(origin).and_then(|entry| entry.parent.as_ref()) {
+				Some(parent_handle) if self.bodies.get(target).and_then(|entry| entry.parent.as_ref()) == Some(parent_handle) => {
+					self.get_entry(parent_handle).gm()
+				},
+				_ => return T::infinity(),
+			};
+			total += if departure_times.is_empty() {
+				let r_origin = self.get_entry(origin).orbit.unwrap().semimajor_axis;
+				let r_target = self.get_entry(target).orbit.unwrap().semimajor_axis;
+				hohmann_delta_v(parent_gm, r_origin, r_target)
+			} else {
+				let (t_departure, t_arrival) = (departure_times[leg], departure_times[leg + 1]);
+				let origin_state = self.state_vector_at_time(origin, t_departure).unwrap();
+				self.lambert_transfer_delta_v(target, origin_state, t_departure, t_arrival, parent_gm)
+			};
+		}
+		total
+	}
+	/// Total delta-v of the Lambert transfer leaving the given origin state at `t_departure` and
+	/// meeting the target body at `t_arrival`, or infinity if there's no solution
+	fn lambert_transfer_delta_v(&self, target: &H, (r_origin, v_origin): (Vector3<T>, Vector3<T>), t_departure: T, t_arrival: T, parent_gm: T) -> T where H: Debug, T: RealField {
+		let (r_target, v_target) = self.state_vector_at_time(target, t_arrival).unwrap();
+		match solve_lambert(r_origin, r_target, t_arrival - t_departure, parent_gm, true) {
+			Some((v_departure, v_arrival)) => (v_departure - v_origin).norm() + (v_target - v_arrival).norm(),
+			None => T::infinity(),
+		}
+	}
+	/// Finds every pair of satellites of the given parent whose spheres of influence overlap at the
+	/// given time, for checking that a generated or hand made system suits patched conics
+	/// 
+	/// Two satellites overlap when they're closer together than the sum of their
+	/// [sphere of influence radii](Self::radius_soi), which leaves a region where it's unclear
+	/// which of them a passing probe should follow. Each pair is listed once with the lower handle
+	/// first, sorted by handle. [Markers](DatabaseEntry::is_marker) are skipped.
+	pub fn overlapping_soi_pairs(&self, parent: &H, time: T) -> Vec<(H, H)> where H: Debug + Ord, T: RealField {
+		let satellites: Vec<(H, Vector3<T>, T)> = self.get_satellites(parent).into_iter()
+			.filter(|handle| !self.get_entry(handle).is_marker)
+			.map(|handle| {
+				let position = self.position_at_time(&handle, time);
+				let radius = self.radius_soi(&handle);
+				(handle, position, radius)
+			})
+			.collect();
+		let mut pairs = Vec::new();
+		for (i, (handle_a, position_a, radius_a)) in satellites.iter().enumerate() {
+			for (handle_b, position_b, radius_b) in &satellites[i + 1..] {
+				if (position_a - position_b).norm() < *radius_a + *radius_b {
+					pairs.push((handle_a.clone(), handle_b.clone()));
+				}
+			}
+		}
+		pairs
+	}
+	/// Finds the points where the orbits of two bodies around the same parent cross, as pairs of
+	/// `(true anomaly of body_a, true anomaly of body_b)` in radians sorted by the first
+	/// 
+	/// Orbits that aren't exactly coplanar never truly meet, so this returns the local minima of
+	/// the distance between the two orbital paths that are closer than the sum of both bodies'
+	/// sphere of influence radii. Returns an empty list if the bodies orbit different parents or
+	/// either of them isn't on a closed orbit.
+	pub fn find_orbit_intersections(&self, body_a: &H, body_b: &H) -> Vec<(T, T)> where H: Debug + Ord, T: RealField {
+		let two = T::from_f32(2.0).unwrap();
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		let (orbit_a, orbit_b) = match self.shared_closed_orbits(body_a, body_b) {
+			Some(orbits) => orbits,
+			None => return Vec::new(),
+		};
+		let threshold = self.radius_soi(body_a) + self.radius_soi(body_b);
+		let distance_squared = |true_anomaly_a: T, true_anomaly_b: T| {
+			(orbit_a.position_at_true_anomaly(true_anomaly_a) - orbit_b.position_at_true_anomaly(true_anomaly_b)).norm_squared()
+		};
+		// sample both orbits on a coarse grid, then refine every local minimum of the distance
+		const SAMPLES: usize = 180;
+		let step = tau / T::from_usize(SAMPLES).unwrap();
+		let grid: Vec<Vec<T>> = (0..SAMPLES)
+			.map(|i| (0..SAMPLES).map(|j| distance_squared(step * T::from_usize(i).unwrap(), step * T::from_usize(j).unwrap())).collect())
+			.collect();
+		let wrap = |angle: T| {
+			let angle = angle % tau;
+			if angle < T::from_f32(0.0).unwrap() { angle + tau } else { angle }
+		};
+		let mut intersections: Vec<(T, T)> = Vec::new();
+		for i in 0..SAMPLES {
+			for j in 0..SAMPLES {
+				let value = grid[i][j];
+				let is_minimum = (0..3).all(|di| (0..3).all(|dj| {
+					let neighbour = grid[(i + SAMPLES + di - 1) % SAMPLES][(j + SAMPLES + dj - 1) % SAMPLES];
+					(di == 1 && dj == 1) || value < neighbour || (value == neighbour && (di, dj) > (1, 1))
+				}));
+				if !is_minimum {
+					continue;
+				}
+				// pattern search, shrinking the step whenever no neighbouring point is closer
+				let (mut nu_a, mut nu_b) = (step * T::from_usize(i).unwrap(), step * T::from_usize(j).unwrap());
+				let mut best = value;
+				let mut search_step = step;
+				let min_step = T::from_f64(1e-10).unwrap();
+				for _ in 0..10_000 {
+					if search_step < min_step {
+						break;
+					}
+					let candidates = [(nu_a + search_step, nu_b), (nu_a - search_step, nu_b), (nu_a, nu_b + search_step), (nu_a, nu_b - search_step)];
+					match candidates.iter().map(|&(a, b)| (distance_squared(a, b), a, b)).find(|(d, _, _)| *d < best) {
+						Some((d, a, b)) => {
+							best = d;
+							nu_a = a;
+							nu_b = b;
+						},
+						None => search_step /= two,
+					}
+				}
+				if Float::sqrt(best) > threshold {
+					continue;
+				}
+				let (nu_a, nu_b) = (wrap(nu_a), wrap(nu_b));
+				let is_duplicate = intersections.iter().any(|&(a, b)| {
+					let difference = |x: T, y: T| {
+						let d = Float::abs(x - y);
+						Float::min(d, tau - d)
+					};
+					difference(a, nu_a) < T::from_f64(1e-6).unwrap() && difference(b, nu_b) < T::from_f64(1e-6).unwrap()
+				});
+				if !is_duplicate {
+					intersections.push((nu_a, nu_b));
+				}
+			}
+		}
+		intersections.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+		intersections
+	}
+	/// Finds the next time at or after `after_time` that two bodies orbiting the same parent are
+	/// both at one of the crossings found by
+	/// [`find_orbit_intersections`](Self::find_orbit_intersections) at the same time
+	/// 
+	/// Checks each time `body_a` passes through a crossing, and counts it as a close approach if
+	/// `body_b` is within the sum of both bodies' sphere of influence radii at that moment. Only
+	/// the next 1000 passes of `body_a` through each crossing are checked, so returns `None` if
+	/// there's no such approach in that span or the orbits never cross.
+	pub fn time_to_intersection(&self, body_a: &H, body_b: &H, after_time: T) -> Option<T> where H: Debug + Ord, T: RealField {
+		const MAX_PASSES: usize = 1000;
+		let zero = T::from_f32(0.0).unwrap();
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		let (orbit_a, _) = self.shared_closed_orbits(body_a, body_b)?;
+		let threshold = self.radius_soi(body_a) + self.radius_soi(body_b);
+		let n = self.mean_motion_of(body_a)?;
+		let period = tau / n;
+		let current_mean_anomaly = self.mean_anomaly_at_time(body_a, after_time);
+		let mut earliest: Option<T> = None;
+		for (true_anomaly_a, _) in self.find_orbit_intersections(body_a, body_b) {
+			let mut delta = (orbit_a.mean_anomaly_at_true_anomaly(true_anomaly_a) - current_mean_anomaly) % tau;
+			if delta < zero {
+				delta += tau;
+			}
+			let first_pass = after_time + delta / n;
+			for pass in 0..MAX_PASSES {
+				let time = first_pass + period * T::from_usize(pass).unwrap();
+				if earliest.is_some_and(|earliest| time >= earliest) {
+					break;
+				}
+				let separation = (self.position_at_time(body_a, time) - self.position_at_time(body_b, time)).norm();
+				if separation <= threshold {
+					earliest = Some(time);
+					break;
+				}
+			}
+		}
+		earliest
+	}
+	/// Quickly checks whether the orbits of two bodies around the same parent could cross
+	/// 
+	/// This is a heuristic rather than an exact test. The orbits are flagged as crossing when the
+	/// ranges of distances from the parent, from periapsis to apoapsis, overlap and their planes
+	/// are within 30° of each other. It doesn't check where along the orbits the overlap happens,
+	/// so two orbits can be flagged even when the line where their planes meet keeps them apart,
+	/// like Neptune and Pluto. Use [`find_orbit_intersections`](Self::find_orbit_intersections)
+	/// to find where the paths actually come close.
+	/// 
+	/// Returns `false` if the bodies orbit different parents or either of them isn't on a closed
+	/// orbit.
+	pub fn orbits_cross(&self, body_a: &H, body_b: &H) -> bool where T: RealField {
+		let one = T::from_f32(1.0).unwrap();
+		let half_pi = T::from_f64(std::f64::consts::FRAC_PI_2).unwrap();
+		let max_mutual_inclination = T::from_f64(30.0 * CONVERT_DEG_TO_RAD).unwrap();
+		let (orbit_a, orbit_b) = match self.shared_closed_orbits(body_a, body_b) {
+			Some(orbits) => orbits,
+			None => return false,
+		};
+		let radial_range = |orbit: &OrbitalElements<T>| (
+			orbit.semimajor_axis * (one - orbit.eccentricity),
+			orbit.semimajor_axis * (one + orbit.eccentricity),
+		);
+		let (periapsis_a, apoapsis_a) = radial_range(&orbit_a);
+		let (periapsis_b, apoapsis_b) = radial_range(&orbit_b);
+		if periapsis_a > apoapsis_b || periapsis_b > apoapsis_a {
+			return false;
+		}
+		let normal = |orbit: &OrbitalElements<T>| orbit.position_at_true_anomaly(T::from_f32(0.0).unwrap())
+			.cross(&orbit.position_at_true_anomaly(half_pi))
+			.normalize();
+		let cos_mutual_inclination = Float::min(one, normal(&orbit_a).dot(&normal(&orbit_b)));
+		Float::acos(cos_mutual_inclination) <= max_mutual_inclination
+	}
+	/// Calculates the Tisserand parameter of `small_body` relative to `planet`, where both orbit
+	/// the same parent
+	/// 
+	/// *T = a_p/a + 2·cos(i)·sqrt((a/a_p)(1 - e²))*, where *a_p* is the planet's semimajor axis,
+	/// *a* and *e* are the small body's, and *i* is the inclination between the two orbits. It
+	/// stays nearly constant through close encounters with the planet, so it's used to tell
+	/// whether two sightings could be the same object and to classify small bodies. Relative to
+	/// Jupiter, asteroids have *T > 3*, Jupiter family comets *2 < T < 3* and long period comets
+	/// *T < 2*. Returns `None` if the bodies don't orbit the same parent.
+	pub fn tisserand_parameter(&self, small_body: &H, planet: &H) -> Option<T> where T: RealField {
+		let one = T::from_f32(1.0).unwrap();
+		let half_pi = T::from_f64(std::f64::consts::FRAC_PI_2).unwrap();
+		let small_entry = self.bodies.get(small_body)?;
+		let planet_entry = self.bodies.get(planet)?;
+		if small_entry.parent.is_none() || small_entry.parent != planet_entry.parent {
+			return None;
+		}
+		let (orbit, planet_orbit) = (small_entry.orbit?, planet_entry.orbit?);
+		let normal = |orbit: &OrbitalElements<T>| orbit.position_at_true_anomaly(T::from_f32(0.0).unwrap())
+			.cross(&orbit.position_at_true_anomaly(half_pi))
+			.normalize();
+		let cos_mutual_inclination = Float::max(-one, Float::min(one, normal(&orbit).dot(&normal(&planet_orbit))));
+		let ratio = orbit.semimajor_axis / planet_orbit.semimajor_axis;
+		let e_squared = orbit.eccentricity * orbit.eccentricity;
+		Some(one / ratio + T::from_f32(2.0).unwrap() * cos_mutual_inclination * Float::sqrt(ratio * (one - e_squared)))
+	}
+	/// Gets the orbits of two bodies if they orbit the same parent and both orbits are closed
+	fn shared_closed_orbits(&self, body_a: &H, body_b: &H) -> Option<(OrbitalElements<T>, OrbitalElements<T>)> {
+		let one = T::from_f32(1.0).unwrap();
+		let entry_a = self.bodies.get(body_a)?;
+		let entry_b = self.bodies.get(body_b)?;
+		if entry_a.parent.is_none() || entry_a.parent != entry_b.parent {
+			return None;
+		}
+		let (orbit_a, orbit_b) = (entry_a.orbit?, entry_b.orbit?);
+		if orbit_a.eccentricity >= one || orbit_b.eccentricity >= one {
+			return None;
+		}
+		Some((orbit_a, orbit_b))
+	}
+	/// Calculates the orbital period of the given body around its parent in seconds, or `None` if
+	/// the body doesn't orbit anything
+	/// 
+	/// Also returns `None` for bodies orbiting a massless parent, which never go around, instead
+	/// of an infinite period.
+	pub fn orbital_period_s(&self, handle: &H) -> Option<T> where H: Debug {
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		let n = self.mean_motion_of(handle)?;
+		if n <= T::from_f32(0.0).unwrap() {
+			return None;
+		}
+		Some(tau / n)
+	}
+	/// Calculates the length of a solar day on the given body in seconds, the time it takes for
+	/// its star to return to the same place in its sky
+	/// 
+	/// This combines the body's sidereal [rotation period](Body::rotation_period_s) with the
+	/// period of its orbit around the star at the root of its hierarchy, which for a moon is the
+	/// orbit of its planet. Bodies with an axial tilt of more than 90° rotate retrograde, which
+	/// makes their solar day shorter than their sidereal day instead of longer. A body whose
+	/// rotation is locked to its star has a solar day of infinity.
+	/// 
+	/// Returns `None` if the body has no rotation period or doesn't orbit anything.
+	pub fn solar_day_s(&self, body: &H) -> Option<T> where H: Debug {
+		let one = T::from_f32(1.0).unwrap();
+		let info = &self.bodies.get(body)?.info;
+		let rotation_period = info.rotation_period_s()?;
+		let mut planet = body.clone();
+		while let Some(parent) = self.bodies.get(&planet)?.parent.clone() {
+			if self.get_entry(&parent).parent.is_none() {
+				break;
+			}
+			planet = parent;
+		}
+		let orbital_period = self.orbital_period_s(&planet)?;
+		let rate = if self.is_retrograde_rotation(body)? {
+			one / rotation_period + one / orbital_period
+		} else {
+			one / rotation_period - one / orbital_period
+		};
+		Some(one / rate)
+	}
+	/// Estimates the power radiated away as gravitational waves by a pair of bodies where one
+	/// orbits the other, in watts (W)
+	/// 
+	/// Uses the Peters formula for quadrupole radiation from a binary on a Keplerian orbit,
+	/// *P = (32/5)·G⁴·m₁²·m₂²·(m₁+m₂) / (c⁵·a⁵·(1-e²)^(7/2))·(1 + 73/24·e² + 37/96·e⁴)*. This is
+	/// entirely negligible for anything in the solar system, with the Earth and Sun radiating about
+	/// 200 W. Returns zero if neither body orbits the other. The powers of *c* and *a* are far
+	/// beyond the range of `f32`, so the whole formula is worked out in `f64`.
+	/// 
+	/// Orbits here keep fixed elements rather than decaying, so the power is the same at any
+	/// `time`, which is currently unused.
+	pub fn gravitational_wave_power_w(&self, body_a: &H, body_b: &H, _time: T) -> T where H: Debug {
+		let entry_a = self.get_entry(body_a);
+		let entry_b = self.get_entry(body_b);
+		let orbit = if entry_a.parent.as_ref() == Some(body_b) {
+			entry_a.orbit
+		} else if entry_b.parent.as_ref() == Some(body_a) {
+			entry_b.orbit
+		} else {
+			None
+		};
+		let orbit = match orbit {
+			Some(orbit) => orbit,
+			None => return T::from_f32(0.0).unwrap(),
+		};
+		let to_f64 = |value: T| ToPrimitive::to_f64(&value).unwrap();
+		let (gm_a, gm_b) = (to_f64(entry_a.gm()), to_f64(entry_b.gm()));
+		let e_squared = to_f64(orbit.eccentricity).powi(2);
+		let enhancement = 1.0 + 73.0 / 24.0 * e_squared + 37.0 / 96.0 * e_squared * e_squared;
+		// G⁴·m₁²·m₂²·(m₁+m₂) written in terms of GM to keep the intermediate values in range
+		let numerator = 32.0 / 5.0 * gm_a * gm_a * gm_b * gm_b * (gm_a + gm_b) / CONST_G;
+		let denominator = SPEED_OF_LIGHT_M_PER_S.powi(5) * to_f64(orbit.semimajor_axis).powi(5) * (1.0 - e_squared).powf(3.5);
+		T::from_f64(numerator / denominator * enhancement).unwrap()
+	}
+	/// Gets the orbital elements of the given body after `time_years` Julian years of slow secular
+	/// change
+	/// 
+	/// Adds the body's [`SecularDrift`] rates, if it has any, and the precession of the ascending
+	/// node and argument of periapsis caused by its parent's equatorial bulge, if the parent has
+	/// a *J2* set. Nothing else about the body's motion changes, so these are meant for showing
+	/// how orbits evolve over thousands or millions of years rather than for exact positions.
+	/// Bodies that don't orbit anything get default elements.
+	pub fn orbital_elements_at_epoch(&self, handle: &H, time_years: T) -> OrbitalElements<T> where H: Debug {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+		let entry = self.get_entry(handle);
+		let (mut orbit, parent_handle) = match (entry.orbit, &entry.parent) {
+			(Some(orbit), Some(parent_handle)) => (orbit, parent_handle),
+			_ => return OrbitalElements::default(),
+		};
+		let parent = &self.get_entry(parent_handle).info;
+		let seconds = time_years * T::from_f64(365.25 * 86_400.0).unwrap();
+		let wrap = |angle: T| {
+			let angle = angle % tau;
+			if angle < zero { angle + tau } else { angle }
+		};
+		if parent.j2() != zero {
+			// first order J2 rates, averaged over an orbit
+			let n = self.mean_motion_of(handle).unwrap();
+			let semilatus_rectum = orbit.semimajor_axis * (one - orbit.eccentricity * orbit.eccentricity);
+			let factor = n * parent.j2() * Float::powi(parent.radius_equator_m() / semilatus_rectum, 2);
+			let cos_i = Float::cos(orbit.inclination);
+			let node_rate = -T::from_f64(1.5).unwrap() * factor * cos_i;
+			let periapsis_rate = T::from_f64(0.75).unwrap() * factor * (T::from_f32(5.0).unwrap() * cos_i * cos_i - one);
+			orbit.long_of_ascending_node = wrap(orbit.long_of_ascending_node + node_rate * seconds);
+			orbit.arg_of_periapsis = wrap(orbit.arg_of_periapsis + periapsis_rate * seconds);
+		}
+		if let Some(drift) = &entry.secular_drift {
+			orbit.semimajor_axis = orbit.semimajor_axis + drift.da_per_year * time_years;
+			orbit.eccentricity = Float::max(zero, orbit.eccentricity + drift.de_per_year * time_years);
+			orbit.inclination = orbit.inclination + drift.di_per_year * time_years;
+		}
+		orbit
+	}
+	/// Estimates how fast the ascending node of the given body's orbit regresses, in radians per
+	/// second, or `None` if the body doesn't orbit anything
+	/// 
+	/// Adds the first order secular rates from two perturbations. One is the parent's
+	/// equatorial bulge, using its [estimated *J2*](Body::estimated_j2). The other is the tide of the
+	/// parent's own parent, like the Sun pulling on the Moon, treating the parent's orbit as the
+	/// plane the perturbation acts in:
+	/// 
+	/// *dΩ/dt = -3/2·n·J2·(R/p)²·cos i - 3/4·(n_p²/n)·cos i·(1 + 3/2·e²)/sqrt(1 - e²)/(1 - e_p²)^(3/2)*
+	/// 
+	/// where *n* and *n_p* are the mean motions of the body and its parent, *R* is the parent's
+	/// equatorial radius and *p* the semi-latus rectum of the body's orbit. Both terms use the
+	/// body's own inclination, so the solar term is only a rough estimate for moons whose orbits
+	/// are far from both their planet's equator and its orbit. The result is negative for
+	/// prograde orbits, which regress, and positive for retrograde ones.
+	pub fn nodal_precession_rate(&self, handle: &H) -> Option<T> where H: Debug {
+		let one = T::from_f32(1.0).unwrap();
+		let entry = self.bodies.get(handle)?;
+		let orbit = entry.orbit?;
+		let parent_handle = entry.parent.as_ref()?;
+		let parent = self.get_entry(parent_handle);
+		let n = self.mean_motion_of(handle)?;
+		let cos_i = Float::cos(orbit.inclination);
+		let e_squared = orbit.eccentricity * orbit.eccentricity;
+		let semilatus_rectum = orbit.semimajor_axis * (one - e_squared);
+		let oblateness_rate = -T::from_f64(1.5).unwrap() * n * parent.info.estimated_j2()
+			* Float::powi(parent.info.radius_equator_m() / semilatus_rectum, 2) * cos_i;
+		let third_body_rate = match (parent.orbit, self.mean_motion_of(parent_handle)) {
+			(Some(parent_orbit), Some(parent_n)) => {
+				let parent_e_factor = Float::powf(one - parent_orbit.eccentricity * parent_orbit.eccentricity, T::from_f64(1.5).unwrap());
+				-T::from_f64(0.75).unwrap() * parent_n * parent_n / n * cos_i
+					* (one + T::from_f64(1.5).unwrap() * e_squared) / Float::sqrt(one - e_squared) / parent_e_factor
+			},
+			_ => T::from_f32(0.0).unwrap(),
+		};
+		Some(oblateness_rate + third_body_rate)
+	}
+	/// Uniformly rescales the whole database, multiplying every orbit's semimajor axis and every
+	/// body's radii by `distance_factor` and every body's mass by `mass_factor`
+	/// 
+	/// Shapes and orientations of orbits are unchanged, but since the period of an orbit is
+	/// *2π·sqrt(a³/GM)*, every orbital period gets multiplied by
+	/// *sqrt(distance_factor³ / mass_factor)*. To keep periods the same while shrinking a system,
+	/// pass `mass_factor = distance_factor³`, otherwise expect everything to orbit faster or slower.
+	/// Spheres of influence scale along with `distance_factor` either way.
+	pub fn rescale(&mut self, distance_factor: T, mass_factor: T) {
+		self.spatial_index = None;
+		for entry in self.bodies.values_mut() {
+			let gm_km3_s2 = entry.info.gm_km3_s2() * mass_factor;
+			entry.info = entry.info.clone()
+				.with_mass_kg(entry.info.mass_kg() * mass_factor)
+				.with_gm_km3_s2(gm_km3_s2)
+				.with_radii_km(entry.info.radius_equator_km() * distance_factor, entry.info.radius_polar_km() * distance_factor);
+			if let Some(orbit) = &mut entry.orbit {
+				orbit.semimajor_axis = orbit.semimajor_axis * distance_factor;
+			}
+			if let Some(n) = entry.fixed_mean_motion {
+				entry.fixed_mean_motion = Some(n * Float::sqrt(mass_factor / Float::powi(distance_factor, 3)));
+			}
+		}
+	}
+	/// Advances every orbiting body along its orbit by `dt` seconds, by adding its mean motion
+	/// times `dt` to its stored mean anomaly at epoch
+	/// 
+	/// This is for simulations that only ever move forwards in time. After stepping, the current
+	/// position of a body is simply [`position_at_mean_anomaly`](Self::position_at_mean_anomaly)
+	/// with its `mean_anomaly_at_epoch`, without any time math. Mixing this with the `*_at_time`
+	/// methods means the times passed to those become relative to the last step.
+	/// 
+	/// Bodies with an [update interval](Self::set_update_interval) save up the time from each step
+	/// and only move once at least that much has built up. Mean anomalies on closed orbits are
+	/// wrapped into `[0, 2π)` like in [`mean_anomaly_at_time`](Self::mean_anomaly_at_time), while
+	/// bodies on hyperbolic trajectories keep counting up as they head away from periapsis.
+	pub fn step(&mut self, dt: T) where H: Debug {
+		self.spatial_index = None;
+		let zero = T::from_f32(0.0).unwrap();
+		let to_f64 = |value: T| ToPrimitive::to_f64(&value).unwrap();
+		let motions: Vec<(H, T)> = self.bodies.keys()
+			.filter_map(|handle| self.mean_motion_of(handle).map(|n| (handle.clone(), n)))
+			.collect();
+		for (handle, n) in motions {
+			let entry = self.bodies.get_mut(&handle).unwrap();
+			let elapsed = entry.pending_step + dt;
+			if entry.update_interval.is_some_and(|interval| elapsed < interval) {
+				entry.pending_step = elapsed;
+				continue;
+			}
+			entry.pending_step = zero;
+			let mean_anomaly = to_f64(entry.mean_anomaly_at_epoch) + to_f64(n) * to_f64(elapsed);
+			entry.mean_anomaly_at_epoch = Self::wrap_mean_anomaly(&entry.orbit.unwrap(), mean_anomaly);
+		}
+	}
+	/// Sets the minimum time in seconds between updates of the given body's position by
+	/// [`step`](Self::step), or `None` to update it on every step
+	/// 
+	/// Far off bodies that barely move on screen don't need to be moved every frame, so marking
+	/// them with a long interval saves the work of updating them. This trades accuracy for
+	/// performance, since in between updates the body sits still where it was last put, up to
+	/// `interval` seconds behind where it should be. Panics if the body isn't in the database.
+	pub fn set_update_interval(&mut self, handle: &H, interval: Option<T>) where H: Debug {
+		let error_msg = format!("No body with handle {:?}", handle);
+		self.bodies.get_mut(handle).expect(&error_msg).update_interval = interval;
+	}
+	/// Shrinks the orbit of the given body by the drag it would feel over `dt` seconds from a
+	/// simple exponential atmosphere around its parent
+	/// 
+	/// The atmosphere's density at an altitude *h* above the parent's average radius is
+	/// *ρ₀·e^(-h/H)* for the given `surface_density` *ρ₀* in kg/m³ and `scale_height` *H* in
+	/// meters. The semimajor axis decays at *da/dt = -ρ·sqrt(GM·a)·C_d·A/m*, treating the body as
+	/// a sphere with a drag coefficient *C_d* of 2.2. Drag is strongest at periapsis where the
+	/// air is thickest, which mostly lowers the apoapsis, so the density at each apsis is used to
+	/// split the decay between them and the orbit slowly circularizes as it sinks towards reentry.
+	/// 
+	/// This is an opt in extra on top of the Keplerian orbits and is applied by rewriting the
+	/// body's orbital elements at the given `time`. The body's mean anomaly at `time` is kept, so
+	/// it carries on from where it is on its slightly smaller orbit, but the new mean motion
+	/// applies to every other time too, so positions before `time` aren't where they used to be.
+	/// Bodies without mass, without a parent or on hyperbolic trajectories are left alone.
+	pub fn apply_drag(&mut self, handle: &H, dt: T, scale_height: T, surface_density: T, time: T) where H: Debug {
+		self.spatial_index = None;
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let drag_coefficient = T::from_f32(2.2).unwrap();
+		let Some(entry) = self.bodies.get(handle) else {
+			return;
+		};
+		let (Some(parent_handle), Some(orbit)) = (&entry.parent, entry.orbit) else {
+			return;
+		};
+		let mass = entry.info.mass_kg();
+		if mass <= zero || orbit.eccentricity >= one {
+			return;
+		}
+		let parent = self.get_entry(parent_handle);
+		let area = T::from_f64(std::f64::consts::PI).unwrap() * Float::powi(entry.info.radius_avg_m(), 2);
+		let density = |radius: T| surface_density * Float::exp(-(radius - parent.info.radius_avg_m()) / scale_height);
+		let periapsis = orbit.semimajor_axis * (one - orbit.eccentricity);
+		let apoapsis = orbit.semimajor_axis * (one + orbit.eccentricity);
+		let (periapsis_density, apoapsis_density) = (density(periapsis), density(apoapsis));
+		let mean_density = (periapsis_density + apoapsis_density) / two;
+		let decay = mean_density * Float::sqrt(parent.gm() * orbit.semimajor_axis) * drag_coefficient * area / mass * dt;
+		if Float::is_nan(decay) || decay <= zero {
+			return;
+		}
+		// drag at periapsis lowers the apoapsis and drag at apoapsis lowers the periapsis
+		let new_apoapsis = apoapsis - decay * periapsis_density / mean_density;
+		let new_periapsis = Float::max(periapsis - decay * apoapsis_density / mean_density, zero);
+		let new_apoapsis = Float::max(new_apoapsis, new_periapsis);
+		let current_mean_anomaly = ToPrimitive::to_f64(&self.mean_anomaly_at_time(handle, time)).unwrap();
+		let entry = self.bodies.get_mut(handle).unwrap();
+		let orbit = entry.orbit.as_mut().unwrap();
+		orbit.semimajor_axis = (new_apoapsis + new_periapsis) / two;
+		orbit.eccentricity = (new_apoapsis - new_periapsis) / (new_apoapsis + new_periapsis);
+		let orbit = *orbit;
+		// pick the mean anomaly at epoch so the body is still where it was at `time`
+		let n = ToPrimitive::to_f64(&self.mean_motion_of(handle).unwrap()).unwrap();
+		let mean_anomaly_at_epoch = Self::wrap_mean_anomaly(&orbit, current_mean_anomaly - n * ToPrimitive::to_f64(&time).unwrap());
+		self.bodies.get_mut(handle).unwrap().mean_anomaly_at_epoch = mean_anomaly_at_epoch;
+	}
+	/// Adds a virtual [marker](DatabaseEntry::is_marker) body with no mass or radius at one of the
+	/// Lagrange points of `secondary` and its parent `primary`
+	/// 
+	/// The virtual body orbits `primary` in lockstep with `secondary`, sharing its orbital period
+	/// and orientation. L4 and L5 form equilateral triangles with both bodies. L1 and L2 are placed
+	/// at the Hill sphere distance *a·cbrt(m₂ / 3m₁)* from the secondary, and L3 at *a·(1 + 5μ/12)*
+	/// from the primary with *μ = m₂ / (m₁ + m₂)*, which are the usual approximations to the exact
+	/// positions when the secondary is much lighter than the primary.
+	/// 
+	/// Panics if `secondary` doesn't orbit `primary`.
+	pub fn add_lagrange_point(&mut self, handle: H, primary: &H, secondary: &H, point: LagrangePoint, name: &str) where H: Debug {
+		let error_msg = format!("Body {:?} doesn't orbit body {:?}", secondary, primary);
+		let entry = self.lagrange_point_entry(primary, secondary, point, name).expect(&error_msg);
+		self.add_entry(handle, entry);
+	}
+	/// Adds a body that shares the orbit of `companion_of`, 60° ahead of it at its L4 point if
+	/// `leading` is `true` or 60° behind it at L5 otherwise, like the Trojan asteroids of Jupiter
+	/// 
+	/// The companion's orbital elements are copied and its mean anomaly at epoch is shifted by 60°,
+	/// so the two keep the same period and stay the same distance apart in mean anomaly forever.
+	/// Unlike [`add_lagrange_point`](Self::add_lagrange_point) the new body is a real body with
+	/// its own `info`, which makes it easy to populate whole swarms of co-orbital asteroids.
+	/// 
+	/// Fails if the handle is already in use, or if the companion isn't in the database or doesn't
+	/// orbit anything.
+	pub fn add_trojan(&mut self, handle: H, companion_of: &H, leading: bool, info: Body<T>, name: &str) -> Result<(), OrbitError<H>> {
+		if self.bodies.contains_key(&handle) {
+			return Err(OrbitError::DuplicateHandle(handle));
+		}
+		let companion = self.bodies.get(companion_of).ok_or_else(|| OrbitError::UnknownBody(companion_of.clone()))?;
+		let (Some(parent_handle), Some(orbit)) = (&companion.parent, companion.orbit) else {
+			return Err(OrbitError::NoOrbit(companion_of.clone()));
+		};
+		let sixty_degrees = T::from_f64(std::f64::consts::FRAC_PI_3).unwrap();
+		let offset = if leading { sixty_degrees } else { -sixty_degrees };
+		let mut entry = DatabaseEntry::new(info, name).with_parent(parent_handle.clone(), orbit);
+		entry.mean_anomaly_at_epoch = companion.mean_anomaly_at_epoch + offset;
+		entry.fixed_mean_motion = companion.fixed_mean_motion;
+		self.add_entry(handle, entry);
+		Ok(())
+	}
+	/// Gets the position of one of the Lagrange points of `secondary` and its parent `primary`
+	/// relative to `primary` at the given time, without adding it to the database
+	/// 
+	/// See [`add_lagrange_point`](Self::add_lagrange_point) for how the points are placed.
+	/// Returns `None` if `secondary` doesn't orbit `primary`.
+	pub fn lagrange_point_position(&self, primary: &H, secondary: &H, point: LagrangePoint, time: T) -> Option<Vector3<T>> where H: Debug, T: RealField {
+		let entry = self.lagrange_point_entry(primary, secondary, point, "")?;
+		let orbit = entry.orbit?;
+		let to_f64 = |value: T| ToPrimitive::to_f64(&value).unwrap();
+		let mean_anomaly = to_f64(entry.mean_anomaly_at_epoch) + to_f64(entry.fixed_mean_motion?) * to_f64(time);
+		let true_anomaly = orbit.true_anomaly_at_mean_anomaly(Self::wrap_mean_anomaly(&orbit, mean_anomaly));
+		Some(self.apply_convention(self.parent_axis_rotation(&entry) * orbit.position_at_true_anomaly(true_anomaly)))
+	}
+	/// Creates the entry for a virtual body at a Lagrange point, see
+	/// [`add_lagrange_point`](Self::add_lagrange_point)
+	fn lagrange_point_entry(&self, primary: &H, secondary: &H, point: LagrangePoint, name: &str) -> Option<DatabaseEntry<H, T>> where H: Debug {
+		let one = T::from_f32(1.0).unwrap();
+		let secondary_entry = self.bodies.get(secondary)?;
+		if secondary_entry.parent.as_ref() != Some(primary) {
+			return None;
+		}
+		let mut orbit = secondary_entry.orbit?;
+		let primary_mass = self.get_entry(primary).info.mass_kg();
+		let secondary_mass = secondary_entry.info.mass_kg();
+		let hill_ratio = Float::cbrt(secondary_mass / (T::from_f32(3.0).unwrap() * primary_mass));
+		let mass_ratio = secondary_mass / (primary_mass + secondary_mass);
+		let (distance_factor, angle_deg) = match point {
+			LagrangePoint::L1 => (one - hill_ratio, 0.0),
+			LagrangePoint::L2 => (one + hill_ratio, 0.0),
+			LagrangePoint::L3 => (one + T::from_f64(5.0 / 12.0).unwrap() * mass_ratio, 180.0),
+			LagrangePoint::L4 => (one, 60.0),
+			LagrangePoint::L5 => (one, -60.0),
+		};
+		orbit.semimajor_axis = orbit.semimajor_axis * distance_factor;
+		orbit.arg_of_periapsis = orbit.arg_of_periapsis + T::from_f64(angle_deg * CONVERT_DEG_TO_RAD).unwrap();
+		let mut entry = DatabaseEntry::new(Body::default(), name).with_parent(primary.clone(), orbit).with_marker(true);
+		entry.mean_anomaly_at_epoch = secondary_entry.mean_anomaly_at_epoch;
+		entry.fixed_mean_motion = self.mean_motion_of(secondary);
+		Some(entry)
+	}
+	/// Instantly changes the velocity of the given body by `burn_vector_m_per_s` at the given time,
+	/// replacing its orbit with the one that continues on from its current position with the new
+	/// velocity
+	/// 
+	/// The burn is in the same world space as the vectors returned by
+	/// [`state_vector_at_time`](Self::state_vector_at_time). Any fixed mean motion the body had is
+	/// cleared, since it now follows a normal Keplerian orbit. Returns an error and leaves the
+	/// body unchanged if the body isn't in the database, doesn't orbit anything, or if the new
+	/// orbit's periapsis is below the surface of its parent.
+	pub fn apply_impulsive_burn(&mut self, handle: &H, burn_vector_m_per_s: Vector3<T>, time: T) -> Result<(), OrbitError<H>> where H: Debug, T: RealField {
+		self.spatial_index = None;
+		let one = T::from_f32(1.0).unwrap();
+		let entry = self.bodies.get(handle).ok_or_else(|| OrbitError::UnknownBody(handle.clone()))?;
+		let (parent_handle, orbit) = match (&entry.parent, entry.orbit) {
+			(Some(parent_handle), Some(orbit)) => (parent_handle.clone(), orbit),
+			_ => return Err(OrbitError::NoOrbit(handle.clone())),
+		};
+		let parent = &self.get_entry(&parent_handle).info;
+		let parent_gm = parent.gm();
+		// work in the parent's reference plane that the orbital elements are defined in
+		let (position, velocity) = self.state_vector_at_time(handle, time).unwrap();
+		let world_to_orbit = self.parent_axis_rotation(entry).inverse();
+		let position = world_to_orbit * self.remove_convention(position);
+		let velocity = world_to_orbit * self.remove_convention(velocity + burn_vector_m_per_s);
+		let mut new_orbit = OrbitalElements::from_state_vectors(position, velocity, parent_gm, time);
+		new_orbit.element_type = orbit.element_type;
+		if new_orbit.semimajor_axis * (one - new_orbit.eccentricity) < parent.radius_equator_m() {
+			return Err(OrbitError::OrbitIntersectsParentBody(parent_handle));
+		}
+		// pick the mean anomaly at epoch so the body is where it is now at `time`
+		let n = mean_motion(parent_gm, new_orbit.semimajor_axis);
+		let entry = self.bodies.get_mut(handle).unwrap();
+		entry.orbit = Some(new_orbit);
+		entry.fixed_mean_motion = None;
+		entry.mean_anomaly_at_epoch = -n * new_orbit.time_of_periapsis_passage;
+		Ok(())
+	}
+	/// Adds a probe on a hyperbolic flyby trajectory past the given parent body
+	/// 
+	/// The trajectory is described the way launch and arrival conditions usually are: by its
+	/// characteristic energy *C3* in km²/s², which is the square of the hyperbolic excess speed,
+	/// and by the right ascension and declination of its outgoing asymptote in degrees. These are
+	/// measured in the parent's reference plane, with right ascension counter-clockwise around +Y
+	/// from the +X axis. The orbit is placed in the least inclined prograde plane that contains the
+	/// asymptote, and the probe passes its periapsis `periapsis_dist_m` meters from the parent's
+	/// center at `time_of_periapsis` seconds since epoch.
+	/// 
+	/// Panics if the parent isn't in the database.
+	#[allow(clippy::too_many_arguments)]
+	pub fn insert_probe_hyperbolic(&mut self, handle: H, parent: &H, name: &str, c3_km2_per_s2: T, right_ascension_deg: T, declination_deg: T, periapsis_dist_m: T, time_of_periapsis: T) where H: Debug, T: RealField {
+		let parent_gm = self.get_entry(parent).gm();
+		let c3 = c3_km2_per_s2 * T::from_f64(CONVERT_KM_TO_M * CONVERT_KM_TO_M).unwrap();
+		let right_ascension = right_ascension_deg * T::from_f64(CONVERT_DEG_TO_RAD).unwrap();
+		let declination = declination_deg * T::from_f64(CONVERT_DEG_TO_RAD).unwrap();
+		let asymptote = Vector3::new(
+			Float::cos(declination) * Float::cos(right_ascension),
+			Float::sin(declination),
+			-Float::cos(declination) * Float::sin(right_ascension),
+		);
+		let orbit = Self::hyperbola_from_asymptote(parent_gm, asymptote * Float::sqrt(c3), false, periapsis_dist_m, time_of_periapsis);
+		let n = mean_motion(parent_gm, orbit.semimajor_axis);
+		let mut entry = DatabaseEntry::new(Body::default(), name).with_parent(parent.clone(), orbit);
+		entry.mean_anomaly_at_epoch = -n * time_of_periapsis;
+		self.add_entry(handle, entry);
+	}
+	/// Builds the hyperbolic orbit around a parent with the given *GM* that has the hyperbolic
+	/// excess velocity `v_infinity` in the parent's internal frame, on the way in if `incoming` is
+	/// `true` or on the way out otherwise, and passes periapsis `periapsis_dist` meters from the
+	/// parent's center at `time_of_periapsis`
+	/// 
+	/// The orbit is placed in the least inclined prograde plane that contains the asymptote.
+	fn hyperbola_from_asymptote(parent_gm: T, v_infinity: Vector3<T>, incoming: bool, periapsis_dist: T, time_of_periapsis: T) -> OrbitalElements<T> where T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let semimajor_axis = -parent_gm / v_infinity.norm_squared();
+		let eccentricity = one + periapsis_dist / Float::abs(semimajor_axis);
+		// direction from the parent along the asymptote, which the probe comes in from when
+		// arriving and heads out along when leaving
+		let asymptote = match incoming {
+			true => -v_infinity.normalize(),
+			false => v_infinity.normalize(),
+		};
+		// the normal closest to +Y gives the least inclined plane, falling back to a polar orbit
+		// when the asymptote points straight up or down
+		let up = Vector3::new(zero, one, zero);
+		let normal = up - asymptote * asymptote.dot(&up);
+		let normal = if normal.norm() < T::from_f64(1e-9).unwrap() { Vector3::new(one, zero, zero) } else { normal.normalize() };
+		// rotate from the asymptote's true anomaly back to periapsis, forwards for the incoming
+		// asymptote at -ν∞ and backwards for the outgoing one at +ν∞
+		let true_anomaly_asymptote = Float::acos(-one / eccentricity);
+		let rotation_sign = if incoming { one } else { -one };
+		let dir_periapsis = asymptote * Float::cos(true_anomaly_asymptote) + normal.cross(&asymptote) * Float::sin(true_anomaly_asymptote) * rotation_sign;
+		let speed_periapsis = Float::sqrt(parent_gm * (two / periapsis_dist - one / semimajor_axis));
+		let position = dir_periapsis * periapsis_dist;
+		let velocity = normal.cross(&dir_periapsis) * speed_periapsis;
+		OrbitalElements::from_state_vectors(position, velocity, parent_gm, time_of_periapsis)
+	}
+	/// Works out the hyperbolic orbit around `target` of a probe arriving on a transfer orbit,
+	/// the second half of a patched conic interplanetary transfer
+	/// 
+	/// `transfer_vel` is the probe's velocity in meters per second relative to the target's
+	/// parent at `time`, like the arrival velocity from [`lambert`](Self::lambert). Subtracting the
+	/// target's own velocity gives the hyperbolic excess velocity *v∞* that the probe arrives
+	/// with. The hyperbola lies in the least inclined prograde plane around the target that
+	/// contains the incoming asymptote, aimed so that periapsis is 10% of the target's equatorial
+	/// radius above its surface, which is where a capture burn is most efficient. The probe
+	/// crosses into the target's sphere of influence at `time`.
+	/// 
+	/// Returns `None` if the target doesn't orbit anything, or if the probe would arrive with the
+	/// target's exact velocity and so never approach it.
+	pub fn arrival_hyperbola(&self, transfer_vel: Vector3<T>, target: &H, time: T) -> Option<OrbitalElements<T>> where H: Debug + Ord, T: RealField {
+		let zero = T::from_f32(0.0).unwrap();
+		let (_, target_velocity) = self.state_vector_at_time(target, time)?;
+		let v_infinity = self.remove_convention(transfer_vel - target_velocity);
+		if v_infinity.norm() <= zero {
+			return None;
+		}
+		let target_entry = self.get_entry(target);
+		let target_gm = target_entry.gm();
+		// into the target's equatorial frame that its satellites' orbits are measured in
+		let v_infinity = Self::axis_rotation(target_entry).inverse() * v_infinity;
+		let periapsis = target_entry.info.radius_equator_m() * T::from_f32(1.1).unwrap();
+		// find when the probe passes periapsis from when it enters the sphere of influence
+		let approach = Self::hyperbola_from_asymptote(target_gm, v_infinity, true, periapsis, zero);
+		let time_to_periapsis = match approach.true_anomaly_at_radius(self.radius_soi(target)) {
+			Some((outbound, _)) => -approach.mean_anomaly_at_true_anomaly(-outbound) / mean_motion(target_gm, approach.semimajor_axis),
+			None => zero,
+		};
+		Some(Self::hyperbola_from_asymptote(target_gm, v_infinity, true, periapsis, time + time_to_periapsis))
+	}
+	/// Simulates a rocket launch from the equator of the given parent body following a gravity turn,
+	/// returning `(time_s, altitude_m, speed_m_per_s)` once every second of flight
+	/// 
+	/// The rocket starts on the surface moving at `initial_speed_m_per_s` and thrusts with a
+	/// constant acceleration of `thrust_m_per_s2`. It's steered by `pitch_program`, a list of
+	/// `(time_s, pitch_angle_rad)` pairs sorted by time, where the pitch is measured up from the
+	/// local horizon and is linearly interpolated between the given times. The pitch before the
+	/// first entry and after the last one is held constant. An entry with the same time as the
+	/// one before it makes the pitch jump straight to its angle.
+	/// 
+	/// With `drag` set, the rocket is slowed by the atmosphere described there. Otherwise the
+	/// flight happens in vacuum. The flight is integrated in the plane of the launch with a 4th
+	/// order Runge-Kutta stepper, ignoring the parent's rotation, and ends as soon as the rocket
+	/// reaches `target_altitude_m`, falls back to the surface, or after an hour of flight.
+	pub fn gravity_turn_trajectory(&self, parent: &H, target_altitude_m: T, initial_speed_m_per_s: T, thrust_m_per_s2: T, pitch_program: &[(T, T)], drag: Option<LaunchDrag<T>>) -> Vec<(T, T, T)> where H: Debug {
+		let zero = T::from_f32(0.0).unwrap();
+		let half = T::from_f32(0.5).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let six = T::from_f32(6.0).unwrap();
+		let time_step = T::from_f32(1.0).unwrap();
+		let max_steps = 3600;
+		let parent = &self.get_entry(parent).info;
+		let parent_gm = parent.gm();
+		let radius = parent.radius_equator_m();
+		let pitch_at = |time: T| {
+			let (first_time, first_pitch) = match pitch_program.first() {
+				Some(&entry) => entry,
+				None => return zero,
+			};
+			if time <= first_time {
+				return first_pitch;
+			}
+			for window in pitch_program.windows(2) {
+				let ((start_time, start_pitch), (end_time, end_pitch)) = (window[0], window[1]);
+				if end_time <= start_time {
+					continue;
+				}
+				if time <= end_time {
+					return start_pitch + (end_pitch - start_pitch) * (time - start_time) / (end_time - start_time);
+				}
+			}
+			pitch_program[pitch_program.len() - 1].1
+		};
+		// state is [x, y, vx, vy] with +Y pointing up from the launch site
+		let derivative = |time: T, state: [T; 4]| {
+			let distance = Float::sqrt(state[0] * state[0] + state[1] * state[1]);
+			let (up_x, up_y) = (state[0] / distance, state[1] / distance);
+			let (horizon_x, horizon_y) = (up_y, -up_x);
+			let pitch = pitch_at(time);
+			let (cos_pitch, sin_pitch) = (Float::cos(pitch), Float::sin(pitch));
+			let gravity = parent_gm / (distance * distance);
+			// drag acts against the velocity with a magnitude of ρv²·C_d·A/2m
+			let drag_per_speed = match drag {
+				Some(drag) => {
+					let density = drag.surface_density_kg_m3 * Float::exp(-(distance - radius) / drag.scale_height_m);
+					let speed = Float::sqrt(state[2] * state[2] + state[3] * state[3]);
+					half * density * speed * drag.drag_coefficient * drag.area_to_mass_m2_per_kg
+				},
+				None => zero,
+			};
+			[
+				state[2],
+				state[3],
+				thrust_m_per_s2 * (cos_pitch * horizon_x + sin_pitch * up_x) - gravity * up_x - drag_per_speed * state[2],
+				thrust_m_per_s2 * (cos_pitch * horizon_y + sin_pitch * up_y) - gravity * up_y - drag_per_speed * state[3],
+			]
+		};
+		let offset = |state: [T; 4], slope: [T; 4], scale: T| {
+			[state[0] + slope[0] * scale, state[1] + slope[1] * scale, state[2] + slope[2] * scale, state[3] + slope[3] * scale]
+		};
+		let initial_pitch = pitch_at(zero);
+		let mut state = [
+			zero,
+			radius,
+			initial_speed_m_per_s * Float::cos(initial_pitch),
+			initial_speed_m_per_s * Float::sin(initial_pitch),
+		];
+		let mut time = zero;
+		let mut samples = vec![(zero, zero, initial_speed_m_per_s)];
+		for _ in 0..max_steps {
+			let k1 = derivative(time, state);
+			let k2 = derivative(time + time_step * half, offset(state, k1, time_step * half));
+			let k3 = derivative(time + time_step * half, offset(state, k2, time_step * half));
+			let k4 = derivative(time + time_step, offset(state, k3, time_step));
+			for i in 0..4 {
+				state[i] = state[i] + time_step * (k1[i] + two * k2[i] + two * k3[i] + k4[i]) / six;
+			}
+			time = time + time_step;
+			let altitude = Float::sqrt(state[0] * state[0] + state[1] * state[1]) - radius;
+			let speed = Float::sqrt(state[2] * state[2] + state[3] * state[3]);
+			samples.push((time, altitude, speed));
+			if altitude >= target_altitude_m || altitude < zero {
+				break;
+			}
+		}
+		samples
+	}
+	/// Mean motion *n* of the given body around its parent, in radians per second
+	fn mean_motion_of(&self, handle: &H) -> Option<T> where H: Debug {
+		let entry = self.bodies.get(handle)?;
+		let orbit = entry.orbit?;
+		let parent_gm = self.get_entry(entry.parent.as_ref()?).gm();
+		if let Some(n) = entry.fixed_mean_motion {
+			return Some(n);
+		}
+		Some(mean_motion(parent_gm, orbit.semimajor_axis))
+	}
+	/// Estimates the orbit of an unknown object around the given parent body from three
+	/// `(position, time)` observations relative to that parent, in chronological order
+	/// 
+	/// See [`OrbitalElements::gibbs_iod`] for details on the method used.
+	pub fn observation_arc_to_elements(&self, parent: &H, observations: [(Vector3<T>, T); 3]) -> Option<OrbitalElements<T>> where T: RealField {
+		let parent_gm = self.bodies.get(parent)?.gm();
+		let [(r1, t1), (r2, t2), (r3, t3)] = observations;
+		OrbitalElements::gibbs_iod(self.remove_convention(r1), t1, self.remove_convention(r2), t2, self.remove_convention(r3), t3, parent_gm)
+	}
+	/// Exports a table of positions for each of the given bodies as comma separated text, for
+	/// cross-referencing with ephemerides from other tools
+	/// 
+	/// This is a plain table, not a SPICE kernel. Each body gets a `# <name> (ID <handle>)` comment
+	/// line, a `time_s,x_km,y_km,z_km` header and `steps` rows evenly spaced from `t_start` to
+	/// `t_end` inclusive. Times are seconds since the database epoch and positions are the body's
+	/// absolute position in kilometers (km). Blocks are separated by a blank line and bodies that
+	/// aren't in the database are skipped.
+	pub fn export_position_table(&self, body_handles: &[H], t_start: T, t_end: T, steps: usize) -> String where H: Debug, T: RealField + SimdValue + SimdRealField + Display {
+		let m_to_km = T::from_f64(CONVERT_M_TO_KM).unwrap();
+		let mut blocks = Vec::new();
+		for handle in body_handles {
+			let Some(entry) = self.bodies.get(handle) else {
+				continue;
+			};
+			let mut block = format!("# {} (ID {:?})\ntime_s,x_km,y_km,z_km\n", entry.name, handle);
+			for step in 0..steps {
+				let time = if steps > 1 {
+					t_start + (t_end - t_start) * T::from_usize(step).unwrap() / T::from_usize(steps - 1).unwrap()
+				} else {
+					t_start
+				};
+				let position = self.absolute_position_at_time(handle, time) * m_to_km;
+				block.push_str(&format!("{},{},{},{}\n", time, position.x, position.y, position.z));
+			}
+			blocks.push(block);
+		}
+		blocks.join("\n")
+	}
+	/// Prints the orbital elements of every body that orbits something as a table sorted by
+	/// handle, see [`OrbitalElements::elements_table_row`]
+	/// 
+	/// Only available in debug builds.
+	#[cfg(debug_assertions)]
+	pub fn print_orbital_table(&self) where H: Ord, T: Display {
+		println!("{}", self.orbital_table());
+	}
+	/// Builds the table printed by [`print_orbital_table`](Self::print_orbital_table)
+	#[cfg(debug_assertions)]
+	fn orbital_table(&self) -> String where H: Ord, T: Display {
+		let mut entries: Vec<(&H, &DatabaseEntry<H, T>)> = self.bodies.iter().collect();
+		entries.sort_by_key(|(handle, _)| *handle);
+		let mut rows = vec![format!("| {:20} | {:12} | {:>11} | {:8} | {:9} | {:9} | {:9} |", "Name", "Parent", "a", "e", "i", "ω", "Ω")];
+		for (_, entry) in entries {
+			if let (Some(parent_handle), Some(orbit)) = (&entry.parent, &entry.orbit) {
+				let parent_name = self.bodies.get(parent_handle).map_or("?", |parent| parent.name.as_str());
+				rows.push(orbit.elements_table_row(&entry.name, parent_name));
+			}
+		}
+		rows.join("\n")
+	}
+	/// Finds all bodies whose name contains `query`, ignoring case, sorted alphabetically by name
+	pub fn find_by_partial_name(&self, query: &str) -> Vec<(&H, &DatabaseEntry<H, T>)> {
+		let query = query.to_lowercase();
+		self.find_by_name(|name| name.contains(&query))
+	}
+	/// Finds all bodies whose name starts with `prefix`, ignoring case, sorted alphabetically by
+	/// name
+	pub fn find_by_name_prefix(&self, prefix: &str) -> Vec<(&H, &DatabaseEntry<H, T>)> {
+		let prefix = prefix.to_lowercase();
+		self.find_by_name(|name| name.starts_with(&prefix))
+	}
+	/// Finds all bodies whose lowercase name matches the given predicate, sorted by name
+	fn find_by_name<F>(&self, predicate: F) -> Vec<(&H, &DatabaseEntry<H, T>)> where F: Fn(&str) -> bool {
+		let mut matches: Vec<(&H, &DatabaseEntry<H, T>)> = self.iter()
+			.filter(|(_, entry)| predicate(&entry.name.to_lowercase()))
+			.collect();
+		matches.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+		matches
+	}
+	pub fn iter(&self) -> Iter<'_, H, DatabaseEntry<H, T>> {
+		self.bodies.iter()
+	}
+}
+impl<H, T> Default for Database<H, T> {
+	fn default() -> Self {
+		Self{ bodies: HashMap::new(), coordinate_convention: CoordinateConvention::default(), reference_plane: ReferencePlane::default(), reference_plane_rotation: None, spatial_index: None, barycentric_wobble: false }
+	}
+}
+
 
 pub struct DatabaseEntry<H, T> {
 	pub parent: Option<H>,
@@ -1143,6 +3536,23 @@ pub struct DatabaseEntry<H, T> {
 	pub orbit: Option<OrbitalElements<T>>,
 	pub mean_anomaly_at_epoch: T,
 	pub scale: T,
+	/// Mean motion in radians per second to use instead of the one from Kepler's third law, for
+	/// virtual bodies like Lagrange points that don't follow their own Keplerian orbit
+	pub fixed_mean_motion: Option<T>,
+	/// Linear drift of the body's orbital elements over long timescales, see
+	/// [`Database::orbital_elements_at_epoch`]
+	pub secular_drift: Option<SecularDrift<T>>,
+	/// Minimum time in seconds between updates of the body's position by [`Database::step`], see
+	/// [`Database::set_update_interval`]
+	update_interval: Option<T>,
+	/// Time in seconds passed to [`Database::step`] that hasn't been applied to the body yet
+	/// because of its `update_interval`
+	pending_step: T,
+	/// Marks the body as a massless marker, like a barycenter node, a waypoint or a Lagrange point,
+	/// which is left out of mass and gravity calculations even if its [`Body`] has a mass, and
+	/// doesn't block lines of sight or show up in proximity queries like
+	/// [`Database::bodies_within`]
+	pub is_marker: bool,
 }
 impl<H, T> DatabaseEntry<H, T> where T: Float + FromPrimitive + SubAssign {
 	pub fn new<S>(info: Body<T>, name: S) -> Self where S: Into<String> {
@@ -1150,6 +3560,11 @@ impl<H, T> DatabaseEntry<H, T> where T: Float + FromPrimitive + SubAssign {
 			info, name: name.into(),
 			parent: None, orbit: None, mean_anomaly_at_epoch: T::from_f64(0.0).unwrap(),
 			scale: T::from_f64(1.0 / 3_000_000.0).unwrap(),
+			fixed_mean_motion: None,
+			secular_drift: None,
+			update_interval: None,
+			pending_step: T::from_f64(0.0).unwrap(),
+			is_marker: false,
 		}
 	}
 	pub fn with_parent(mut self, parent_handle: H, orbital_elements: OrbitalElements<T>) -> Self {
@@ -1161,6 +3576,27 @@ impl<H, T> DatabaseEntry<H, T> where T: Float + FromPrimitive + SubAssign {
 		self.scale = scale;
 		self
 	}
+	/// Sets the minimum time in seconds between updates of the body's position by
+	/// [`Database::step`], see [`Database::set_update_interval`]
+	pub fn with_update_interval(mut self, interval: T) -> Self {
+		self.update_interval = Some(interval);
+		self
+	}
+	/// Gets the minimum time in seconds between updates of the body's position by
+	/// [`Database::step`], if it has one
+	pub fn update_interval(&self) -> Option<T> {
+		self.update_interval
+	}
+	/// Marks the body as a massless marker, see [`is_marker`](Self::is_marker)
+	pub fn with_marker(mut self, is_marker: bool) -> Self {
+		self.is_marker = is_marker;
+		self
+	}
+	/// Sets the linear drift rates of the body's orbital elements
+	pub fn with_secular_drift(mut self, drift: SecularDrift<T>) -> Self {
+		self.secular_drift = Some(drift);
+		self
+	}
 	pub fn with_mean_anomaly_deg(mut self, mean_anomaly: T) -> Self {
 		self.mean_anomaly_at_epoch = mean_anomaly * T::from_f64(CONVERT_DEG_TO_RAD).unwrap();
 		let circle = T::from_f64(360.0).unwrap();
@@ -1174,11 +3610,128 @@ impl<H, T> DatabaseEntry<H, T> where T: Float + FromPrimitive + SubAssign {
 	}
 }
 
+/// Which axis points "up", away from the reference plane, in vectors passed into and returned from
+/// a [`Database`]
+/// 
+/// Both conventions are right-handed, and the +X axis is the same in both of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoordinateConvention {
+	/// +Y is up and the reference plane is the X/Z plane, like in Bevy and Godot
+	#[default]
+	YUp,
+	/// +Z is up and the reference plane is the X/Y plane, like in Blender and Unreal
+	ZUp,
+}
+
+/// The plane that vectors passed into and returned from a [`Database`] are measured against,
+/// which is the plane "up" points away from in its [`CoordinateConvention`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReferencePlane {
+	/// The plane of Earth's orbit, which the orbital elements of the bundled solar system are
+	/// given relative to
+	#[default]
+	Ecliptic,
+	/// The [invariable plane](Database::invariable_plane_normal) perpendicular to the total
+	/// orbital angular momentum of the bodies in the database
+	Invariable,
+}
+
+/// Linear rates of change of an orbit's elements per Julian year, for slow changes from
+/// perturbations by other bodies over long timescales
+/// 
+/// Published ephemerides like JPL's approximate planetary positions list these rates per century,
+/// so divide those by 100.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SecularDrift<T> {
+	/// Change in semimajor axis in meters (m) per year
+	pub da_per_year: T,
+	/// Change in eccentricity per year
+	pub de_per_year: T,
+	/// Change in inclination in radians per year
+	pub di_per_year: T,
+}
+
+/// Aerodynamic drag on a rocket climbing through an exponential atmosphere, see
+/// [`Database::gravity_turn_trajectory`]
+/// 
+/// The air density at altitude *h* is *ρ₀·e^(-h/H)*, the same model as [`Database::apply_drag`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LaunchDrag<T> {
+	/// Drag coefficient *C_d* of the rocket
+	pub drag_coefficient: T,
+	/// Cross-sectional area of the rocket divided by its mass, in m²/kg
+	pub area_to_mass_m2_per_kg: T,
+	/// Density *ρ₀* of the atmosphere at the surface in kg/m³
+	pub surface_density_kg_m3: T,
+	/// Scale height *H* of the atmosphere in meters (m)
+	pub scale_height_m: T,
+}
+
+/// The five Lagrange points of a pair of bodies, where a third body of negligible mass can stay
+/// in place relative to both of them
+/// 
+/// See [`Database::add_lagrange_point`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LagrangePoint {
+	/// Between the two bodies
+	L1,
+	/// On the far side of the secondary body from the primary
+	L2,
+	/// On the far side of the primary body from the secondary
+	L3,
+	/// 60° ahead of the secondary body along its orbit
+	L4,
+	/// 60° behind the secondary body along its orbit
+	L5,
+}
+
+/// Shape of the shadow a body casts away from a light source, see [`Database::shadow_geometry`]
+/// 
+/// The umbra, where the light is completely blocked, is a cone that narrows from the occluder's
+/// radius down to a point `umbra_length` behind it. The penumbra, where the light is only partly
+/// blocked, is a cone that widens from the occluder. Fading between the two radii at a given
+/// distance behind the occluder gives a proper darkening gradient for eclipses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowCone<T> {
+	/// Unit vector pointing from the light through the occluder, along the middle of the shadow
+	pub axis: Vector3<T>,
+	/// Average radius of the occluder in meters (m)
+	pub occluder_radius: T,
+	/// Distance in meters (m) behind the occluder to the tip of the umbra
+	pub umbra_length: T,
+	/// Distance in meters (m) in front of the occluder, towards the light, to the point the
+	/// penumbra cone spreads out from
+	pub penumbra_apex_distance: T,
+}
+impl<T> ShadowCone<T> where T: Float + FromPrimitive {
+	/// Gets the radius of the umbra in meters (m) at the given distance behind the occluder, which
+	/// is zero past the tip of the umbra
+	pub fn umbra_radius_at(&self, distance: T) -> T {
+		Float::max(self.occluder_radius * (T::from_f32(1.0).unwrap() - distance / self.umbra_length), T::from_f32(0.0).unwrap())
+	}
+	/// Gets the radius of the penumbra in meters (m) at the given distance behind the occluder
+	pub fn penumbra_radius_at(&self, distance: T) -> T {
+		self.occluder_radius * (T::from_f32(1.0).unwrap() + distance / self.penumbra_apex_distance)
+	}
+}
+
+
+/// Total delta-v of a Hohmann transfer between circular orbits of radius `r_1` and `r_2` around a
+/// parent with the given *GM*
+fn hohmann_delta_v<T>(gm: T, r_1: T, r_2: T) -> T where T: Float + FromPrimitive {
+	let one = T::from_f32(1.0).unwrap();
+	let two = T::from_f32(2.0).unwrap();
+	let departure = Float::sqrt(gm / r_1) * (Float::sqrt(two * r_2 / (r_1 + r_2)) - one);
+	let arrival = Float::sqrt(gm / r_2) * (one - Float::sqrt(two * r_1 / (r_1 + r_2)));
+	Float::abs(departure) + Float::abs(arrival)
+}
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use super::handles::*;
+	use approx::assert_ulps_eq;
+	use crate::constants;
 
 	#[test]
 	fn get_satellites() {
@@ -1192,6 +3745,20 @@ mod tests {
 		assert!(satellites.contains(&HANDLE_DEIMOS));
 	}
 
+	#[test]
+	fn siblings() {
+		let database = Database::<u16, f32>::default().with_solar_system();
+		let siblings = database.siblings(&HANDLE_EARTH);
+		assert!(siblings.contains(&HANDLE_MARS));
+		assert!(siblings.contains(&HANDLE_JUPITER));
+		assert!(!siblings.contains(&HANDLE_EARTH));
+		assert!(!siblings.contains(&HANDLE_LUNA));
+		assert!(siblings.windows(2).all(|pair| pair[0] < pair[1]));
+		assert_eq!(vec![HANDLE_DEIMOS], database.siblings(&HANDLE_PHOBOS));
+		assert!(database.siblings(&HANDLE_LUNA).is_empty());
+		assert!(database.siblings(&HANDLE_SOL).is_empty());
+	}
+
 	#[test]
 	fn get_parents() {
 		let database = Database::<u16, f32>::default().with_solar_system();
@@ -1208,4 +3775,1754 @@ mod tests {
 		assert_eq!(HANDLE_MARS, heirarchy[1]);
 		assert_eq!(HANDLE_DEIMOS, heirarchy[2]);
 	}
+
+	#[test]
+	fn common_ancestor() {
+		let database = Database::<u16, f32>::default().with_solar_system();
+		assert_eq!(Some(HANDLE_MARS), database.common_ancestor(&HANDLE_PHOBOS, &HANDLE_DEIMOS));
+		assert_eq!(Some(HANDLE_SOL), database.common_ancestor(&HANDLE_LUNA, &HANDLE_DEIMOS));
+		assert_eq!(Some(HANDLE_EARTH), database.common_ancestor(&HANDLE_EARTH, &HANDLE_LUNA));
+		assert_eq!(Some(HANDLE_EARTH), database.common_ancestor(&HANDLE_LUNA, &HANDLE_EARTH));
+		assert_eq!(Some(HANDLE_SOL), database.common_ancestor(&HANDLE_SOL, &HANDLE_SOL));
+		assert_eq!(None, database.common_ancestor(&HANDLE_SOL, &u16::MAX));
+	}
+
+	#[test]
+	fn radius_hill_sphere() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let earth_hill_km = database.radius_hill_sphere(&HANDLE_EARTH) / 1000.0;
+		assert!((1_450_000.0..1_550_000.0).contains(&earth_hill_km), "Expected Earth's Hill sphere to be about 1.5 million km, got {:.0} km", earth_hill_km);
+		assert!(database.radius_hill_sphere(&HANDLE_LUNA) < database.radius_hill_sphere(&HANDLE_EARTH));
+		assert!(database.radius_hill_sphere(&HANDLE_SOL).is_infinite());
+	}
+
+	#[test]
+	fn satellites_within_radius() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(30_000.0).with_eccentricity(0.2);
+		database.add_entry(1000, DatabaseEntry::new(Body::new(1e15, 5.0, 5.0, 0.0), "Close moon").with_parent(HANDLE_EARTH, orbit));
+		let half_period = database.orbital_period_s(&1000).unwrap() / 2.0;
+		assert_ulps_eq!(24_000_000.0, database.orbital_radius_at_time(&1000, 0.0).unwrap(), epsilon=1.0);
+		assert_ulps_eq!(36_000_000.0, database.orbital_radius_at_time(&1000, half_period).unwrap(), epsilon=1.0);
+		let threshold = 30_000_000.0;
+		assert_eq!(vec![1000], database.satellites_within_radius(&HANDLE_EARTH, threshold, 0.0));
+		assert!(database.satellites_within_radius(&HANDLE_EARTH, threshold, half_period).is_empty());
+		assert_eq!(vec![HANDLE_LUNA, 1000], database.satellites_within_radius(&HANDLE_EARTH, 1e9, 0.0));
+		assert!(database.orbital_radius_at_time(&HANDLE_SOL, 0.0).is_none());
+	}
+
+	#[test]
+	fn rogue_planet() {
+		let mut database = Database::<u16, f64>::default();
+		// a dwarf planet sized rogue with a distant moon, far enough out that the moon would be
+		// outside the gravity threshold used for root bodies
+		let planet = Body::new(1e22, 700.0, 700.0, 10.0);
+		let moon = Body::new(1e19, 100.0, 100.0, 0.0);
+		let moon_orbit = OrbitalElements::default().with_semimajor_axis_km(2_000_000.0).with_eccentricity(0.1);
+		database.add_entry(1000, DatabaseEntry::new(planet.clone(), "Rogue"));
+		database.add_entry(1001, DatabaseEntry::new(moon, "Rogue moon").with_parent(1000, moon_orbit));
+		assert!(planet.distance_of_gravity(0.0000005) < moon_orbit.semimajor_axis);
+		let moon_position = database.position_at_time(&1001, 0.0);
+		assert_ulps_eq!(1_800_000_000.0, moon_position.norm(), epsilon=1.0);
+		assert_eq!(moon_position, database.absolute_position_at_time(&1001, 0.0));
+		assert_eq!(Some(moon_position), database.relative_position(&1000, &1001, 0.0));
+		assert!(database.position_at_time(&1000, 1e6).norm() == 0.0);
+		let planet_soi = database.radius_soi(&1000);
+		assert!(planet_soi.is_finite());
+		assert!(planet_soi >= 2_200_000_000.0 + database.radius_soi(&1001));
+		assert!(database.time_to_soi_exit(&1001, 0.0).is_none());
+	}
+
+	#[test]
+	fn schwarzschild_radius() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		assert_ulps_eq!(2953.0, database.schwarzschild_radius_m(&HANDLE_SOL), epsilon=1.0);
+		assert!(!database.is_black_hole(&HANDLE_SOL));
+		assert!(!database.is_black_hole(&HANDLE_EARTH));
+		let black_hole = Body::new(10.0 * constants::f64::MASS_SUN_KG, 20.0, 20.0, 0.0);
+		database.add_entry(1000, DatabaseEntry::new(black_hole, "Black hole"));
+		assert_ulps_eq!(29_530.0, database.schwarzschild_radius_m(&1000), epsilon=10.0);
+		assert!(database.is_black_hole(&1000));
+	}
+
+	#[test]
+	fn time_to_soi_exit() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let handle = 1000;
+		let radius_soi = database.radius_soi(&HANDLE_EARTH);
+		let periapsis = 7_000_000.0;
+		let apoapsis = radius_soi * 2.0;
+		let orbit = OrbitalElements::default()
+			.with_semimajor_axis_m((periapsis + apoapsis) / 2.0)
+			.with_eccentricity((apoapsis - periapsis) / (apoapsis + periapsis));
+		let entry = DatabaseEntry::new(Body::default(), "Probe").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(handle, entry);
+		let exit_time = database.time_to_soi_exit(&handle, 0.0).unwrap();
+		assert!(exit_time > 0.0);
+		let radius = database.position_at_time(&handle, exit_time).norm();
+		assert!((radius - radius_soi).abs() / radius_soi < 0.001, "Expected radius {} at SOI exit, got {}", radius_soi, radius);
+		// once it's outside, the exit is right now
+		assert_eq!(Some(exit_time + 1.0), database.time_to_soi_exit(&handle, exit_time + 1.0));
+		// asking again from the next periapsis should give the exit on the following orbit
+		let period = database.orbital_period_s(&handle).unwrap();
+		let next_periapsis = exit_time + period - database.mean_anomaly_at_time(&handle, exit_time) / std::f64::consts::TAU * period;
+		let next_exit_time = database.time_to_soi_exit(&handle, next_periapsis).unwrap();
+		assert!((next_exit_time - exit_time - period).abs() < 1.0);
+		// a circular orbit entirely outside the sphere of influence is already out
+		let outside = OrbitalElements::default().with_semimajor_axis_m(radius_soi * 1.5);
+		database.add_entry(handle + 1, DatabaseEntry::new(Body::default(), "Outside").with_parent(HANDLE_EARTH, outside));
+		assert_eq!(Some(1234.0), database.time_to_soi_exit(&(handle + 1), 1234.0));
+		// luna's orbit stays well inside earth's sphere of influence
+		assert!(database.time_to_soi_exit(&HANDLE_LUNA, 0.0).is_none());
+		assert!(database.time_to_soi_exit(&HANDLE_SOL, 0.0).is_none());
+	}
+
+	#[test]
+	fn laplace_resonance_angle() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let start = database.laplace_resonance_angle_rad(&HANDLE_IO, &HANDLE_EUROPA, &HANDLE_GANYMEDE, 0.0);
+		let jupiter_gm = database.get_entry(&HANDLE_JUPITER).gm();
+		let n = |handle: u16| mean_motion(jupiter_gm, database.get_entry(&handle).orbit.unwrap().semimajor_axis);
+		let drift = n(HANDLE_IO) - 3.0 * n(HANDLE_EUROPA) + 2.0 * n(HANDLE_GANYMEDE);
+		// each moon's mean longitude circulates hundreds of times by the end, while the resonant
+		// combination only drifts by the small amount the mean motions miss the resonance by
+		assert!(drift.abs() < 0.01 * n(HANDLE_GANYMEDE));
+		for time in [0.0, 1e5, 1e6, 1e7, 1e8] {
+			let angle = database.laplace_resonance_angle_rad(&HANDLE_IO, &HANDLE_EUROPA, &HANDLE_GANYMEDE, time);
+			let expected = (start + drift * time).rem_euclid(std::f64::consts::TAU);
+			let offset = (angle - expected + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU) - std::f64::consts::PI;
+			assert!(offset.abs() < 1e-6, "Expected a resonance angle of {} at t = {}, got {}", expected, time, angle);
+		}
+		// anything other than one coefficient per body isn't a resonance angle
+		assert!(database.resonance_angle(&[HANDLE_IO, HANDLE_EUROPA], &[1, -3, 2], 0.0).is_none());
+		assert!(database.resonance_angle(&[HANDLE_IO, HANDLE_EUROPA], &[1, -2], 0.0).is_some());
+	}
+
+	#[test]
+	fn next_transfer_window() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let sun_gm = database.get_entry(&HANDLE_SOL).gm();
+		let n_earth = (sun_gm / database.get_entry(&HANDLE_EARTH).orbit.unwrap().semimajor_axis.powi(3)).sqrt();
+		let n_mars = (sun_gm / database.get_entry(&HANDLE_MARS).orbit.unwrap().semimajor_axis.powi(3)).sqrt();
+		let synodic_period = std::f64::consts::TAU / (n_earth - n_mars);
+		let first = database.next_transfer_window(&HANDLE_EARTH, &HANDLE_MARS, 0.0).unwrap();
+		assert!(first >= 0.0 && first <= synodic_period * 1.1);
+		let transfer_time = database.hohmann_transfer_time(&HANDLE_EARTH, &HANDLE_MARS).unwrap();
+		let ideal_phase = std::f64::consts::PI - n_mars * transfer_time;
+		let phase = (database.true_longitude_at_time(&HANDLE_MARS, first) - database.true_longitude_at_time(&HANDLE_EARTH, first)).rem_euclid(std::f64::consts::TAU);
+		assert_ulps_eq!(ideal_phase, phase, epsilon=0.001);
+		let second = database.next_transfer_window(&HANDLE_EARTH, &HANDLE_MARS, first + 1.0).unwrap();
+		let spacing = second - first;
+		assert!((spacing - synodic_period).abs() < 0.1 * synodic_period, "Expected windows {:.1} days apart, got {:.1} days", synodic_period / 86400.0, spacing / 86400.0);
+		assert!(database.next_transfer_window(&HANDLE_EARTH, &HANDLE_LUNA, 0.0).is_none());
+	}
+
+	#[test]
+	fn porkchop_grid() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let day = 86_400.0;
+		let synodic_period = 779.94 * day;
+		// a full synodic period of departures around each of the next few transfer windows always
+		// has its cheapest transfer inside the grid rather than pressed up against an edge
+		let mut after = 0.0;
+		for _ in 0..3 {
+			let window = database.next_transfer_window(&HANDLE_EARTH, &HANDLE_MARS, after).unwrap();
+			after = window + 10.0 * day;
+			let departure_range = (window - synodic_period / 2.0, window + synodic_period / 2.0);
+			let arrival_range = (departure_range.0 + 150.0 * day, departure_range.1 + 400.0 * day);
+			let grid = database.porkchop_grid(&HANDLE_EARTH, &HANDLE_MARS, departure_range, arrival_range, (10, 10));
+			assert_eq!(10, grid.len());
+			assert!(grid.iter().all(|row| row.len() == 10));
+			let (minimum, i, j) = grid.iter().enumerate()
+				.flat_map(|(i, row)| row.iter().enumerate().map(move |(j, delta_v)| (*delta_v, i, j)))
+				.fold((f64::INFINITY, 0, 0), |best, cell| if cell.0 < best.0 { cell } else { best });
+			assert!((1..9).contains(&i) && (1..9).contains(&j), "Expected the cheapest transfer inside the grid, found it at ({}, {})", i, j);
+			// nothing gets far below the ~5.6 km/s of a Hohmann transfer between the two orbits
+			assert!(minimum > 5000.0, "Expected no transfer under 5000 m/s, cheapest was {:.0} m/s", minimum);
+		}
+		// mars' eccentric orbit makes windows near its perihelion, like the one about 13 years
+		// after epoch, much cheaper than the rest. A finer grid around that window finds the
+		// published ~5.6 km/s minimum
+		let window = database.next_transfer_window(&HANDLE_EARTH, &HANDLE_MARS, 4500.0 * day).unwrap();
+		let departure_range = (window - 60.0 * day, window + 60.0 * day);
+		let arrival_range = (window + 150.0 * day, window + 390.0 * day);
+		let grid = database.porkchop_grid(&HANDLE_EARTH, &HANDLE_MARS, departure_range, arrival_range, (25, 25));
+		let (minimum, i, j) = grid.iter().enumerate()
+			.flat_map(|(i, row)| row.iter().enumerate().map(move |(j, delta_v)| (*delta_v, i, j)))
+			.fold((f64::INFINITY, 0, 0), |best, cell| if cell.0 < best.0 { cell } else { best });
+		assert!((1..24).contains(&i) && (1..24).contains(&j), "Expected the cheapest transfer inside the grid, found it at ({}, {})", i, j);
+		assert!((5000.0..6000.0).contains(&minimum), "Expected the cheapest transfer between 5000 and 6000 m/s, got {:.0} m/s", minimum);
+		// arriving before departing is impossible
+		let grid = database.porkchop_grid(&HANDLE_EARTH, &HANDLE_MARS, (10.0 * day, 20.0 * day), (0.0, 5.0 * day), (2, 2));
+		assert!(grid.iter().flatten().all(|delta_v| delta_v.is_infinite()));
+	}
+
+	#[test]
+	fn orbit_point_iter() {
+		let database = Database::<u16, f64>::default().with_solar_system().with_coordinate_convention(CoordinateConvention::ZUp);
+		let segments = 64;
+		for handle in [HANDLE_MERCURY, HANDLE_LUNA, HANDLE_TRITON] {
+			let points: Vec<Vector3<f64>> = database.orbit_point_iter(&handle, segments).collect();
+			assert_eq!(segments + 1, points.len());
+			let step = std::f64::consts::TAU / segments as f64;
+			for (i, point) in points.iter().enumerate() {
+				let expected = database.position_at_mean_anomaly(&handle, step * i as f64);
+				assert_ulps_eq!(expected, *point, epsilon=1e-6 * expected.norm());
+			}
+			assert_ulps_eq!(points[0], points[segments], epsilon=1e-6 * points[0].norm());
+		}
+		assert_eq!(0, database.orbit_point_iter(&HANDLE_SOL, segments).count());
+	}
+
+	#[test]
+	fn apparent_brightness() {
+		let mut database = Database::<u16, f64>::default();
+		database.add_sol();
+		let body = Body::new(1e20, 500.0, 500.0, 0.0);
+		let orbit = |distance_au: f64| OrbitalElements::default().with_semimajor_axis_au(distance_au);
+		// both observers are on the far side of the sun from the target, seeing it fully lit
+		database.add_entry(1000, DatabaseEntry::new(body.clone(), "Target").with_parent(HANDLE_SOL, orbit(5.0)));
+		database.add_entry(1001, DatabaseEntry::new(body.clone(), "Near").with_parent(HANDLE_SOL, orbit(1.0)).with_mean_anomaly_deg(180.0));
+		database.add_entry(1002, DatabaseEntry::new(body.clone(), "Far").with_parent(HANDLE_SOL, orbit(7.0)).with_mean_anomaly_deg(180.0));
+		let near = database.apparent_brightness(&1001, &1000, &HANDLE_SOL, 0.0).unwrap();
+		let far = database.apparent_brightness(&1002, &1000, &HANDLE_SOL, 0.0).unwrap();
+		assert!(near > 0.0);
+		assert_ulps_eq!(4.0, near / far, epsilon=1e-6);
+		// a body seen from its night side reflects nothing
+		database.add_entry(1003, DatabaseEntry::new(body, "Outer").with_parent(HANDLE_SOL, orbit(9.0)));
+		assert_ulps_eq!(0.0, database.apparent_brightness(&1003, &1000, &HANDLE_SOL, 0.0).unwrap() / near, epsilon=1e-9);
+		assert!(database.apparent_brightness(&1001, &9999, &HANDLE_SOL, 0.0).is_none());
+	}
+
+	#[test]
+	fn multi_hop_transfer_dv() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let day = 86_400.0;
+		let waypoints = [HANDLE_EARTH, HANDLE_MARS, HANDLE_JUPITER];
+		// published heliocentric Hohmann costs are about 5.6 km/s from Earth to Mars and 10.2 km/s
+		// from Mars to Jupiter
+		let published = 5_600.0 + 10_200.0;
+		let hohmann = database.multi_hop_transfer_dv(&waypoints, &[]);
+		assert_ulps_eq!(published, hohmann, epsilon=0.2 * published);
+		// leaving Earth 10 days before epoch and heading straight on to Jupiter on arriving at Mars
+		// costs a little more than two separate Hohmann transfers, which wait for the best phasing
+		let times = [-10.0 * day, 325.0 * day, 1425.0 * day];
+		let lambert = database.multi_hop_transfer_dv(&waypoints, &times);
+		assert_ulps_eq!(published, lambert, epsilon=0.2 * published);
+		// bodies around different parents can't be reached this way
+		assert!(database.multi_hop_transfer_dv(&[HANDLE_EARTH, HANDLE_IO], &[]).is_infinite());
+		assert_eq!(0.0, database.multi_hop_transfer_dv(&[HANDLE_EARTH], &[0.0]));
+	}
+
+	#[test]
+	fn orbit_intersections() {
+		let mut database = Database::<u16, f64>::default();
+		database.add_sol();
+		let asteroid = Body::new(1e15, 5.0, 5.0, 0.0);
+		let circular = OrbitalElements::default().with_semimajor_axis_au(1.0);
+		let crossing = OrbitalElements::default().with_semimajor_axis_au(1.5).with_eccentricity(0.5);
+		// both bodies reach the first crossing one year after epoch
+		let day = 86_400.0;
+		let encounter_time = 365.0 * day;
+		database.add_entry(1000, DatabaseEntry::new(asteroid.clone(), "Circular").with_parent(HANDLE_SOL, circular));
+		database.add_entry(1001, DatabaseEntry::new(asteroid.clone(), "Crossing").with_parent(HANDLE_SOL, crossing));
+		let intersections = database.find_orbit_intersections(&1000, &1001);
+		assert_eq!(2, intersections.len());
+		for &(nu_a, nu_b) in &intersections {
+			let distance = (circular.position_at_true_anomaly(nu_a) - crossing.position_at_true_anomaly(nu_b)).norm();
+			assert!(distance < 1000.0, "Expected the orbits to cross, closest approach was {:.0} m", distance);
+		}
+		let (nu_a, nu_b) = intersections[0];
+		for (handle, orbit, true_anomaly) in [(1000, circular, nu_a), (1001, crossing, nu_b)] {
+			let n = database.orbital_period_s(&handle).map(|period| std::f64::consts::TAU / period).unwrap();
+			database.bodies.get_mut(&handle).unwrap().mean_anomaly_at_epoch = orbit.mean_anomaly_at_true_anomaly(true_anomaly) - n * encounter_time;
+		}
+		let time = database.time_to_intersection(&1000, &1001, 0.0).unwrap();
+		assert!((time - encounter_time).abs() < 1.0, "Expected an encounter after {:.1} days, got {:.1} days", encounter_time / day, time / day);
+		assert!(database.time_to_intersection(&1000, &1001, encounter_time + day).is_none_or(|time| time > encounter_time + day));
+		// orbits that never come close don't intersect
+		database.add_entry(1002, DatabaseEntry::new(asteroid, "Distant").with_parent(HANDLE_SOL, circular.with_semimajor_axis_au(5.0)));
+		assert!(database.find_orbit_intersections(&1000, &1002).is_empty());
+		assert!(database.time_to_intersection(&1000, &1002, 0.0).is_none());
+	}
+
+	#[test]
+	fn gravity_at_point() {
+		let mut database = Database::<u16, f64>::default();
+		database.add_sol();
+		let sun = database.get_entry(&HANDLE_SOL).info.clone();
+		let distance = constants::f64::CONVERT_AU_TO_M;
+		let point = Vector3::new(distance, 0.0, 0.0);
+		let gravity = database.gravity_at_point(point, 0.0, None);
+		assert_ulps_eq!(-sun.gravity_at_distance(distance), gravity.x, epsilon=1e-12);
+		assert_eq!(0.0, gravity.y);
+		assert_eq!(0.0, gravity.z);
+		let softened = database.gravity_at_point(point, 0.0, Some(1000.0));
+		assert_ulps_eq!(gravity.x, softened.x, epsilon=1e-12);
+		let center = Vector3::new(0.0, 0.0, 0.0);
+		assert!(database.gravity_at_point(center, 0.0, None).x.is_nan());
+		assert_eq!(center, database.gravity_at_point(center, 0.0, Some(1000.0)));
+		// at r = ε the Plummer pull is GM/(2√2·ε²), rather than GM/2ε² or the unsoftened GM/ε²
+		let softening = 1e9;
+		let softened = database.gravity_at_point(Vector3::new(softening, 0.0, 0.0), 0.0, Some(softening));
+		assert_ulps_eq!(-sun.gm() / (2.0 * 2.0f64.sqrt() * softening * softening), softened.x, epsilon=1e-15);
+	}
+
+	#[test]
+	fn gravitational_wave_power() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let earth_sun = database.gravitational_wave_power_w(&HANDLE_SOL, &HANDLE_EARTH, 0.0);
+		assert!((190.0..210.0).contains(&earth_sun), "Expected about 200 W from the Earth and Sun, got {:.1} W", earth_sun);
+		assert_ulps_eq!(earth_sun, database.gravitational_wave_power_w(&HANDLE_EARTH, &HANDLE_SOL, 1e9));
+		// the moon is far lighter and its orbit is far smaller, only a few microwatts
+		let earth_moon = database.gravitational_wave_power_w(&HANDLE_EARTH, &HANDLE_LUNA, 0.0);
+		assert!((1e-6..1e-5).contains(&earth_moon), "Expected a few microwatts from the Earth and Moon, got {:e} W", earth_moon);
+		assert_eq!(0.0, database.gravitational_wave_power_w(&HANDLE_LUNA, &HANDLE_MARS, 0.0));
+		// c⁵ and a⁵ overflow f32, but the result itself fits easily
+		let database_f32 = Database::<u16, f32>::default().with_solar_system();
+		assert_ulps_eq!(earth_sun as f32, database_f32.gravitational_wave_power_w(&HANDLE_SOL, &HANDLE_EARTH, 0.0), epsilon=earth_sun as f32 * 1e-3);
+	}
+
+	#[test]
+	fn line_of_sight() {
+		let mut database = Database::<u16, f64>::default();
+		database.add_sol();
+		database.add_earth();
+		let time = 1_000_000.0;
+		let earth = database.absolute_position_at_time(&HANDLE_EARTH, time);
+		let luna = database.absolute_position_at_time(&HANDLE_LUNA, time);
+		let earth_radius = database.get_entry(&HANDLE_EARTH).info.radius_equator_m();
+		let luna_radius = database.get_entry(&HANDLE_LUNA).info.radius_equator_m();
+		let to_luna = (luna - earth).normalize();
+		let observer = earth + to_luna * earth_radius;
+		let target = luna - to_luna * luna_radius;
+		assert!(database.can_see(observer, target, time));
+		assert_eq!(None, database.los_blocking_body(observer, target, time));
+		// the far side of the earth can't see the moon
+		let far_side = earth - to_luna * earth_radius;
+		assert_eq!(Some(HANDLE_EARTH), database.los_blocking_body(far_side, target, time));
+		// with the sun between the two points, it blocks the view
+		let beyond_sun = -earth;
+		assert!(!database.can_see(observer, beyond_sun, time));
+		assert_eq!(Some(HANDLE_SOL), database.los_blocking_body(observer, beyond_sun, time));
+		// the earth is closer to the observer than the sun
+		assert_eq!(Some(HANDLE_EARTH), database.los_blocking_body(far_side, beyond_sun, time));
+	}
+
+	#[test]
+	fn position_at_mean_anomaly_orientation() {
+		// the classical perifocal to ecliptic rotation, with ecliptic Z up mapped onto +Y and the
+		// ecliptic Y axis onto -Z so orbits still run counter-clockwise seen from above
+		fn textbook_position(orbit: &OrbitalElements<f64>, true_anomaly: f64) -> Vector3<f64> {
+			let radius = orbit.semimajor_axis * (1.0 - orbit.eccentricity.powi(2)) / (1.0 + orbit.eccentricity * true_anomaly.cos());
+			let (node, inclination, latitude) = (orbit.long_of_ascending_node, orbit.inclination, orbit.arg_of_periapsis + true_anomaly);
+			let x = radius * (node.cos() * latitude.cos() - node.sin() * latitude.sin() * inclination.cos());
+			let y = radius * (node.sin() * latitude.cos() + node.cos() * latitude.sin() * inclination.cos());
+			let z = radius * latitude.sin() * inclination.sin();
+			Vector3::new(x, z, -y)
+		}
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let tilted = OrbitalElements::from_degrees_km(200_000_000.0, 0.3, 60.0, 250.0, 130.0, 0.0);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "Tilted").with_parent(HANDLE_SOL, tilted));
+		for handle in [HANDLE_EARTH, HANDLE_MARS, HANDLE_PLUTO, 1000] {
+			let orbit = database.get_entry(&handle).orbit.unwrap();
+			for mean_anomaly in [0.0, 1.0, 2.5, 4.0, 5.5] {
+				let expected = textbook_position(&orbit, orbit.true_anomaly_at_mean_anomaly(mean_anomaly));
+				let position = database.position_at_mean_anomaly(&handle, mean_anomaly);
+				assert_ulps_eq!(expected, position, epsilon=expected.norm() * 1e-12);
+			}
+		}
+		// crossing the ascending node heads north at the longitude of the ascending node
+		let node_anomaly = tilted.mean_anomaly_at_true_anomaly(std::f64::consts::TAU - tilted.arg_of_periapsis);
+		let at_node = database.position_at_mean_anomaly(&1000, node_anomaly);
+		assert_ulps_eq!(0.0, at_node.y, epsilon=at_node.norm() * 1e-12);
+		assert_ulps_eq!(130.0, Float::atan2(-at_node.z, at_node.x).to_degrees(), epsilon=1e-9);
+		assert!(database.position_at_mean_anomaly(&1000, node_anomaly + 0.01).y > 0.0);
+	}
+
+	#[test]
+	fn position_at_mean_anomaly_solves_kepler() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let comet = OrbitalElements::default().with_semimajor_axis_au(17.8).with_eccentricity(0.967);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "Comet").with_parent(HANDLE_SOL, comet));
+		for handle in [HANDLE_MERCURY, HANDLE_PLUTO, 1000] {
+			let orbit = database.get_entry(&handle).orbit.unwrap();
+			let e = orbit.eccentricity;
+			for mean_anomaly in [0.3, 1.0, 2.0, 3.0, 4.5, 6.0] {
+				// solve E - e·sin(E) = M by bisection, independently of the Newton solver
+				let (mut low, mut high) = (0.0, std::f64::consts::TAU);
+				for _ in 0..100 {
+					let middle = (low + high) / 2.0;
+					if middle - e * middle.sin() < mean_anomaly { low = middle } else { high = middle }
+				}
+				let expected_radius = orbit.semimajor_axis * (1.0 - e * low.cos());
+				let radius = database.position_at_mean_anomaly(&handle, mean_anomaly).norm();
+				assert_ulps_eq!(expected_radius, radius, epsilon=expected_radius * 1e-9);
+			}
+		}
+		// the old series M + 2e·sin(M) + 5/4·e²·sin(2M) put this comet tens of degrees off
+		let mean_anomaly = 0.3;
+		let series = mean_anomaly + 2.0 * 0.967 * f64::sin(mean_anomaly) + 1.25 * 0.967 * 0.967 * f64::sin(2.0 * mean_anomaly);
+		assert!((series - comet.true_anomaly_at_mean_anomaly(mean_anomaly)).abs().to_degrees() > 10.0);
+	}
+
+	#[test]
+	fn add_body_with_period() {
+		let mut database = Database::<u16, f64>::default();
+		database.add_sol();
+		database.add_earth();
+		let satellite = |parent: u16| DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Geostationary")
+			.with_parent(parent, OrbitalElements::default());
+		let sidereal_day = database.get_entry(&HANDLE_EARTH).info.rotation_period_s().unwrap();
+		assert_eq!(Ok(()), database.add_body_with_period(1000, satellite(HANDLE_EARTH), sidereal_day));
+		let semimajor_axis_km = database.get_entry(&1000).orbit.unwrap().semimajor_axis_km();
+		assert_ulps_eq!(42_164.0, semimajor_axis_km, epsilon=5.0);
+		assert_ulps_eq!(sidereal_day, database.orbital_period_s(&1000).unwrap(), epsilon=1e-6);
+		let orphan = DatabaseEntry::new(Body::default(), "Orphan");
+		assert_eq!(Err(OrbitError::NoOrbit(1001)), database.add_body_with_period(1001, orphan, sidereal_day));
+		assert_eq!(Err(OrbitError::UnknownBody(HANDLE_MARS)), database.add_body_with_period(1002, satellite(HANDLE_MARS), sidereal_day));
+	}
+
+	#[test]
+	fn has_line_of_sight() {
+		let mut database = Database::<u16, f64>::default();
+		database.add_sol();
+		database.add_earth();
+		let satellite = Body::default().with_mass_kg(1000.0).with_radius_km(0.01);
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(7000.0);
+		database.add_entry(1000, DatabaseEntry::new(satellite.clone(), "Near side").with_parent(HANDLE_EARTH, orbit));
+		database.add_entry(1001, DatabaseEntry::new(satellite.clone(), "Far side").with_parent(HANDLE_EARTH, orbit).with_mean_anomaly_deg(180.0));
+		database.add_entry(1002, DatabaseEntry::new(satellite, "Nearby").with_parent(HANDLE_EARTH, orbit).with_mean_anomaly_deg(30.0));
+		let time = 0.0;
+		assert!(!database.has_line_of_sight(&1000, &1001, time));
+		assert!(database.has_line_of_sight(&1000, &1002, time));
+		// a satellite isn't hidden by the planet it orbits
+		assert!(database.has_line_of_sight(&1000, &HANDLE_EARTH, time));
+	}
+
+	#[test]
+	fn line_of_sight_ellipsoid() {
+		let mut database = Database::<u16, f64>::default();
+		database.add_sol();
+		let flattened = Body::new(1e26, 60_000.0, 30_000.0, 0.0);
+		database.add_entry(1000, DatabaseEntry::new(flattened, "Flattened").with_parent(HANDLE_SOL, OrbitalElements::default().with_semimajor_axis_au(5.0)));
+		let center = database.absolute_position_at_time(&1000, 0.0);
+		let up = database.equatorial_normal(&1000, 0.0);
+		let side = up.cross(&center).normalize();
+		// a line passing over the pole inside the equatorial radius but outside the polar radius
+		let offset = up * 45_000_000.0;
+		assert!(database.can_see(center + offset - side * 1e9, center + offset + side * 1e9, 0.0));
+		let offset = side.cross(&up) * 45_000_000.0;
+		assert!(!database.can_see(center + offset - side * 1e9, center + offset + side * 1e9, 0.0));
+	}
+
+	#[test]
+	fn lagrange_points() {
+		let mut database = Database::<u16, f64>::default();
+		database.add_sol();
+		database.add_earth();
+		database.add_lagrange_point(1001, &HANDLE_SOL, &HANDLE_EARTH, LagrangePoint::L1, "Sun-Earth L1");
+		database.add_lagrange_point(1002, &HANDLE_SOL, &HANDLE_EARTH, LagrangePoint::L2, "Sun-Earth L2");
+		database.add_lagrange_point(1003, &HANDLE_SOL, &HANDLE_EARTH, LagrangePoint::L3, "Sun-Earth L3");
+		database.add_lagrange_point(1004, &HANDLE_SOL, &HANDLE_EARTH, LagrangePoint::L4, "Sun-Earth L4");
+		database.add_lagrange_point(1005, &HANDLE_SOL, &HANDLE_EARTH, LagrangePoint::L5, "Sun-Earth L5");
+		let earth_period = database.orbital_period_s(&HANDLE_EARTH).unwrap();
+		for time in [0.0, 1e7, 2e7] {
+			let earth = database.position_at_time(&HANDLE_EARTH, time);
+			let l1_distance_km = (database.position_at_time(&1001, time) - earth).norm() / 1000.0;
+			let l2_distance_km = (database.position_at_time(&1002, time) - earth).norm() / 1000.0;
+			assert!((1_450_000.0..1_550_000.0).contains(&l1_distance_km), "Expected L1 about 1.5 million km from earth, got {:.0} km", l1_distance_km);
+			assert!((1_450_000.0..1_550_000.0).contains(&l2_distance_km), "Expected L2 about 1.5 million km from earth, got {:.0} km", l2_distance_km);
+			// L1 and L2 stay on the sun-earth line, L3 opposite the earth
+			let earth_dir = earth.normalize();
+			assert!(database.position_at_time(&1001, time).normalize().dot(&earth_dir) > 1.0 - 1e-12);
+			assert!(database.position_at_time(&1002, time).norm() > earth.norm());
+			assert!(database.position_at_time(&1003, time).normalize().dot(&earth_dir) < -1.0 + 1e-12);
+			for handle in [1004, 1005] {
+				let point = database.position_at_time(&handle, time);
+				assert_ulps_eq!(earth.norm(), point.norm(), epsilon=1.0);
+				assert_ulps_eq!(earth.norm(), (point - earth).norm(), epsilon=1.0);
+			}
+		}
+		for handle in 1001..=1005 {
+			assert_ulps_eq!(earth_period, database.orbital_period_s(&handle).unwrap(), epsilon=1e-6);
+			assert_eq!(0.0, database.get_entry(&handle).info.mass_kg());
+			assert_eq!(0.0, database.get_entry(&handle).info.radius_equator_m());
+		}
+		assert_eq!(Some(database.position_at_time(&1002, 1e7)), database.lagrange_point_position(&HANDLE_SOL, &HANDLE_EARTH, LagrangePoint::L2, 1e7));
+		assert_eq!(None, database.lagrange_point_position(&HANDLE_EARTH, &HANDLE_SOL, LagrangePoint::L2, 1e7));
+		// L4 leads the earth along its orbit
+		let (earth, earth_velocity) = database.state_vector_at_time(&HANDLE_EARTH, 0.0).unwrap();
+		assert!((database.position_at_time(&1004, 0.0) - earth).dot(&earth_velocity) > 0.0);
+		assert!((database.position_at_time(&1005, 0.0) - earth).dot(&earth_velocity) < 0.0);
+	}
+
+	#[test]
+	fn eccentric_anomaly_position() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let orbit = database.get_entry(&HANDLE_MERCURY).orbit.unwrap();
+		let a = orbit.semimajor_axis;
+		let b = a * (1.0 - orbit.eccentricity.powi(2)).sqrt();
+		let periapsis = database.position_at_mean_anomaly(&HANDLE_MERCURY, 0.0);
+		let dir_periapsis = periapsis.normalize();
+		let center = dir_periapsis * (-a * orbit.eccentricity);
+		for time in [0.0, 1e6, 3e6, 5e6] {
+			let point = database.eccentric_anomaly_position(&HANDLE_MERCURY, time).unwrap();
+			assert_ulps_eq!(a, (point - center).norm(), epsilon=a * 1e-12);
+			// squashing the circle onto the ellipse gives the body's position
+			let position = database.position_at_time(&HANDLE_MERCURY, time) - center;
+			let circle = point - center;
+			assert_ulps_eq!(circle.dot(&dir_periapsis), position.dot(&dir_periapsis), epsilon=a * 1e-9);
+			let minor_position = position - dir_periapsis * position.dot(&dir_periapsis);
+			let minor_circle = circle - dir_periapsis * circle.dot(&dir_periapsis);
+			assert_ulps_eq!(minor_circle.norm() * b / a, minor_position.norm(), epsilon=a * 1e-9);
+		}
+		assert!(database.eccentric_anomaly_position(&HANDLE_SOL, 0.0).is_none());
+	}
+
+	#[test]
+	fn earth_moon_l1() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		for time in [0.0, 1e6] {
+			let luna = database.position_at_time(&HANDLE_LUNA, time);
+			let l1 = database.lagrange_point_position(&HANDLE_EARTH, &HANDLE_LUNA, LagrangePoint::L1, time).unwrap();
+			// the exact distance is about 326,000 km at the moon's mean distance, the Hill sphere
+			// approximation puts it a few thousand km closer to the earth
+			let distance_km = l1.norm() / luna.norm() * database.get_entry(&HANDLE_LUNA).orbit.unwrap().semimajor_axis / 1000.0;
+			assert!((320_000.0..330_000.0).contains(&distance_km), "Expected L1 about 326,000 km from earth, got {:.0} km", distance_km);
+			assert!(l1.normalize().dot(&luna.normalize()) > 1.0 - 1e-12);
+			assert!(l1.norm() < luna.norm());
+		}
+	}
+
+	#[test]
+	fn position_at_time_plus_anomaly() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		for handle in [HANDLE_EARTH, HANDLE_LUNA, HANDLE_PHOBOS] {
+			for time in [0.0, 1e5, 1e7] {
+				assert_eq!(database.position_at_time(&handle, time), database.position_at_time_plus_anomaly(&handle, time, 0.0));
+			}
+			let period = database.orbital_period_s(&handle).unwrap();
+			let half_orbit_later = database.position_at_time(&handle, 1e5 + period / 2.0);
+			let half_orbit_offset = database.position_at_time_plus_anomaly(&handle, 1e5, std::f64::consts::PI);
+			assert!((half_orbit_later - half_orbit_offset).norm() < half_orbit_later.norm() * 1e-9);
+		}
+	}
+
+	#[test]
+	fn orbital_elements_at_epoch() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		// JPL's rates for the Earth-Moon barycenter, per century
+		let drift = SecularDrift {
+			da_per_year: 0.00000562 * constants::f64::CONVERT_AU_TO_M / 100.0,
+			de_per_year: -0.00004392 / 100.0,
+			di_per_year: -0.01294668 * constants::f64::CONVERT_DEG_TO_RAD / 100.0,
+		};
+		let entry = database.bodies.remove(&HANDLE_EARTH).unwrap().with_secular_drift(drift);
+		database.add_entry(HANDLE_EARTH, entry);
+		let now = database.orbital_elements_at_epoch(&HANDLE_EARTH, 0.0);
+		let later = database.orbital_elements_at_epoch(&HANDLE_EARTH, 100.0);
+		assert_ulps_eq!(0.0167086, now.eccentricity);
+		assert!((0.01666..0.01668).contains(&later.eccentricity), "Expected an eccentricity around 0.01667 after a century, got {:.6}", later.eccentricity);
+		assert!(later.inclination < now.inclination);
+		assert_ulps_eq!(now.semimajor_axis + 0.00000562 * constants::f64::CONVERT_AU_TO_M, later.semimajor_axis, epsilon=1.0);
+		// the sun has no J2 set, so the orientation of the orbit doesn't change
+		assert_eq!(now.long_of_ascending_node, later.long_of_ascending_node);
+		// the ISS's orbital plane regresses about 5° a day from earth's equatorial bulge
+		let iss = OrbitalElements::default().with_semimajor_axis_km(6_778.0).with_inclination_deg(51.6);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "ISS").with_parent(HANDLE_EARTH, iss));
+		let one_day = database.orbital_elements_at_epoch(&1000, 1.0 / 365.25);
+		let regression_deg = (one_day.long_of_ascending_node - std::f64::consts::TAU) * constants::f64::CONVERT_RAD_TO_DEG;
+		assert!((-5.2..-4.8).contains(&regression_deg), "Expected the node to regress about 5° a day, got {:.2}°", regression_deg);
+		assert_eq!(0.0, database.orbital_elements_at_epoch(&HANDLE_SOL, 100.0).semimajor_axis);
+	}
+
+	#[test]
+	fn reference_plane() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		assert_eq!(ReferencePlane::Ecliptic, database.reference_plane());
+		let normal = database.invariable_plane_normal();
+		assert_ulps_eq!(1.58, normal.y.acos().to_degrees(), epsilon=0.1);
+		let ecliptic_position = database.position_at_time(&HANDLE_JUPITER, 1e7);
+		database.set_reference_plane(ReferencePlane::Invariable);
+		assert_eq!(ReferencePlane::Invariable, database.reference_plane());
+		assert_ulps_eq!(Vector3::y(), database.invariable_plane_normal(), epsilon=1e-12);
+		// positions are rotated but keep their distances
+		let invariable_position = database.position_at_time(&HANDLE_JUPITER, 1e7);
+		assert_ulps_eq!(ecliptic_position.norm(), invariable_position.norm(), epsilon=1.0);
+		assert_ulps_eq!(normal.dot(&ecliptic_position), invariable_position.y, epsilon=1.0);
+		// and vectors passed in are rotated back into the ecliptic
+		assert_ulps_eq!(ecliptic_position, database.remove_convention(invariable_position), epsilon=1.0);
+		database.set_reference_plane(ReferencePlane::Ecliptic);
+		assert_eq!(ecliptic_position, database.position_at_time(&HANDLE_JUPITER, 1e7));
+	}
+
+	#[test]
+	fn coordinate_convention() {
+		let mut database = Database::<u16, f64>::default();
+		assert_eq!(CoordinateConvention::YUp, database.coordinate_convention());
+		database.add_sol();
+		let orbit = OrbitalElements::default().with_semimajor_axis_au(1.0).with_eccentricity(0.1);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "Equatorial").with_parent(HANDLE_SOL, orbit));
+		let y_up: Vec<Vector3<f64>> = [0.0, 1e6, 1e7].iter().map(|time| database.position_at_time(&1000, *time)).collect();
+		assert!(y_up.iter().all(|position| position.y == 0.0));
+		database.set_coordinate_convention(CoordinateConvention::ZUp);
+		for (i, time) in [0.0, 1e6, 1e7].iter().enumerate() {
+			let (position, velocity) = database.state_vector_at_time(&1000, *time).unwrap();
+			assert_eq!(0.0, position.z);
+			assert_eq!(position, database.position_at_time(&1000, *time));
+			assert_eq!(y_up[i].norm(), position.norm());
+			// prograde orbits still circle counter-clockwise around the up axis
+			assert!(position.cross(&velocity).z > 0.0);
+		}
+		let earth_database = Database::<u16, f64>::default().with_solar_system().with_coordinate_convention(CoordinateConvention::ZUp);
+		let normal = earth_database.equatorial_normal(&HANDLE_EARTH, 0.0);
+		assert!(normal.z > 0.9);
+		assert_eq!(0.0, normal.x);
+	}
+
+	#[test]
+	fn apply_impulsive_burn() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let earth = database.get_entry(&HANDLE_EARTH).info.clone();
+		let (r_low, r_high) = (6_678_000.0, 42_164_000.0);
+		let orbit = OrbitalElements::default().with_semimajor_axis_m(r_low).with_inclination_deg(28.5);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "Satellite").with_parent(HANDLE_EARTH, orbit));
+		let time = 1_000.0;
+		let (position, velocity) = database.state_vector_at_time(&1000, time).unwrap();
+		// first burn of a hohmann transfer up to geostationary orbit
+		let delta_v = (earth.gm() / r_low).sqrt() * ((2.0 * r_high / (r_low + r_high)).sqrt() - 1.0);
+		database.apply_impulsive_burn(&1000, velocity.normalize() * delta_v, time).unwrap();
+		let new_orbit = database.get_entry(&1000).orbit.unwrap();
+		let apoapsis = new_orbit.semimajor_axis * (1.0 + new_orbit.eccentricity);
+		assert_ulps_eq!(r_high, apoapsis, epsilon=1.0);
+		assert_ulps_eq!(28.5, new_orbit.inclination * constants::f64::CONVERT_RAD_TO_DEG, epsilon=1e-9);
+		// the body carries on from where it was
+		let (new_position, new_velocity) = database.state_vector_at_time(&1000, time).unwrap();
+		assert!((new_position - position).norm() < 1e-3);
+		assert_ulps_eq!(velocity.norm() + delta_v, new_velocity.norm(), epsilon=1e-6);
+		let half_transfer = std::f64::consts::PI * (new_orbit.semimajor_axis.powi(3) / earth.gm()).sqrt();
+		assert_ulps_eq!(r_high, database.orbital_radius_at_time(&1000, time + half_transfer).unwrap(), epsilon=1.0);
+		// slowing down enough to hit the earth is rejected
+		let (_, velocity) = database.state_vector_at_time(&1000, time).unwrap();
+		let result = database.apply_impulsive_burn(&1000, -velocity * 0.5, time);
+		assert_eq!(Err(OrbitError::OrbitIntersectsParentBody(HANDLE_EARTH)), result);
+		assert_eq!(new_orbit.eccentricity, database.get_entry(&1000).orbit.unwrap().eccentricity);
+		assert_eq!(Err(OrbitError::NoOrbit(HANDLE_SOL)), database.apply_impulsive_burn(&HANDLE_SOL, velocity, time));
+		assert_eq!(Err(OrbitError::UnknownBody(u16::MAX)), database.apply_impulsive_burn(&u16::MAX, velocity, time));
+	}
+
+	#[test]
+	fn insert_probe_hyperbolic() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let sun_gm = database.get_entry(&HANDLE_SOL).gm();
+		let (c3, periapsis, time_of_periapsis) = (25.0, 1.2e11, 1_000_000.0);
+		database.insert_probe_hyperbolic(1000, &HANDLE_SOL, "Probe", c3, 45.0, 20.0, periapsis, time_of_periapsis);
+		let orbit = database.get_entry(&1000).orbit.unwrap();
+		assert_ulps_eq!(-sun_gm / 25e6, orbit.semimajor_axis, max_ulps=1000);
+		assert_ulps_eq!(1.0 + periapsis / orbit.semimajor_axis.abs(), orbit.eccentricity, epsilon=1e-9);
+		assert_ulps_eq!(0.0, database.mean_anomaly_at_time(&1000, time_of_periapsis), epsilon=1e-9);
+		assert_ulps_eq!(periapsis, database.position_at_mean_anomaly(&1000, 0.0).norm(), epsilon=1.0);
+		// long after the flyby the probe is heading out along the asymptote with the same energy
+		let (position, velocity) = database.state_vector_at_time(&1000, time_of_periapsis + 1e10).unwrap();
+		let (ra, dec) = (45f64.to_radians(), 20f64.to_radians());
+		let asymptote = Vector3::new(dec.cos() * ra.cos(), dec.sin(), -dec.cos() * ra.sin());
+		assert!(position.normalize().dot(&asymptote) > 0.999);
+		assert_ulps_eq!(c3 * 1e6 / 2.0, velocity.norm_squared() / 2.0 - sun_gm / position.norm(), epsilon=1.0);
+		// and the flyby is prograde
+		assert!(position.cross(&velocity).y > 0.0);
+	}
+
+	#[test]
+	fn orbits_cross() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		assert!(database.orbits_cross(&HANDLE_NEPTUNE, &HANDLE_PLUTO));
+		assert!(database.orbits_cross(&HANDLE_PLUTO, &HANDLE_NEPTUNE));
+		assert!(!database.orbits_cross(&HANDLE_EARTH, &HANDLE_JUPITER));
+		// different parents
+		assert!(!database.orbits_cross(&HANDLE_EARTH, &HANDLE_LUNA));
+		assert!(!database.orbits_cross(&HANDLE_SOL, &HANDLE_EARTH));
+	}
+
+	#[test]
+	fn libration_longitude() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let orbit = database.get_entry(&HANDLE_LUNA).orbit.unwrap();
+		let period = database.orbital_period_s(&HANDLE_LUNA).unwrap();
+		let n = std::f64::consts::TAU / period;
+		let epoch_mean_anomaly = database.get_entry(&HANDLE_LUNA).mean_anomaly_at_epoch;
+		// zero at periapsis and apoapsis
+		for mean_anomaly in [0.0, std::f64::consts::PI, std::f64::consts::TAU] {
+			let time = (mean_anomaly - epoch_mean_anomaly) / n;
+			assert_ulps_eq!(0.0, database.libration_longitude_at_time(&HANDLE_LUNA, time).unwrap(), epsilon=1e-9);
+		}
+		// peaks near the semi-latus rectum, at about 2e for a nearly circular orbit
+		let (peak_time, peak) = (0..1000)
+			.map(|step| -epoch_mean_anomaly / n + period * step as f64 / 1000.0)
+			.map(|time| (time, database.libration_longitude_at_time(&HANDLE_LUNA, time).unwrap()))
+			.fold((0.0, 0.0), |best, sample| if sample.1 > best.1 { sample } else { best });
+		let peak_true_anomaly = orbit.true_anomaly_at_mean_anomaly(database.mean_anomaly_at_time(&HANDLE_LUNA, peak_time));
+		assert!((peak_true_anomaly.to_degrees() - 90.0).abs() < 5.0);
+		assert_ulps_eq!(2.0 * orbit.eccentricity, peak, epsilon=0.01);
+		assert_eq!(None, database.libration_longitude_at_time(&HANDLE_SOL, 0.0));
+	}
+
+	#[test]
+	fn retrograde() {
+		let database: Database<u16, f64> = Database::default().with_solar_system();
+		assert_eq!(Some(true), database.is_retrograde_orbit(&HANDLE_TRITON));
+		assert_eq!(Some(false), database.is_retrograde_orbit(&HANDLE_VENUS));
+		assert_eq!(Some(false), database.is_retrograde_orbit(&HANDLE_LUNA));
+		assert_eq!(None, database.is_retrograde_orbit(&HANDLE_SOL));
+		assert_eq!(Some(true), database.is_retrograde_rotation(&HANDLE_VENUS));
+		assert_eq!(Some(false), database.is_retrograde_rotation(&HANDLE_EARTH));
+		assert_eq!(Some(false), database.is_retrograde_rotation(&HANDLE_TRITON));
+		assert_eq!(None, database.is_retrograde_rotation(&9999));
+	}
+
+	#[test]
+	fn orbit_color_suggestion() {
+		let database: Database<u16, f64> = Database::default().with_solar_system();
+		let retrograde = database.orbit_color_suggestion(&HANDLE_TRITON);
+		let circular = database.orbit_color_suggestion(&HANDLE_VENUS);
+		let eccentric = database.orbit_color_suggestion(&HANDLE_NEREID);
+		let default = database.orbit_color_suggestion(&HANDLE_SOL);
+		assert!(retrograde.2 > retrograde.0 && retrograde.2 > retrograde.1, "Triton's retrograde orbit should be blue");
+		assert!(circular.1 > circular.0 && circular.1 > circular.2, "Venus's near circular orbit should be green");
+		assert!(eccentric.0 > eccentric.1 && eccentric.1 > eccentric.2, "Nereid's eccentric orbit should be orange");
+		// earth's eccentricity of 0.0167 is just above the near circular cutoff
+		assert_eq!(default, database.orbit_color_suggestion(&HANDLE_EARTH));
+		for color in [retrograde, circular, eccentric] {
+			assert_ne!(default, color);
+		}
+	}
+
+	#[test]
+	fn no_rotation_breakup() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let mut checked = 0;
+		for (_, entry) in database.iter() {
+			if let Some(rotation_period) = entry.info.rotation_period_s() {
+				assert!(!entry.info.rotation_breakup_check(rotation_period), "{} would fly apart", entry.name);
+				checked += 1;
+			}
+		}
+		assert!(checked >= 10);
+	}
+
+	#[test]
+	fn solar_day() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let day = 86_400.0;
+		assert_eq!(Some(86164.0905), database.get_entry(&HANDLE_EARTH).info.rotation_period_s());
+		assert_ulps_eq!(day, database.solar_day_s(&HANDLE_EARTH).unwrap(), epsilon=10.0);
+		assert_ulps_eq!(116.75 * day, database.solar_day_s(&HANDLE_VENUS).unwrap(), epsilon=0.1 * day);
+		// the moon's day is set by the earth's orbit around the sun, giving the synodic month
+		assert_ulps_eq!(29.53 * day, database.solar_day_s(&HANDLE_LUNA).unwrap(), epsilon=0.05 * day);
+		assert_eq!(None, database.solar_day_s(&HANDLE_SOL));
+		assert_eq!(None, database.solar_day_s(&HANDLE_PHOBOS));
+	}
+
+	#[test]
+	fn gravity_turn_trajectory() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let earth_gm = database.get_entry(&HANDLE_EARTH).gm();
+		let earth_radius = database.get_entry(&HANDLE_EARTH).info.radius_equator_m();
+		let target_altitude = 200_000.0;
+		let pitch_program = [(0.0, 90f64.to_radians()), (10.0, 80f64.to_radians()), (120.0, 0.0)];
+		let trajectory = database.gravity_turn_trajectory(&HANDLE_EARTH, target_altitude, 0.0, 30.0, &pitch_program, None);
+		assert_eq!((0.0, 0.0, 0.0), trajectory[0]);
+		assert!(trajectory.windows(2).all(|pair| pair[1].0 - pair[0].0 == 1.0));
+		let (_, altitude, speed) = *trajectory.last().unwrap();
+		assert_ulps_eq!(target_altitude, altitude, epsilon=1000.0);
+		let circular_speed = (earth_gm / (earth_radius + target_altitude)).sqrt();
+		assert_ulps_eq!(circular_speed, speed, epsilon=circular_speed * 0.1);
+		// going straight up without enough thrust falls back down
+		let trajectory = database.gravity_turn_trajectory(&HANDLE_EARTH, target_altitude, 100.0, 5.0, &[(0.0, 90f64.to_radians())], None);
+		assert!(trajectory.last().unwrap().1 < 0.0);
+		// the atmosphere slows the rocket down, but an empty one doesn't
+		let atmosphere = LaunchDrag{ drag_coefficient: 0.5, area_to_mass_m2_per_kg: 1e-4, surface_density_kg_m3: 1.225, scale_height_m: 8500.0 };
+		let vacuum = database.gravity_turn_trajectory(&HANDLE_EARTH, target_altitude, 0.0, 30.0, &pitch_program, None);
+		let dragged = database.gravity_turn_trajectory(&HANDLE_EARTH, target_altitude, 0.0, 30.0, &pitch_program, Some(atmosphere));
+		let empty = database.gravity_turn_trajectory(&HANDLE_EARTH, target_altitude, 0.0, 30.0, &pitch_program, Some(LaunchDrag{ surface_density_kg_m3: 0.0, ..atmosphere }));
+		assert_eq!(vacuum, empty);
+		assert!(dragged[60].2 < vacuum[60].2 - 10.0, "Expected drag to slow the rocket, got {} m/s vs {} m/s in vacuum", dragged[60].2, vacuum[60].2);
+		// a repeated time in the pitch program is a step change in pitch
+		let stepped = [(0.0, 90f64.to_radians()), (10.0, 90f64.to_radians()), (10.0, 45f64.to_radians()), (100.0, 45f64.to_radians())];
+		let trajectory = database.gravity_turn_trajectory(&HANDLE_EARTH, target_altitude, 0.0, 30.0, &stepped, None);
+		assert!(trajectory.iter().all(|(_, altitude, speed)| altitude.is_finite() && speed.is_finite()));
+		let vertical = database.gravity_turn_trajectory(&HANDLE_EARTH, target_altitude, 0.0, 30.0, &[(0.0, 90f64.to_radians())], None);
+		assert_eq!(trajectory[..=10], vertical[..=10]);
+		assert!(trajectory[11].1 < vertical[11].1);
+	}
+
+	#[test]
+	fn laplace_plane_normal() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let far_orbit = OrbitalElements::default().with_semimajor_axis_km(20_000_000.0);
+		database.add_entry(1000, DatabaseEntry::new(Body::default().with_mass_kg(1e18), "Far moon").with_parent(HANDLE_SATURN, far_orbit));
+		let equator_normal = database.equatorial_normal(&HANDLE_SATURN, 0.0);
+		let (position, velocity) = database.state_vector_at_time(&HANDLE_SATURN, 0.0).unwrap();
+		let orbit_normal = position.cross(&velocity).normalize();
+		let angle = |a: Vector3<f64>, b: Vector3<f64>| a.dot(&b).clamp(-1.0, 1.0).acos().to_degrees();
+		// mimas is deep inside saturn's laplace radius
+		let close = database.laplace_plane_normal(&HANDLE_MIMAS).unwrap();
+		assert!(angle(close, equator_normal) < 0.01);
+		let far = database.laplace_plane_normal(&1000).unwrap();
+		assert!(angle(far, orbit_normal) < 0.1);
+		// iapetus is close to the laplace radius, so its plane is somewhere in between
+		let middle = database.laplace_plane_normal(&HANDLE_IAPETUS).unwrap();
+		let obliquity = angle(equator_normal, orbit_normal);
+		assert!(angle(middle, equator_normal) > 1.0 && angle(middle, orbit_normal) > 1.0);
+		assert_ulps_eq!(obliquity, angle(middle, equator_normal) + angle(middle, orbit_normal), epsilon=1e-6);
+		assert_eq!(Some(database.equatorial_normal(&HANDLE_SOL, 0.0)), database.laplace_plane_normal(&HANDLE_EARTH));
+		assert_eq!(None, database.laplace_plane_normal(&HANDLE_SOL));
+	}
+
+	#[test]
+	fn spatial_index() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let au = constants::f64::CONVERT_AU_TO_M;
+		let time = 1e8;
+		let earth = database.absolute_position_at_time(&HANDLE_EARTH, time);
+		let within = database.bodies_within(earth, 0.5 * au, time);
+		let nearest = database.bodies_by_distance_from(earth, time, 5);
+		assert_eq!(HANDLE_EARTH, within[0].0);
+		assert_eq!(HANDLE_LUNA, within[1].0);
+		assert!(within.iter().all(|(_, distance)| *distance <= 0.5 * au));
+		assert_eq!(5, nearest.len());
+		// the indexed queries find exactly the same bodies
+		for cell_size in [0.01 * au, 0.5 * au, 100.0 * au] {
+			database.rebuild_spatial_index(time, cell_size);
+			assert_eq!(within, database.bodies_within(earth, 0.5 * au, time));
+			assert_eq!(nearest, database.bodies_by_distance_from(earth, time, 5));
+			assert_eq!(database.iter().count(), database.bodies_by_distance_from(earth, time, 1000).len());
+		}
+		// queries at other times don't use the stale index
+		let earth_later = database.absolute_position_at_time(&HANDLE_EARTH, 2.0 * time);
+		assert_eq!(HANDLE_EARTH, database.bodies_by_distance_from(earth_later, 2.0 * time, 1)[0].0);
+		// changing the database throws the index away instead of answering from stale positions
+		database.rebuild_spatial_index(time, 0.01 * au);
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(50_000.0);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "Probe").with_parent(HANDLE_EARTH, orbit));
+		assert!(database.bodies_within(earth, 0.5 * au, time).iter().any(|(handle, _)| *handle == 1000));
+		database.rebuild_spatial_index(time, 0.01 * au);
+		database.apply_impulsive_burn(&1000, Vector3::new(0.0, 0.0, 5000.0), time).unwrap();
+		let probe = database.absolute_position_at_time(&1000, time + 1e5);
+		database.rebuild_spatial_index(time + 1e5, 0.01 * au);
+		database.step(1.0);
+		let moved = database.absolute_position_at_time(&1000, time + 1e5);
+		assert_ne!(probe, moved);
+		assert_eq!(1000, database.bodies_by_distance_from(moved, time + 1e5, 1)[0].0);
+	}
+
+	#[test]
+	fn parent_position_relative_to() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let time = 1e7;
+		let sun = database.parent_position_relative_to(&HANDLE_MARS, &HANDLE_MARS, time).unwrap();
+		assert_ulps_eq!(-database.position_at_time(&HANDLE_MARS, time), sun, epsilon=1e-3);
+		assert_eq!(Some(Vector3::zeros()), database.parent_position_relative_to(&HANDLE_MARS, &HANDLE_SOL, time));
+		assert!(database.parent_position_relative_to(&HANDLE_SOL, &HANDLE_MARS, time).is_none());
+	}
+
+	#[test]
+	fn position_2d_at_time() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let orbit = OrbitalElements::default().with_semimajor_axis_au(2.5).with_eccentricity(0.2).with_arg_of_periapsis_deg(40.0);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "Asteroid").with_parent(HANDLE_SOL, orbit));
+		for time in [0.0, 1e7, 5e7] {
+			let (x, z) = database.position_2d_at_time(&HANDLE_SOL, &1000, time).unwrap();
+			let distance = database.relative_position(&HANDLE_SOL, &1000, time).unwrap().norm();
+			assert_ulps_eq!(distance, (x * x + z * z).sqrt(), epsilon=1e-3);
+		}
+		// inclined orbits lose their out of plane component
+		let position = database.relative_position(&HANDLE_EARTH, &HANDLE_MARS, 0.0).unwrap();
+		assert_eq!(Some((position.x, position.z)), database.position_2d_at_time(&HANDLE_EARTH, &HANDLE_MARS, 0.0));
+		database.set_coordinate_convention(CoordinateConvention::ZUp);
+		let position = database.relative_position(&HANDLE_EARTH, &HANDLE_MARS, 0.0).unwrap();
+		assert_eq!(Some((position.x, position.y)), database.position_2d_at_time(&HANDLE_EARTH, &HANDLE_MARS, 0.0));
+		assert_eq!(None, database.position_2d_at_time(&HANDLE_EARTH, &u16::MAX, 0.0));
+	}
+
+	#[test]
+	fn energy_components() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		for handle in [HANDLE_MERCURY, HANDLE_LUNA, HANDLE_PHOBOS] {
+			let energy = database.specific_orbital_energy(&handle).unwrap();
+			assert!(energy < 0.0);
+			let period = database.orbital_period_s(&handle).unwrap();
+			let (kinetic_start, _) = database.energy_components_at_time(&handle, 0.0).unwrap();
+			let mut kinetic_changed = false;
+			for step in 0..8 {
+				let (kinetic, potential) = database.energy_components_at_time(&handle, period * step as f64 / 8.0).unwrap();
+				assert!(kinetic > 0.0 && potential < 0.0);
+				assert_ulps_eq!(energy, kinetic + potential, epsilon=energy.abs() * 1e-9);
+				kinetic_changed |= (kinetic - kinetic_start).abs() > kinetic_start * 1e-3;
+			}
+			assert!(kinetic_changed, "Expected energy to move between kinetic and potential along an eccentric orbit");
+		}
+		assert!(database.energy_components_at_time(&HANDLE_SOL, 0.0).is_none());
+		assert!(database.specific_orbital_energy(&HANDLE_SOL).is_none());
+	}
+
+	#[cfg(debug_assertions)]
+	#[test]
+	fn orbital_table() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let table = database.orbital_table();
+		let lines: Vec<&str> = table.lines().collect();
+		assert_eq!(database.iter().filter(|(_, entry)| entry.orbit.is_some()).count() + 1, lines.len());
+		assert!(lines[0].starts_with("| Name"));
+		// sorted by handle, so mercury comes first
+		assert!(lines[1].starts_with("| Mercury"));
+		let earth = lines.iter().find(|line| line.starts_with("| Earth ")).unwrap();
+		assert!(earth.contains("| Sol "));
+		let a_au: f64 = earth.split('|').nth(3).unwrap().trim().trim_end_matches("AU").trim().parse().unwrap();
+		assert_ulps_eq!(1.0, a_au, epsilon=0.01);
+		database.print_orbital_table();
+	}
+
+	#[test]
+	fn export_position_table() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let year = 365.25 * 86_400.0;
+		let text = database.export_position_table(&[HANDLE_EARTH, HANDLE_LUNA, u16::MAX], 0.0, year, 10);
+		let blocks: Vec<&str> = text.split("\n\n").collect();
+		assert_eq!(2, blocks.len());
+		let mut lines = blocks[0].lines();
+		assert!(lines.next().unwrap().starts_with("# Earth"));
+		assert_eq!(Some("time_s,x_km,y_km,z_km"), lines.next());
+		let rows: Vec<Vec<f64>> = lines
+			.map(|line| line.split(',').map(|value| value.parse().unwrap()).collect())
+			.collect();
+		assert_eq!(10, rows.len());
+		assert_eq!(0.0, rows[0][0]);
+		assert_eq!(year, rows[9][0]);
+		for row in rows {
+			assert_eq!(4, row.len());
+			let distance_au = (row[1].powi(2) + row[2].powi(2) + row[3].powi(2)).sqrt() * constants::f64::CONVERT_KM_TO_AU;
+			assert!((0.98..1.02).contains(&distance_au), "Expected earth about 1 AU from the sun, got {:.4} AU", distance_au);
+		}
+		assert!(blocks[1].starts_with("# Luna"));
+		assert_eq!(12, blocks[1].lines().count());
+	}
+
+	#[test]
+	fn rescale() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let earth_period = database.orbital_period_s(&HANDLE_EARTH).unwrap();
+		let luna_period = database.orbital_period_s(&HANDLE_LUNA).unwrap();
+		let earth_radius = database.get_entry(&HANDLE_EARTH).info.radius_equator_km();
+		database.rescale(0.5, 1.0);
+		let expected_factor = 0.5_f64.powf(1.5);
+		assert_ulps_eq!(earth_period * expected_factor, database.orbital_period_s(&HANDLE_EARTH).unwrap(), epsilon=1e-3);
+		assert_ulps_eq!(luna_period * expected_factor, database.orbital_period_s(&HANDLE_LUNA).unwrap(), epsilon=1e-3);
+		assert_ulps_eq!(earth_radius * 0.5, database.get_entry(&HANDLE_EARTH).info.radius_equator_km());
+		// scaling mass by the cube of the distance factor keeps periods unchanged
+		database.rescale(2.0, 8.0);
+		assert_ulps_eq!(earth_period * expected_factor, database.orbital_period_s(&HANDLE_EARTH).unwrap(), epsilon=1e-3);
+	}
+
+	#[test]
+	fn find_by_name() {
+		let database = Database::<u16, f32>::default().with_solar_system();
+		let names = |results: Vec<(&u16, &DatabaseEntry<u16, f32>)>| -> Vec<String> {
+			results.iter().map(|(_, entry)| entry.name.clone()).collect()
+		};
+		let results = names(database.find_by_partial_name("io"));
+		assert!(results.contains(&String::from("Io")));
+		assert!(results.contains(&String::from("Dione")));
+		assert!(!results.contains(&String::from("Callisto")));
+		let mut sorted = results.clone();
+		sorted.sort();
+		assert_eq!(sorted, results);
+		assert_eq!(results, names(database.find_by_partial_name("IO")));
+		assert_eq!(vec![String::from("Callisto")], names(database.find_by_name_prefix("Cal")));
+		assert_eq!(vec![String::from("Callisto")], names(database.find_by_name_prefix("cAL")));
+		assert!(database.find_by_name_prefix("io").iter().all(|(handle, _)| **handle == HANDLE_IO));
+		assert!(database.find_by_partial_name("xyz").is_empty());
+	}
+
+	#[test]
+	fn unknown_handles() {
+		let database = Database::<u16, f32>::default().with_solar_system();
+		let unknown = 9999;
+		assert!(database.try_get_entry(&unknown).is_none());
+		assert!(database.try_get_entry(&HANDLE_EARTH).is_some());
+		assert!(database.relative_position(&unknown, &HANDLE_EARTH, 0.0).is_none());
+		assert!(database.relative_position(&HANDLE_EARTH, &unknown, 0.0).is_none());
+		assert_eq!(Err(RelativePositionError::UnknownHandle(unknown)), database.try_relative_position(&unknown, &HANDLE_EARTH, 0.0));
+		assert_eq!(Err(RelativePositionError::UnknownHandle(unknown)), database.try_relative_position(&HANDLE_EARTH, &unknown, 0.0));
+	}
+
+	#[test]
+	fn relative_position_different_systems() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		database.add_entry(1000, DatabaseEntry::new(Body::new_sol(), "Rogue Star"));
+		database.add_entry(1001, DatabaseEntry::new(Body::new_earth(), "Rogue Planet")
+			.with_parent(1000, OrbitalElements::default().with_semimajor_axis_au(1.0)));
+		assert_eq!(Err(RelativePositionError::DifferentSystems(HANDLE_EARTH, 1001)), database.try_relative_position(&HANDLE_EARTH, &1001, 0.0));
+		assert_eq!(Err(RelativePositionError::DifferentSystems(1000, HANDLE_SOL)), database.try_relative_position(&1000, &HANDLE_SOL, 0.0));
+		assert_eq!(None, database.relative_position(&HANDLE_EARTH, &1001, 0.0));
+		assert_eq!(database.relative_position(&HANDLE_EARTH, &HANDLE_MARS, 0.0), database.try_relative_position(&HANDLE_EARTH, &HANDLE_MARS, 0.0).ok());
+	}
+
+	#[test]
+	fn equatorial_normal() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let (position, velocity) = database.state_vector_at_time(&HANDLE_EARTH, 0.0).unwrap();
+		let orbital_normal = position.cross(&velocity).normalize();
+		let equatorial_normal = database.equatorial_normal(&HANDLE_EARTH, 0.0);
+		assert_ulps_eq!(1.0, equatorial_normal.norm(), epsilon=1e-9);
+		let angle_deg = equatorial_normal.angle(&orbital_normal) * constants::f64::CONVERT_RAD_TO_DEG;
+		assert_ulps_eq!(23.44, angle_deg, epsilon=0.01);
+		// luna orbits in earth's equatorial plane
+		let (position, velocity) = database.state_vector_at_time(&HANDLE_LUNA, 0.0).unwrap();
+		let luna_normal = position.cross(&velocity).normalize();
+		let luna_inclination_deg = 18.294;
+		assert_ulps_eq!(luna_inclination_deg, luna_normal.angle(&equatorial_normal) * constants::f64::CONVERT_RAD_TO_DEG, epsilon=0.01);
+		// an untilted moon of a tilted planet spins about the planet's axis, not the ecliptic pole
+		let mut database = database;
+		let moon = DatabaseEntry::new(Body::default().with_radius_km(100.0), "Moon")
+			.with_parent(HANDLE_SATURN, OrbitalElements::default().with_semimajor_axis_km(200_000.0));
+		database.add_entry(1000, moon);
+		assert_ulps_eq!(database.equatorial_normal(&HANDLE_SATURN, 0.0), database.equatorial_normal(&1000, 0.0), epsilon=1e-12);
+		let saturn_tilt_deg = database.equatorial_normal(&1000, 0.0).angle(&Vector3::y()) * constants::f64::CONVERT_RAD_TO_DEG;
+		assert_ulps_eq!(26.73, saturn_tilt_deg, epsilon=1e-9);
+	}
+
+	#[test]
+	fn step() {
+		let reference = Database::<u16, f64>::default().with_solar_system();
+		let mut stepped = Database::<u16, f64>::default().with_solar_system();
+		let dt = 3600.0;
+		let steps = 500;
+		for _ in 0..steps {
+			stepped.step(dt);
+		}
+		for handle in [HANDLE_EARTH, HANDLE_LUNA, HANDLE_PHOBOS, HANDLE_IO] {
+			let mean_anomaly = stepped.get_entry(&handle).mean_anomaly_at_epoch;
+			assert!((0.0..std::f64::consts::TAU).contains(&mean_anomaly));
+			let expected = reference.position_at_time(&handle, dt * steps as f64);
+			let actual = stepped.position_at_mean_anomaly(&handle, mean_anomaly);
+			assert!((expected - actual).norm() < expected.norm() * 1e-6, "Stepped position of {} drifted from {:?} to {:?}", handle, expected, actual);
+		}
+	}
+
+	#[test]
+	fn step_hyperbolic() {
+		let with_probe = || {
+			let mut database = Database::<u16, f64>::default().with_solar_system();
+			let flyby = OrbitalElements::default().with_semimajor_axis_km(-20_000.0).with_eccentricity(1.5);
+			database.add_entry(1000, DatabaseEntry::new(Body::default(), "Probe").with_parent(HANDLE_EARTH, flyby));
+			database
+		};
+		let reference = with_probe();
+		let mut stepped = with_probe();
+		let dt = 3600.0;
+		let steps = 200;
+		for _ in 0..steps {
+			stepped.step(dt);
+		}
+		// far past 2π of mean anomaly, the probe keeps flying away instead of wrapping back to periapsis
+		let mean_anomaly = stepped.get_entry(&1000).mean_anomaly_at_epoch;
+		assert!(mean_anomaly > std::f64::consts::TAU);
+		let expected = reference.position_at_time(&1000, dt * steps as f64);
+		let actual = stepped.position_at_mean_anomaly(&1000, mean_anomaly);
+		assert!((expected - actual).norm() < expected.norm() * 1e-6, "Stepped probe drifted from {:?} to {:?}", expected, actual);
+		assert!(actual.norm() > 1e9);
+	}
+
+	#[test]
+	fn satellite_counts() {
+		let database = Database::<u16, f32>::default().with_solar_system();
+		let jovian_moons = (HANDLE_JUPITER + 1..HANDLE_SATURN).filter(|handle| database.try_get_entry(handle).is_some()).count();
+		assert_eq!(Some((HANDLE_JUPITER, jovian_moons)), database.most_moons_body());
+		let counts = database.satellite_count_by_body();
+		assert_eq!(database.iter().count(), counts.len());
+		assert!(counts.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+		assert!(counts.contains(&(HANDLE_EARTH, 1)));
+		assert!(counts.contains(&(HANDLE_LUNA, 0)));
+		assert!(Database::<u16, f32>::default().most_moons_body().is_none());
+	}
+
+	#[test]
+	fn primary_wobble() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let charon_info = Body::default().with_mass_kg(1.586e21).with_radius_km(606.0);
+		let charon_orbit = OrbitalElements::default().with_semimajor_axis_km(19_591.0).with_eccentricity(0.0002);
+		let charon_entry = DatabaseEntry::new(charon_info, "Charon").with_parent(HANDLE_PLUTO, charon_orbit);
+		database.add_entry(1000, charon_entry);
+		let pluto_radius = database.get_entry(&HANDLE_PLUTO).info.radius_avg_m();
+		let wobble = database.primary_wobble_at_time(&HANDLE_PLUTO, 0.0);
+		// the barycenter is about 2100 km from Pluto's center, outside Pluto itself
+		assert_ulps_eq!(2126.0, wobble.norm() / 1000.0, epsilon=20.0);
+		assert!(wobble.norm() > pluto_radius);
+		let charon_position = database.position_at_time(&1000, 0.0);
+		assert_ulps_eq!(-1.0, wobble.normalize().dot(&charon_position.normalize()), epsilon=1e-9);
+		assert_eq!(Vector3::zeros(), database.primary_wobble_at_time(&HANDLE_MERCURY, 0.0));
+		let ideal = database.position_at_time(&HANDLE_PLUTO, 0.0);
+		database.set_barycentric_wobble(true);
+		assert!(database.barycentric_wobble());
+		assert_ulps_eq!(ideal + wobble, database.position_at_time(&HANDLE_PLUTO, 0.0), epsilon=1.0);
+	}
+
+	#[test]
+	fn escape_delta_v() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let earth_gm = database.get_entry(&HANDLE_EARTH).gm();
+		let earth_soi = database.radius_soi(&HANDLE_EARTH);
+		// half the sphere of influence is the semimajor axis with exactly the energy to reach it
+		let orbit = OrbitalElements::default().with_semimajor_axis_m(earth_soi / 2.0).with_eccentricity(0.5);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Probe").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1000, entry);
+		assert_ulps_eq!(0.0, database.escape_delta_v(&1000, 1000.0).unwrap(), epsilon=0.01);
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(6771.0);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Station").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1001, entry);
+		let radius = 6_771_000.0;
+		let expected = (2.0 * earth_gm * (1.0 / radius - 1.0 / earth_soi)).sqrt() - (earth_gm / radius).sqrt();
+		assert_ulps_eq!(expected, database.escape_delta_v(&1001, 0.0).unwrap(), epsilon=0.01);
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(-20_000.0).with_eccentricity(1.5);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Escaping").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1002, entry);
+		assert_eq!(Some(0.0), database.escape_delta_v(&1002, 0.0));
+		assert_eq!(None, database.escape_delta_v(&HANDLE_SOL, 0.0));
+	}
+
+	#[test]
+	fn absolute_position_f64_accumulation() {
+		fn add_probe<T: Clone + Float + FromPrimitive + SubAssign>(database: &mut Database<u16, T>) {
+			let orbit = OrbitalElements::default().with_semimajor_axis_km(T::from_f32(2345.6).unwrap()).with_eccentricity(T::from_f32(0.1).unwrap());
+			let entry = DatabaseEntry::new(Body::default().with_mass_kg(T::from_f32(1000.0).unwrap()), "Probe").with_parent(HANDLE_IO, orbit);
+			database.add_entry(1000, entry);
+		}
+		let mut database_f32 = Database::<u16, f32>::default().with_solar_system();
+		let mut database_f64 = Database::<u16, f64>::default().with_solar_system();
+		add_probe(&mut database_f32);
+		add_probe(&mut database_f64);
+		let mut naive_error = 0.0;
+		let mut accumulated_error = 0.0;
+		for step in 0..50 {
+			let time = step as f64 * 12_345.0;
+			// the f32 terms converted to f64 and summed exactly, so only the summation differs
+			let terms = [1000, HANDLE_IO, HANDLE_JUPITER].map(|handle| database_f32.position_at_time(&handle, time as f32));
+			let exact_sum = terms.iter().fold(Vector3::<f64>::zeros(), |sum, term| sum + term.map(|value| value as f64));
+			let naive = terms[0] + (terms[1] + terms[2]);
+			let accumulated = database_f32.absolute_position_at_time(&1000, time as f32);
+			naive_error += (naive.map(|value| value as f64) - exact_sum).norm();
+			accumulated_error += (accumulated.map(|value| value as f64) - exact_sum).norm();
+			let expected = database_f64.absolute_position_at_time(&1000, time);
+			assert!((accumulated.map(|value| value as f64) - expected).norm() / expected.norm() < 1e-6);
+		}
+		assert!(accumulated_error < naive_error, "accumulated error {} m, naive error {} m", accumulated_error, naive_error);
+	}
+
+	#[test]
+	fn velocity_at_node() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		for handle in [HANDLE_MERCURY, HANDLE_MARS, HANDLE_LUNA, HANDLE_TRITON] {
+			let normal = database.equatorial_normal(database.get_entry(&handle).parent.as_ref().unwrap(), 0.0);
+			let ascending = database.velocity_at_node(&handle, true).unwrap();
+			let descending = database.velocity_at_node(&handle, false).unwrap();
+			assert!(ascending.dot(&normal) > 0.0, "{} ascends with a velocity of {}", handle, ascending);
+			assert!(descending.dot(&normal) < 0.0, "{} descends with a velocity of {}", handle, descending);
+		}
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(-20_000.0).with_eccentricity(2.0).with_inclination_deg(30.0).with_arg_of_periapsis_deg(150.0);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Flyby").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1000, entry);
+		assert!(database.velocity_at_node(&1000, true).is_none());
+		assert!(database.velocity_at_node(&1000, false).is_some());
+		assert!(database.velocity_at_node(&HANDLE_SOL, true).is_none());
+	}
+
+	#[test]
+	fn all_positions_relative_to() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let time = 1e8;
+		let positions = database.all_positions_relative_to(&HANDLE_MARS, time);
+		assert_eq!(database.iter().count(), positions.len());
+		assert_eq!(Vector3::zeros(), positions[&HANDLE_MARS]);
+		for (handle, position) in &positions {
+			let expected = database.relative_position(&HANDLE_MARS, handle, time).unwrap();
+			assert_ulps_eq!(expected, *position, epsilon=1.0);
+		}
+		// bodies in a separate system aren't included
+		database.add_entry(1000, DatabaseEntry::new(Body::default().with_mass_kg(1e30), "Rogue star"));
+		assert!(!database.all_positions_relative_to(&HANDLE_MARS, time).contains_key(&1000));
+		assert_eq!(1, database.all_positions_relative_to(&1000, time).len());
+		assert!(database.all_positions_relative_to(&1001, time).is_empty());
+	}
+
+	#[test]
+	fn apply_drag() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(6371.0 + 300.0).with_eccentricity(0.01);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0).with_radius_km(0.001), "Satellite").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1000, entry);
+		let mut last = database.get_entry(&1000).orbit.unwrap();
+		for _ in 0..100 {
+			database.apply_drag(&1000, 86_400.0, 8500.0, 1.225, 0.0);
+			let orbit = database.get_entry(&1000).orbit.unwrap();
+			assert!(orbit.semimajor_axis < last.semimajor_axis);
+			assert!(orbit.eccentricity < last.eccentricity);
+			last = orbit;
+		}
+		// bodies without an atmosphere to fly through aren't affected
+		let mars = database.get_entry(&HANDLE_MARS).orbit.unwrap();
+		database.apply_drag(&HANDLE_MARS, 86_400.0, 8500.0, 0.0, 0.0);
+		assert_eq!(mars.semimajor_axis, database.get_entry(&HANDLE_MARS).orbit.unwrap().semimajor_axis);
+		// ten years in, the satellite carries on from where it is instead of jumping along its orbit
+		let time = 10.0 * 365.25 * 86_400.0;
+		let state = |database: &Database<u16, f64>| (database.mean_anomaly_at_time(&1000, time), database.position_at_time(&1000, time));
+		let before = state(&database);
+		database.apply_drag(&1000, 86_400.0, 20_000.0, 1.225, time);
+		let after = state(&database);
+		let decay = last.semimajor_axis - database.get_entry(&1000).orbit.unwrap().semimajor_axis;
+		assert!(decay > 100.0, "Expected the orbit to drop noticeably, it dropped {:.1} m", decay);
+		assert_ulps_eq!(before.0, after.0, epsilon=1e-9);
+		assert!((before.1 - after.1).norm() < 2.0 * decay, "Expected the satellite to move about {:.0} m, it moved {:.0} m", decay, (before.1 - after.1).norm());
+	}
+
+	#[test]
+	fn mean_motion_resonance() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		assert_eq!(Some((2, 1)), database.mean_motion_resonance(&HANDLE_IO, &HANDLE_EUROPA, 0.01));
+		assert_eq!(Some((1, 2)), database.mean_motion_resonance(&HANDLE_EUROPA, &HANDLE_IO, 0.01));
+		assert_eq!(Some((4, 1)), database.mean_motion_resonance(&HANDLE_IO, &HANDLE_GANYMEDE, 0.02));
+		assert_eq!(Some((3, 2)), database.mean_motion_resonance(&HANDLE_NEPTUNE, &HANDLE_PLUTO, 0.01));
+		assert_eq!(None, database.mean_motion_resonance(&HANDLE_IO, &HANDLE_EUROPA, 0.0001));
+		assert_eq!(None, database.mean_motion_resonance(&HANDLE_SOL, &HANDLE_EUROPA, 0.01));
+	}
+
+	#[test]
+	fn update_interval() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		database.set_update_interval(&HANDLE_NEPTUNE, Some(100.0));
+		let neptune_start = database.get_entry(&HANDLE_NEPTUNE).mean_anomaly_at_epoch;
+		let earth_start = database.get_entry(&HANDLE_EARTH).mean_anomaly_at_epoch;
+		for _ in 0..9 {
+			database.step(10.0);
+			assert_eq!(neptune_start, database.get_entry(&HANDLE_NEPTUNE).mean_anomaly_at_epoch);
+		}
+		assert_ne!(earth_start, database.get_entry(&HANDLE_EARTH).mean_anomaly_at_epoch);
+		database.step(10.0);
+		let n = database.mean_motion_of(&HANDLE_NEPTUNE).unwrap();
+		assert_ulps_eq!(neptune_start + n * 100.0, database.get_entry(&HANDLE_NEPTUNE).mean_anomaly_at_epoch);
+		assert_eq!(0.0, database.get_entry(&HANDLE_NEPTUNE).pending_step);
+		database.set_update_interval(&HANDLE_NEPTUNE, None);
+		database.step(10.0);
+		assert_ulps_eq!(neptune_start + n * 110.0, database.get_entry(&HANDLE_NEPTUNE).mean_anomaly_at_epoch);
+	}
+
+	#[test]
+	fn true_anomaly_rate_at_time() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let e = 0.3;
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(20_000.0).with_eccentricity(e);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Probe").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1000, entry);
+		let half_period = database.orbital_period_s(&1000).unwrap() / 2.0;
+		let periapsis_rate = database.true_anomaly_rate_at_time(&1000, 0.0).unwrap();
+		let apoapsis_rate = database.true_anomaly_rate_at_time(&1000, half_period).unwrap();
+		assert_ulps_eq!(((1.0 + e) / (1.0 - e)).powi(2), periapsis_rate / apoapsis_rate, epsilon=1e-9);
+		// on average the true anomaly goes around at the mean motion
+		let n = database.mean_motion_of(&1000).unwrap();
+		assert!(apoapsis_rate < n && n < periapsis_rate);
+		assert_eq!(None, database.true_anomaly_rate_at_time(&HANDLE_SOL, 0.0));
+	}
+
+	#[test]
+	fn position_in_parent_equatorial() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(500_000.0).with_inclination_deg(10.0);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1e20), "Untilted planet").with_parent(HANDLE_SOL, orbit);
+		database.add_entry(1000, entry);
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(50_000.0).with_inclination_deg(10.0).with_arg_of_periapsis_deg(90.0);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Moon").with_parent(1000, orbit);
+		database.add_entry(1001, entry);
+		assert_eq!(Some(database.position_at_time(&1001, 0.0)), database.position_in_parent_equatorial(&1001, 0.0));
+		// a moon orbiting over Earth's equator stays in the equatorial plane
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(42_164.0).with_arg_of_periapsis_deg(90.0);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Geostationary").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1002, entry);
+		assert!(database.position_at_time(&1002, 0.0).y.abs() > 1_000_000.0);
+		assert_ulps_eq!(0.0, database.position_in_parent_equatorial(&1002, 0.0).unwrap().y, epsilon=1e-6);
+		assert_eq!(None, database.position_in_parent_equatorial(&HANDLE_SOL, 0.0));
+	}
+
+	#[test]
+	fn circularize_delta_v() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let earth_gm = database.get_entry(&HANDLE_EARTH).gm();
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(7000.0);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Circular").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1000, entry);
+		assert_ulps_eq!(0.0, database.circularize_delta_v(&1000, true).unwrap(), epsilon=1e-6);
+		assert_ulps_eq!(0.0, database.circularize_delta_v(&1000, false).unwrap(), epsilon=1e-6);
+		// geostationary transfer orbit
+		let (periapsis, apoapsis) = (6_678_000.0, 42_164_000.0);
+		let a = (periapsis + apoapsis) / 2.0;
+		let orbit = OrbitalElements::default().with_semimajor_axis_m(a).with_eccentricity((apoapsis - periapsis) / (apoapsis + periapsis));
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Transfer").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1001, entry);
+		let v_circ = (earth_gm / apoapsis).sqrt();
+		let v_apo = (earth_gm * (2.0 / apoapsis - 1.0 / a)).sqrt();
+		assert_ulps_eq!(v_circ - v_apo, database.circularize_delta_v(&1001, true).unwrap(), epsilon=1e-6);
+		assert_ulps_eq!(1_470.0, database.circularize_delta_v(&1001, true).unwrap(), epsilon=10.0);
+		assert!(database.circularize_delta_v(&1001, false).unwrap() < 0.0);
+		assert_eq!(None, database.circularize_delta_v(&HANDLE_SOL, true));
+	}
+
+	#[test]
+	fn lambert() {
+		let mut database = Database::<u16, f64>::default().with_solar_system().with_coordinate_convention(CoordinateConvention::ZUp);
+		// a third of the way around a circular orbit should come back as that orbit
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(7000.0).with_inclination_deg(30.0);
+		let entry = DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Satellite").with_parent(HANDLE_EARTH, orbit);
+		database.add_entry(1000, entry);
+		let tof = database.orbital_period_s(&1000).unwrap() / 3.0;
+		let (r1, v1) = database.state_vector_at_time(&1000, 0.0).unwrap();
+		let (r2, v2) = database.state_vector_at_time(&1000, tof).unwrap();
+		let (departure, arrival) = database.lambert(&HANDLE_EARTH, r1, r2, tof, true).unwrap();
+		assert_ulps_eq!(v1, departure, epsilon=0.01);
+		assert_ulps_eq!(v2, arrival, epsilon=0.01);
+		// going the other way around takes a very different orbit
+		let (departure, _) = database.lambert(&HANDLE_EARTH, r1, r2, tof, false).unwrap();
+		assert!((departure - v1).norm() > 1000.0);
+		assert!(database.lambert(&1001, r1, r2, tof, true).is_none());
+	}
+
+	#[test]
+	fn shadow_geometry() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let shadow = database.shadow_geometry(&HANDLE_EARTH, &HANDLE_SOL, 0.0).unwrap();
+		assert_ulps_eq!(1_380_000.0, shadow.umbra_length / 1000.0, epsilon=50_000.0);
+		let sun_to_earth = database.position_at_time(&HANDLE_EARTH, 0.0);
+		assert_ulps_eq!(sun_to_earth.normalize(), shadow.axis, epsilon=1e-12);
+		// the moon's distance is about a quarter of the way along the umbra
+		let umbra_at_moon = shadow.umbra_radius_at(384_400_000.0);
+		let penumbra_at_moon = shadow.penumbra_radius_at(384_400_000.0);
+		assert_ulps_eq!(4_600.0, umbra_at_moon / 1000.0, epsilon=100.0);
+		assert!(penumbra_at_moon > shadow.occluder_radius && penumbra_at_moon > umbra_at_moon);
+		assert_eq!(0.0, shadow.umbra_radius_at(2.0 * shadow.umbra_length));
+		assert!(database.shadow_geometry(&HANDLE_SOL, &HANDLE_EARTH, 0.0).is_none());
+	}
+
+	#[test]
+	fn descendant_count() {
+		let mut database = Database::<u16, f32>::default().with_solar_system();
+		let jovian_moons = (HANDLE_JUPITER + 1..HANDLE_SATURN).filter(|handle| database.try_get_entry(handle).is_some()).count();
+		assert_eq!(jovian_moons, database.descendant_count(&HANDLE_JUPITER));
+		assert_eq!(0, database.descendant_count(&HANDLE_LUNA));
+		assert_eq!(database.iter().count() - 1, database.descendant_count(&HANDLE_SOL));
+		// satellites of satellites count too
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(2000.0);
+		database.add_entry(1000, DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Lunar probe").with_parent(HANDLE_LUNA, orbit));
+		assert_eq!(2, database.descendant_count(&HANDLE_EARTH));
+	}
+
+	#[test]
+	fn massless_parent() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let orbit = OrbitalElements::default().with_semimajor_axis_au(40.0);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "Barycenter").with_parent(HANDLE_SOL, orbit));
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(20_000.0).with_eccentricity(0.1);
+		database.add_entry(1001, DatabaseEntry::new(Body::default().with_mass_kg(1e21).with_radius_km(500.0), "Child").with_parent(1000, orbit).with_mean_anomaly_deg(90.0));
+		assert_eq!(None, database.orbital_period_s(&1001));
+		assert_eq!(database.get_entry(&1001).mean_anomaly_at_epoch, database.mean_anomaly_at_time(&1001, 1e9));
+		assert!(database.position_at_time(&1001, 1e9).iter().all(|value| value.is_finite()));
+		let (_, velocity) = database.state_vector_at_time(&1001, 1e9).unwrap();
+		assert!(velocity.iter().all(|value| value.is_finite()));
+		let child_soi = database.radius_soi(&1001);
+		assert!(child_soi.is_finite() && child_soi > 0.0);
+		let barycenter_soi = database.radius_soi(&1000);
+		assert!(barycenter_soi.is_finite() && barycenter_soi >= 0.0);
+		// with no tides from the parent the hill sphere falls back to the sphere of influence
+		assert_eq!(child_soi, database.radius_hill_sphere(&1001));
+		let (inner, outer) = database.stable_satellite_range(&1001).unwrap();
+		assert!(inner.is_finite() && outer.is_finite() && inner < outer);
+		// a massless child of a massless parent doesn't come out as 0/0 either
+		database.add_entry(1002, DatabaseEntry::new(Body::default(), "Massless child").with_parent(1000, orbit));
+		assert!(database.radius_hill_sphere(&1002).is_finite());
+		assert_eq!(None, database.stable_satellite_range(&1002));
+	}
+
+	#[test]
+	fn orbit_path_2d() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(10_000.0);
+		database.add_entry(1000, DatabaseEntry::new(Body::default().with_mass_kg(1000.0), "Satellite").with_parent(HANDLE_SOL, orbit));
+		let path = database.orbit_path_2d(&1000, 32);
+		assert_eq!(33, path.len());
+		for (x, y) in &path {
+			assert_ulps_eq!(10_000_000.0, (x * x + y * y).sqrt(), epsilon=1e-3);
+		}
+		assert_ulps_eq!(path[0].0, path[32].0, epsilon=1e-3);
+		assert_ulps_eq!(path[0].1, path[32].1, epsilon=1e-3);
+		assert!(database.orbit_path_2d(&HANDLE_SOL, 32).is_empty());
+	}
+
+	#[test]
+	fn equilibrium_temperature() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		// early january, close to perihelion, then half a year later near aphelion
+		let perihelion = database.equilibrium_temperature_k(&HANDLE_EARTH, &HANDLE_SOL, 0.0).unwrap();
+		let aphelion = database.equilibrium_temperature_k(&HANDLE_EARTH, &HANDLE_SOL, 182.6 * 86_400.0).unwrap();
+		assert_ulps_eq!(255.0, perihelion, epsilon=4.0);
+		assert_ulps_eq!(255.0, aphelion, epsilon=4.0);
+		assert!(perihelion > aphelion);
+		assert_ulps_eq!(1361.0, database.insolation_w_m2(&HANDLE_EARTH, &HANDLE_SOL, 0.0).unwrap(), epsilon=60.0);
+		// further out and brighter, Jupiter is much colder
+		let jupiter = database.equilibrium_temperature_k(&HANDLE_JUPITER, &HANDLE_SOL, 0.0).unwrap();
+		assert!((90.0..130.0).contains(&jupiter), "Expected Jupiter around 110 K, got {:.1} K", jupiter);
+		assert!(database.equilibrium_temperature_k(&1000, &HANDLE_SOL, 0.0).is_none());
+	}
+
+	#[test]
+	fn tisserand_parameter() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		// comet 67P/Churyumov-Gerasimenko, a Jupiter family comet with a published T of 2.75
+		let churyumov_gerasimenko = OrbitalElements::default()
+			.with_semimajor_axis_au(3.4628)
+			.with_eccentricity(0.6410)
+			.with_inclination_deg(7.0405)
+			.with_long_of_ascending_node_deg(50.147)
+			.with_arg_of_periapsis_deg(12.780);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "67P").with_parent(HANDLE_SOL, churyumov_gerasimenko));
+		assert_ulps_eq!(2.75, database.tisserand_parameter(&1000, &HANDLE_JUPITER).unwrap(), epsilon=0.02);
+		// relative to itself a circular orbit gives exactly 3, and an eccentric one a little less
+		let mars_eccentricity = database.get_entry(&HANDLE_MARS).orbit.unwrap().eccentricity;
+		let expected = 1.0 + 2.0 * (1.0 - mars_eccentricity * mars_eccentricity).sqrt();
+		assert_ulps_eq!(expected, database.tisserand_parameter(&HANDLE_MARS, &HANDLE_MARS).unwrap(), epsilon=1e-9);
+		assert!(database.tisserand_parameter(&HANDLE_LUNA, &HANDLE_JUPITER).is_none());
+		assert!(database.tisserand_parameter(&HANDLE_SOL, &HANDLE_JUPITER).is_none());
+	}
+
+	#[test]
+	fn overlapping_soi_pairs() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		assert!(database.overlapping_soi_pairs(&HANDLE_SOL, 0.0).is_empty());
+		assert!(database.overlapping_soi_pairs(&HANDLE_EARTH, 0.0).is_empty());
+		// two heavy moons a degree apart on the same orbit are well within each other's reach
+		let moon = || Body::default().with_mass_kg(1e22).with_radius_km(500.0);
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(100_000.0);
+		database.add_entry(1001, DatabaseEntry::new(moon(), "Moon A").with_parent(HANDLE_EARTH, orbit));
+		database.add_entry(1000, DatabaseEntry::new(moon(), "Moon B").with_parent(HANDLE_EARTH, orbit).with_mean_anomaly_deg(1.0));
+		assert_eq!(vec![(1000, 1001)], database.overlapping_soi_pairs(&HANDLE_EARTH, 0.0));
+		assert!(database.overlapping_soi_pairs(&HANDLE_LUNA, 0.0).is_empty());
+	}
+
+	#[test]
+	fn stable_satellite_range() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let (inner, outer) = database.stable_satellite_range(&HANDLE_EARTH).unwrap();
+		let luna_distance = database.get_entry(&HANDLE_LUNA).orbit.unwrap().semimajor_axis;
+		assert!(inner > database.get_entry(&HANDLE_EARTH).info.radius_equator_m());
+		assert!(inner * 10.0 < luna_distance && luna_distance < outer, "Expected the moon well inside ({}, {}), got {}", inner, outer, luna_distance);
+		// earth's roche limit for rocky moons is just under 20,000 km
+		assert_ulps_eq!(19_000_000.0, inner, epsilon=1_000_000.0);
+		assert!(database.stable_satellite_range(&HANDLE_SOL).is_none());
+		// a planet skimming its star's surface can't keep anything
+		let hot = Body::default().with_mass_earths(1.0).with_radius_km(6_400.0);
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(1_000_000.0);
+		database.add_entry(1000, DatabaseEntry::new(hot, "Hot Earth").with_parent(HANDLE_SOL, orbit));
+		assert!(database.stable_satellite_range(&1000).is_none());
+	}
+
+	#[test]
+	fn marker_bodies() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let earth_mass = database.get_combined_mass_kg(&HANDLE_EARTH);
+		let earth_soi = database.radius_soi(&HANDLE_EARTH);
+		let point = Vector3::new(1e11, 0.0, 0.0);
+		let gravity = database.gravity_at_point(point, 0.0, None);
+		// a waypoint with a mass set by mistake still weighs nothing once it's marked
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(2_000_000.0);
+		let waypoint = DatabaseEntry::new(Body::default().with_mass_kg(1e24), "Waypoint").with_parent(HANDLE_EARTH, orbit).with_marker(true);
+		database.add_entry(1000, waypoint);
+		assert!(database.get_satellites(&HANDLE_EARTH).contains(&1000));
+		assert_eq!(earth_mass, database.get_combined_mass_kg(&HANDLE_EARTH));
+		assert_eq!(earth_soi, database.radius_soi(&HANDLE_EARTH));
+		assert_eq!(gravity, database.gravity_at_point(point, 0.0, None));
+		// satellites of a marker still count towards the masses above it
+		let moonlet = DatabaseEntry::new(Body::default().with_mass_kg(1e15), "Moonlet").with_parent(1000, OrbitalElements::default().with_semimajor_axis_km(10.0));
+		database.add_entry(1001, moonlet);
+		assert_eq!(earth_mass + 1e15, database.get_combined_mass_kg(&HANDLE_EARTH));
+		// markers can't be bumped into or hide anything, even with a radius
+		let marker_position = database.absolute_position_at_time(&1000, 0.0);
+		database.add_entry(1002, DatabaseEntry::new(Body::default().with_radius_km(1000.0), "Beacon").with_parent(1000, OrbitalElements::default()).with_marker(true));
+		for indexed in [false, true] {
+			if indexed {
+				database.rebuild_spatial_index(0.0, 1e9);
+			}
+			let nearby: Vec<u16> = database.bodies_within(marker_position, 1e6, 0.0).into_iter().map(|(handle, _)| handle).collect();
+			assert_eq!(vec![1001], nearby);
+			assert_eq!(1001, database.bodies_by_distance_from(marker_position, 0.0, 1)[0].0);
+		}
+		let across = Vector3::new(0.0, 0.0, 5e6);
+		assert_eq!(None, database.los_blocking_body(marker_position - across, marker_position + across, 0.0));
+		// lagrange points are markers too
+		database.add_lagrange_point(1003, &HANDLE_SOL, &HANDLE_EARTH, LagrangePoint::L4, "Earth L4");
+		assert!(database.get_entry(&1003).is_marker);
+	}
+
+	#[test]
+	fn elements_relative_to() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let time = 1_000_000.0;
+		// a body's elements relative to its own parent are just its orbit
+		let earth = database.get_entry(&HANDLE_EARTH).orbit.unwrap();
+		let earth_around_sol = database.elements_relative_to(&HANDLE_EARTH, &HANDLE_SOL, time).unwrap();
+		assert_ulps_eq!(earth.semimajor_axis, earth_around_sol.semimajor_axis, epsilon=1.0);
+		assert_ulps_eq!(earth.eccentricity, earth_around_sol.eccentricity, epsilon=1e-9);
+		// the moon's path around the sun is mostly the earth's, nudged by its own month long orbit
+		let luna_around_sol = database.elements_relative_to(&HANDLE_LUNA, &HANDLE_SOL, time).unwrap();
+		assert_ulps_eq!(1.0, luna_around_sol.semimajor_axis / earth.semimajor_axis, epsilon=0.1);
+		assert!(luna_around_sol.eccentricity < 0.1, "Expected a nearly circular heliocentric orbit, got e={}", luna_around_sol.eccentricity);
+		assert!(luna_around_sol.eccentricity > earth.eccentricity);
+		assert!(database.elements_relative_to(&HANDLE_LUNA, &HANDLE_LUNA, time).is_none());
+		assert!(database.elements_relative_to(&HANDLE_LUNA, &HANDLE_MARS, time).is_none());
+		assert!(database.elements_relative_to(&HANDLE_SOL, &HANDLE_EARTH, time).is_none());
+	}
+
+	#[test]
+	fn frame_update() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let time = 12_345.0;
+		let update = database.frame_update(&HANDLE_EARTH, time);
+		assert_eq!(database.all_positions_relative_to(&HANDLE_EARTH, time).len(), update.len());
+		let (_, earth_position, earth_orientation) = update.iter().find(|(handle, _, _)| *handle == HANDLE_EARTH).unwrap();
+		assert_eq!(Vector3::zeros(), *earth_position);
+		assert_eq!(database.orientation_at_time(&HANDLE_EARTH, time), *earth_orientation);
+		let (_, luna_position, _) = update.iter().find(|(handle, _, _)| *handle == HANDLE_LUNA).unwrap();
+		assert_ulps_eq!(database.position_at_time(&HANDLE_LUNA, time), *luna_position, epsilon=1e-3);
+		assert!(database.frame_update(&1000, time).is_empty());
+	}
+
+	#[test]
+	fn orientation_at_time() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let sidereal_day = database.get_entry(&HANDLE_EARTH).info.rotation_period_s().unwrap();
+		for convention in [CoordinateConvention::YUp, CoordinateConvention::ZUp] {
+			database.set_coordinate_convention(convention);
+			let up = match convention {
+				CoordinateConvention::YUp => Vector3::y(),
+				CoordinateConvention::ZUp => Vector3::z(),
+			};
+			// the up axis always points along the rotation axis while the body spins around it
+			for time in [0.0, sidereal_day * 0.25, sidereal_day * 0.6] {
+				let orientation = database.orientation_at_time(&HANDLE_EARTH, time);
+				assert_ulps_eq!(database.equatorial_normal(&HANDLE_EARTH, time), orientation * up, epsilon=1e-12);
+			}
+			// a quarter of a day turns a point on the equator a quarter of the way around
+			let start = database.orientation_at_time(&HANDLE_EARTH, 0.0);
+			let quarter = database.orientation_at_time(&HANDLE_EARTH, sidereal_day * 0.25);
+			let side = start * up.cross(&Vector3::x());
+			assert_ulps_eq!(90.0, side.angle(&(quarter * up.cross(&Vector3::x()))) * constants::f64::CONVERT_RAD_TO_DEG, epsilon=1e-6);
+			assert_ulps_eq!(start, database.orientation_at_time(&HANDLE_EARTH, sidereal_day), epsilon=1e-9);
+		}
+	}
+
+	#[test]
+	fn nodal_precession_rate() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let seconds_per_year = 365.25 * 86_400.0;
+		// the moon's nodes go around once every 18.6 years, about 19.35° a year, mostly because of the sun
+		let luna_deg_per_year = database.nodal_precession_rate(&HANDLE_LUNA).unwrap() * seconds_per_year * constants::f64::CONVERT_RAD_TO_DEG;
+		assert!((-21.0..-18.0).contains(&luna_deg_per_year), "Expected the moon's node to regress about 19° a year, got {:.2}°", luna_deg_per_year);
+		// a low earth orbit regresses about 5° a day from earth's equatorial bulge alone
+		let iss = OrbitalElements::default().with_semimajor_axis_km(6_778.0).with_inclination_deg(51.6);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "ISS").with_parent(HANDLE_EARTH, iss));
+		let iss_deg_per_day = database.nodal_precession_rate(&1000).unwrap() * 86_400.0 * constants::f64::CONVERT_RAD_TO_DEG;
+		assert!((-5.2..-4.8).contains(&iss_deg_per_day), "Expected the node to regress about 5° a day, got {:.2}°", iss_deg_per_day);
+		assert_eq!(None, database.nodal_precession_rate(&HANDLE_SOL));
+	}
+
+	#[test]
+	fn recent_trail() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let from_time = 1_000_000.0;
+		let to_time = 1_000_000.0 + 86_400.0 * 3.0;
+		let trail = database.recent_trail(&HANDLE_LUNA, from_time, to_time, 12);
+		assert_eq!(13, trail.len());
+		assert_eq!(database.position_at_time(&HANDLE_LUNA, from_time), trail[0]);
+		assert_eq!(database.position_at_time(&HANDLE_LUNA, to_time), trail[12]);
+		assert_eq!(database.position_at_time(&HANDLE_LUNA, from_time + 86_400.0 * 1.5), trail[6]);
+	}
+
+	#[test]
+	fn mean_anomaly_wrapping() {
+		// exactly representable in f32 so both databases start from the same elements
+		fn add_satellite<T: Clone + Float + FromPrimitive + SubAssign>(database: &mut Database<u16, T>) {
+			let planet = Body::default().with_mass_kg(T::from_f64(1e24).unwrap()).with_gm_m3_s2(T::from_f64(2f64.powi(48)).unwrap());
+			database.add_entry(1000, DatabaseEntry::new(planet, "Planet"));
+			let orbit = OrbitalElements::default().with_semimajor_axis_m(T::from_f64(2f64.powi(23)).unwrap()).with_eccentricity(T::from_f64(0.25).unwrap());
+			database.add_entry(1001, DatabaseEntry::new(Body::default(), "Satellite").with_parent(1000, orbit));
+		}
+		let mut database_f32 = Database::<u16, f32>::default();
+		let mut database_f64 = Database::<u16, f64>::default();
+		add_satellite(&mut database_f32);
+		add_satellite(&mut database_f64);
+		let time = 2f32.powi(30);
+		let expected = database_f64.position_at_time(&1001, time as f64);
+		let wrapped = database_f32.position_at_time(&1001, time).map(|value| value as f64);
+		// what position_at_time used to do, all in f32 on the unwrapped angle
+		let orbit = database_f32.get_entry(&1001).orbit.unwrap();
+		let n = mean_motion(database_f32.get_entry(&1000).gm(), orbit.semimajor_axis);
+		let unwrapped = orbit.position_at_true_anomaly(orbit.true_anomaly_at_mean_anomaly(n * time)).map(|value| value as f64);
+		let wrapped_error = (wrapped - expected).norm();
+		let unwrapped_error = (unwrapped - expected).norm();
+		assert!(wrapped_error < 10.0, "wrapped error {} m", wrapped_error);
+		assert!(unwrapped_error > 100.0 * wrapped_error, "unwrapped error {} m, wrapped error {} m", unwrapped_error, wrapped_error);
+		let mean_anomaly = database_f64.mean_anomaly_at_time(&1001, 1e12);
+		assert!((0.0..std::f64::consts::TAU).contains(&mean_anomaly));
+	}
+
+	#[test]
+	fn heliocentric_longitude_at_time() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let year = database.orbital_period_s(&HANDLE_EARTH).unwrap();
+		let steps = 1000;
+		let step_days = year / steps as f64 / 86_400.0;
+		let longitudes: Vec<f64> = (0..=steps).map(|step| database.heliocentric_longitude_at_time(&HANDLE_EARTH, step as f64 * year / steps as f64).unwrap()).collect();
+		let mut total = 0.0;
+		let mut crossings = Vec::new();
+		for (step, pair) in longitudes.windows(2).enumerate() {
+			let delta = (pair[1] - pair[0]).rem_euclid(std::f64::consts::TAU);
+			assert!(delta > 0.0 && delta < 0.01);
+			total += delta;
+			// days when Earth passes each quarter point
+			if (pair[0] / std::f64::consts::FRAC_PI_2).floor() != (pair[1] / std::f64::consts::FRAC_PI_2).floor() {
+				crossings.push(step);
+			}
+		}
+		assert_ulps_eq!(360.0, total.to_degrees(), epsilon=1e-6);
+		assert_eq!(4, crossings.len());
+		// the seasons are each about a quarter of a year, but not equal since the orbit's eccentric
+		for season in crossings.windows(2) {
+			let season_days = (season[1] - season[0]) as f64 * step_days;
+			assert!((87.0..=95.0).contains(&season_days), "Season of {} days", season_days);
+		}
+		assert_eq!(None, database.heliocentric_longitude_at_time(&HANDLE_SOL, 0.0));
+		let luna = database.heliocentric_longitude_at_time(&HANDLE_LUNA, 0.0).unwrap();
+		assert!((luna - longitudes[0]).abs() < 0.01);
+	}
+
+	#[test]
+	fn relative_position_common_ancestor() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		// the old approach summed every position from the root down on both sides
+		let pairs = [(HANDLE_DEIMOS, HANDLE_IO), (HANDLE_LUNA, HANDLE_TITAN), (HANDLE_TRITON, HANDLE_EARTH), (HANDLE_SOL, HANDLE_PHOBOS), (HANDLE_PHOBOS, HANDLE_DEIMOS), (HANDLE_EARTH, HANDLE_LUNA)];
+		for time in [0.0, 1e6, 1e8, -3e9] {
+			for (origin, relative) in pairs {
+				let expected = database.absolute_position_at_time(&relative, time) - database.absolute_position_at_time(&origin, time);
+				let position = database.relative_position(&origin, &relative, time).unwrap();
+				assert_ulps_eq!(expected, position, epsilon=expected.norm() * 1e-12 + 1e-3);
+			}
+		}
+		let time = 1e8;
+		// only the bodies below the sun are needed, each once, where subtracting the whole of
+		// Deimos' hierarchy and adding back Io's used the sun's position twice
+		let (origin_branch, relative_branch) = database.branches_below_common_ancestor(&HANDLE_DEIMOS, &HANDLE_IO).unwrap();
+		assert_eq!(vec![HANDLE_MARS, HANDLE_DEIMOS], origin_branch);
+		assert_eq!(vec![HANDLE_JUPITER, HANDLE_IO], relative_branch);
+		let (origin_branch, relative_branch) = database.branches_below_common_ancestor(&HANDLE_EARTH, &HANDLE_LUNA).unwrap();
+		assert!(origin_branch.is_empty());
+		assert_eq!(vec![HANDLE_LUNA], relative_branch);
+		assert_eq!(Some(Vector3::zeros()), database.relative_position(&HANDLE_IO, &HANDLE_IO, time));
+	}
+
+	#[test]
+	fn arrival_hyperbola() {
+		let database = Database::<u16, f64>::default().with_solar_system();
+		let sun_gm = database.get_entry(&HANDLE_SOL).gm();
+		let mars_gm = database.get_entry(&HANDLE_MARS).gm();
+		let time = 1e7;
+		// arriving at the apoapsis of a Hohmann transfer from Earth, slower than Mars
+		let (mars_position, mars_velocity) = database.state_vector_at_time(&HANDLE_MARS, time).unwrap();
+		let (r_earth, r_mars) = (1.496e11, mars_position.norm());
+		let transfer_speed = (sun_gm * (2.0 / r_mars - 2.0 / (r_earth + r_mars))).sqrt();
+		let transfer_vel = mars_velocity.normalize() * transfer_speed;
+		let v_infinity = (transfer_vel - mars_velocity).norm();
+		let orbit = database.arrival_hyperbola(transfer_vel, &HANDLE_MARS, time).unwrap();
+		assert!(orbit.eccentricity > 1.0);
+		assert_ulps_eq!(-mars_gm / v_infinity.powi(2), orbit.semimajor_axis, epsilon=1.0);
+		let radius = database.get_entry(&HANDLE_MARS).info.radius_equator_m();
+		assert_ulps_eq!(1.1 * radius, orbit.semimajor_axis * (1.0 - orbit.eccentricity), epsilon=1.0);
+		// comes in along the excess velocity and reaches periapsis after entering the sphere of influence
+		let true_anomaly_asymptote = (-1.0 / orbit.eccentricity).acos();
+		let incoming = orbit.position_at_true_anomaly(-true_anomaly_asymptote + 1e-6).normalize();
+		let mars_axis_rotation = Database::axis_rotation(database.get_entry(&HANDLE_MARS));
+		assert_ulps_eq!(-(transfer_vel - mars_velocity).normalize(), mars_axis_rotation * incoming, epsilon=1e-3);
+		assert!(orbit.time_of_periapsis_passage > time);
+		assert!(database.arrival_hyperbola(mars_velocity, &HANDLE_MARS, time).is_none());
+		assert!(database.arrival_hyperbola(transfer_vel, &HANDLE_SOL, time).is_none());
+	}
+
+	#[test]
+	fn true_anomaly_at_position() {
+		let mut database = Database::<u16, f64>::default();
+		database.add_entry(1000, DatabaseEntry::new(Body::default().with_mass_kg(1e24), "Planet"));
+		let orbit = OrbitalElements::default().with_semimajor_axis_km(20_000.0).with_eccentricity(0.4).with_inclination_deg(35.0).with_arg_of_periapsis_deg(70.0).with_long_of_ascending_node_deg(200.0);
+		database.add_entry(1001, DatabaseEntry::new(Body::default(), "Satellite").with_parent(1000, orbit));
+		for true_anomaly in [-3.0, -1.2, 0.0, 0.5, 2.0, std::f64::consts::PI] {
+			let position = orbit.position_at_true_anomaly(true_anomaly);
+			assert_ulps_eq!(true_anomaly, database.true_anomaly_at_position(&1001, position).unwrap(), epsilon=1e-9);
+		}
+		// positions from a tilted parent in a Z-up database come back to the same true anomaly
+		let mut database = Database::<u16, f64>::default().with_solar_system().with_coordinate_convention(CoordinateConvention::ZUp);
+		database.add_entry(1000, DatabaseEntry::new(Body::default(), "Satellite").with_parent(HANDLE_URANUS, orbit));
+		let time = 12_345.0;
+		let expected = orbit.true_anomaly_at_mean_anomaly(database.mean_anomaly_at_time(&1000, time));
+		let position = database.position_at_time(&1000, time);
+		let found = database.true_anomaly_at_position(&1000, position).unwrap();
+		let difference = (found - expected + std::f64::consts::PI).rem_euclid(std::f64::consts::TAU) - std::f64::consts::PI;
+		assert_ulps_eq!(0.0, difference, epsilon=1e-9);
+		assert_eq!(None, database.true_anomaly_at_position(&HANDLE_SOL, position));
+	}
+
+	#[test]
+	fn add_trojan() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let asteroid = || Body::default().with_mass_kg(1e18).with_radius_km(50.0);
+		assert_eq!(Ok(()), database.add_trojan(1000, &HANDLE_JUPITER, true, asteroid(), "Hektor"));
+		assert_eq!(Ok(()), database.add_trojan(1001, &HANDLE_JUPITER, false, asteroid(), "Patroclus"));
+		assert_eq!(Err(OrbitError::DuplicateHandle(1000)), database.add_trojan(1000, &HANDLE_JUPITER, true, asteroid(), "Hektor"));
+		assert_eq!(Err(OrbitError::NoOrbit(HANDLE_SOL)), database.add_trojan(1002, &HANDLE_SOL, true, asteroid(), "Nothing"));
+		assert_eq!(Err(OrbitError::UnknownBody(2000)), database.add_trojan(1002, &2000, true, asteroid(), "Nothing"));
+		let period = database.orbital_period_s(&HANDLE_JUPITER).unwrap();
+		assert_ulps_eq!(period, database.orbital_period_s(&1000).unwrap());
+		for step in 0..20 {
+			let time = step as f64 * period / 7.0;
+			let lead = database.mean_longitude_at_time(&1000, time) - database.mean_longitude_at_time(&HANDLE_JUPITER, time);
+			let trail = database.mean_longitude_at_time(&1001, time) - database.mean_longitude_at_time(&HANDLE_JUPITER, time);
+			assert_ulps_eq!(60.0, lead.to_degrees().rem_euclid(360.0), epsilon=1e-6);
+			assert_ulps_eq!(300.0, trail.to_degrees().rem_euclid(360.0), epsilon=1e-6);
+			// Jupiter's orbit is only slightly eccentric so the actual angle stays close to 60°
+			let jupiter = database.position_at_time(&HANDLE_JUPITER, time);
+			let angle = jupiter.angle(&database.position_at_time(&1000, time)).to_degrees();
+			assert!((angle - 60.0).abs() < 7.0, "Trojan is {}° from Jupiter", angle);
+		}
+	}
+
+	#[test]
+	fn roche_lobe_radius() {
+		let mut database = Database::<u16, f64>::default().with_solar_system();
+		let charon_orbit = OrbitalElements::default().with_semimajor_axis_km(19_591.0);
+		database.add_entry(1000, DatabaseEntry::new(Body::default().with_mass_kg(1.586e21), "Charon").with_parent(HANDLE_PLUTO, charon_orbit));
+		let (pluto_lobe, charon_lobe) = database.roche_lobe_radius(&HANDLE_PLUTO, &1000).unwrap();
+		assert!(pluto_lobe > charon_lobe);
+		assert!(pluto_lobe + charon_lobe < 19_591_000.0);
+		// equal masses split the separation evenly
+		let twin_orbit = OrbitalElements::default().with_semimajor_axis_km(1000.0);
+		database.add_entry(1001, DatabaseEntry::new(Body::default().with_mass_kg(1e20), "Twin A"));
+		database.add_entry(1002, DatabaseEntry::new(Body::default().with_mass_kg(1e20), "Twin B").with_parent(1001, twin_orbit));
+		let (lobe_a, lobe_b) = database.roche_lobe_radius(&1001, &1002).unwrap();
+		assert_eq!(lobe_a, lobe_b);
+		assert_ulps_eq!(378_900.0, lobe_a, epsilon=100.0);
+		assert_eq!(None, database.roche_lobe_radius(&1000, &HANDLE_PLUTO));
+		assert_eq!(None, database.roche_lobe_radius(&HANDLE_EARTH, &HANDLE_MARS));
+	}
 }
\ No newline at end of file
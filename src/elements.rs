@@ -1,6 +1,19 @@
-use std::ops::SubAssign;
+use std::{f64::consts::TAU, fmt::Display, ops::SubAssign};
+use nalgebra::{Matrix4, RealField, Rotation3, Vector3};
 use num_traits::{Float, FromPrimitive};
-use crate::constants::f64::*;
+use crate::{constants::f64::*, Body};
+
+/// Which kind of orbital elements a set of [`OrbitalElements`] holds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ElementType {
+	/// Instantaneous elements of the pure two-body orbit that matches a position and velocity,
+	/// like the ones from [`OrbitalElements::from_state_vectors`]
+	#[default]
+	Osculating,
+	/// Mean elements with the short-periodic effects of the parent's *J2* oblateness averaged out,
+	/// like the ones published in TLEs
+	MeanJ2,
+}
 
 /// Keplerian elements that define an orbit
 #[derive(Clone, Copy)]
@@ -17,6 +30,8 @@ pub struct OrbitalElements<T> {
     pub time_of_periapsis_passage: T,
     /// Longitude of Ascending Node, *Ω*
     pub long_of_ascending_node: T,
+	/// Whether these are osculating or mean elements
+	pub element_type: ElementType,
 }
 impl<T> OrbitalElements<T> where T: Float + FromPrimitive + SubAssign {
 	/// Sets the orbit's semimajor axis *a* in kilometers (km)
@@ -32,6 +47,78 @@ impl<T> OrbitalElements<T> where T: Float + FromPrimitive + SubAssign {
 		self.semimajor_axis = a;
 		self
 	}
+	/// Sets the orbit's semimajor axis *a* from its orbital period in seconds (s) around a parent
+	/// with the given *GM* in m³/s²
+	/// 
+	/// Inverts Kepler's third law, *a = cbrt(GM·T²/4π²)*. See
+	/// [`Database::add_body_with_period`](crate::Database::add_body_with_period) to use the *GM*
+	/// of a parent that's already in a database.
+	pub fn with_period_s(mut self, period: T, parent_gm: T) -> Self {
+		let four_pi_squared = T::from_f64(4.0 * std::f64::consts::PI * std::f64::consts::PI).unwrap();
+		self.semimajor_axis = Float::cbrt(parent_gm * period * period / four_pi_squared);
+		self
+	}
+	/// Sets the orbit's semimajor axis *a* in light-seconds
+	pub fn with_semimajor_axis_light_seconds(mut self, a: T) -> Self {
+		self.semimajor_axis = a * T::from_f64(CONVERT_LIGHT_SECOND_TO_M).unwrap();
+		self
+	}
+	/// Sets the orbit's semimajor axis *a* in light-years
+	pub fn with_semimajor_axis_light_years(mut self, a: T) -> Self {
+		self.semimajor_axis = a * T::from_f64(CONVERT_LIGHT_YEAR_TO_M).unwrap();
+		self
+	}
+	/// Gets the orbit's semimajor axis *a* in kilometers (km)
+	pub fn semimajor_axis_km(&self) -> T {
+		self.semimajor_axis * T::from_f64(CONVERT_M_TO_KM).unwrap()
+	}
+	/// Gets the orbit's semimajor axis *a* in astronomical units (AU)
+	pub fn semimajor_axis_au(&self) -> T {
+		self.semimajor_axis * T::from_f64(CONVERT_M_TO_AU).unwrap()
+	}
+	/// Formats the orbit's size, shape and orientation as one row of a table, for debugging
+	/// 
+	/// The row is `| name | parent | a AU | e | i | ω | Ω |`, with the columns padded so that rows
+	/// for different bodies line up.
+	pub fn elements_table_row(&self, name: &str, parent_name: &str) -> String where T: Display {
+		let rad_to_deg = T::from_f64(CONVERT_RAD_TO_DEG).unwrap();
+		format!(
+			"| {:20} | {:12} | {:8.4} AU | e={:6.4} | i={:6.2}° | ω={:6.2}° | Ω={:6.2}° |",
+			name, parent_name, self.semimajor_axis_au(), self.eccentricity,
+			self.inclination * rad_to_deg, self.arg_of_periapsis * rad_to_deg, self.long_of_ascending_node * rad_to_deg,
+		)
+	}
+	/// Finds the true anomalies *ν* at which the orbit is at the given distance `r` from its parent,
+	/// in meters (m)
+	/// 
+	/// Returns the `(outbound, inbound)` pair of true anomalies, where the outbound one is in
+	/// `[0, π]` and the inbound one is `2π` minus the outbound one. Returns `None` if `r` is below
+	/// the periapsis or above the apoapsis of the orbit. Every point on a circular orbit is at the
+	/// same radius, so those give `(0, 2π)` when `r` matches the orbit's radius.
+	/// 
+	/// Solves *r = p / (1 + e cos ν)* for *ν*, with *p* the semi-latus rectum.
+	pub fn true_anomaly_at_radius(&self, r: T) -> Option<(T, T)> {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let tau = T::from_f64(TAU).unwrap();
+		let e = self.eccentricity;
+		if e <= T::epsilon() {
+			let tolerance = Float::abs(self.semimajor_axis) * T::epsilon() * T::from_f32(16.0).unwrap();
+			return if Float::abs(r - self.semimajor_axis) <= tolerance { Some((zero, tau)) } else { None };
+		}
+		let semilatus_rectum = self.semimajor_axis * (one - e * e);
+		let cos_true_anomaly = (semilatus_rectum / r - one) / e;
+		if r <= zero || cos_true_anomaly > one || cos_true_anomaly < -one {
+			return None;
+		}
+		let outbound = Float::acos(cos_true_anomaly);
+		Some((outbound, tau - outbound))
+	}
+	/// Sets whether the orbit's elements are osculating or mean elements
+	pub fn with_element_type(mut self, element_type: ElementType) -> Self {
+		self.element_type = element_type;
+		self
+	}
 	/// Sets the orbit's eccentricity
 	pub fn with_eccentricity(mut self, e: T) -> Self {
 		self.eccentricity = e;
@@ -64,6 +151,356 @@ impl<T> OrbitalElements<T> where T: Float + FromPrimitive + SubAssign {
 		}
 		self
 	}
+	/// Creates an orbit from its semimajor axis *a* in kilometers (km), eccentricity *e*,
+	/// inclination *i*, argument of periapsis *ω* and longitude of ascending node *Ω* in degrees,
+	/// and time of periapsis passage *T* in seconds (s)
+	/// 
+	/// This is the same as chaining [`with_semimajor_axis_km`](Self::with_semimajor_axis_km),
+	/// [`with_eccentricity`](Self::with_eccentricity) and the other `with_*` builders onto the
+	/// default elements, for when all the elements are at hand in the units they're usually
+	/// published in.
+	pub fn from_degrees_km(a_km: T, e: T, i_deg: T, arg_peri_deg: T, lan_deg: T, time_of_passage: T) -> Self {
+		let mut elements = Self::default()
+			.with_semimajor_axis_km(a_km)
+			.with_eccentricity(e)
+			.with_inclination_deg(i_deg)
+			.with_arg_of_periapsis_deg(arg_peri_deg)
+			.with_long_of_ascending_node_deg(lan_deg);
+		elements.time_of_periapsis_passage = time_of_passage;
+		elements
+	}
+	/// Gets the orbit's semimajor axis *a* in kilometers (km), eccentricity *e*, and inclination
+	/// *i*, argument of periapsis *ω* and longitude of ascending node *Ω* in degrees, the same
+	/// values taken by [`from_degrees_km`](Self::from_degrees_km)
+	/// 
+	/// The angles are wrapped into `[0°, 360°)`, so an inclination set as -10° comes back as 350°,
+	/// which describes the same orbit. Handy for showing an orbit in an inspector and building it
+	/// again from the edited values.
+	pub fn as_degrees(&self) -> (T, T, T, T, T) {
+		let circle = T::from_f32(360.0).unwrap();
+		let to_degrees = |rad: T| {
+			let deg = (rad * T::from_f64(CONVERT_RAD_TO_DEG).unwrap()) % circle;
+			if deg < T::from_f32(0.0).unwrap() { deg + circle } else { deg }
+		};
+		(
+			self.semimajor_axis_km(), self.eccentricity,
+			to_degrees(self.inclination), to_degrees(self.arg_of_periapsis), to_degrees(self.long_of_ascending_node),
+		)
+	}
+	/// Blends between these elements at `t = 0` and `other` at `t = 1`, for scripted orbit
+	/// changes like a comet gradually shifting onto a new orbit
+	/// 
+	/// Each element is interpolated linearly, except that the argument of periapsis *ω* and the
+	/// longitude of the ascending node *Ω* go the short way around the circle, so blending from
+	/// 350° to 10° passes through 0° rather than 180°, and come back wrapped into `[0, 2π)`. The
+	/// element type switches over halfway.
+	/// The time of periapsis passage is blended like everything else, so if the two orbits have
+	/// very different periods, keep `t` changing slowly to avoid the body jumping along its orbit.
+	pub fn lerp(&self, other: &Self, t: T) -> Self {
+		let one = T::from_f32(1.0).unwrap();
+		let zero = T::from_f32(0.0).unwrap();
+		let half = T::from_f32(0.5).unwrap();
+		let pi = T::from_f64(TAU / 2.0).unwrap();
+		let tau = T::from_f64(TAU).unwrap();
+		let blend = |a: T, b: T| a * (one - t) + b * t;
+		let wrap = |angle: T| {
+			let angle = angle % tau;
+			let angle = if angle < zero { angle + tau } else { angle };
+			// a tiny negative angle rounds up to a whole turn when shifted
+			if angle >= tau { zero } else { angle }
+		};
+		let blend_angle = |a: T, b: T| {
+			let difference = (b - a) % tau;
+			let difference = if difference > pi { difference - tau } else if difference < -pi { difference + tau } else { difference };
+			// step from whichever end is closer so that both ends come back exactly
+			wrap(if t < half { a + difference * t } else { b - difference * (one - t) })
+		};
+		Self{
+			semimajor_axis: blend(self.semimajor_axis, other.semimajor_axis),
+			eccentricity: blend(self.eccentricity, other.eccentricity),
+			inclination: blend(self.inclination, other.inclination),
+			arg_of_periapsis: blend_angle(self.arg_of_periapsis, other.arg_of_periapsis),
+			time_of_periapsis_passage: blend(self.time_of_periapsis_passage, other.time_of_periapsis_passage),
+			long_of_ascending_node: blend_angle(self.long_of_ascending_node, other.long_of_ascending_node),
+			element_type: if t < T::from_f64(0.5).unwrap() { self.element_type } else { other.element_type },
+		}
+	}
+}
+impl<T> OrbitalElements<T> where T: Float + FromPrimitive + SubAssign + RealField {
+	/// Derives the orbital elements from a position and velocity relative to the parent body
+	/// 
+	/// `position` is in meters (m), `velocity` in meters per second (m/s) and `parent_gm` is the
+	/// parent body's *GM*. `time` is the time at which the state vectors were observed, and is
+	/// used to calculate the time of periapsis passage *T*.
+	/// 
+	/// Uses the same reference frame as the rest of the library: the reference plane is the X/Z
+	/// plane with +Y pointing "up", and the longitude of the ascending node is measured from the
+	/// +X axis. Equatorial orbits have their ascending node placed on the +X axis, and circular
+	/// orbits have their periapsis placed at the ascending node.
+	pub fn from_state_vectors(position: Vector3<T>, velocity: Vector3<T>, parent_gm: T, time: T) -> Self {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let tau = T::from_f64(TAU).unwrap();
+		let epsilon = T::from_f64(1e-9).unwrap();
+		let up = Vector3::new(zero, one, zero);
+		let x_axis = Vector3::new(one, zero, zero);
+		let r = position.norm();
+		let v = velocity.norm();
+		let h = position.cross(&velocity);
+		let h_dir = h.normalize();
+		let node = up.cross(&h);
+		let node_dir = if node.norm() / h.norm() < epsilon { x_axis } else { node.normalize() };
+		let e_vec = (position * (v * v - parent_gm / r) - velocity * position.dot(&velocity)) / parent_gm;
+		let e = e_vec.norm();
+		let energy = v * v / two - parent_gm / r;
+		let semimajor_axis = -parent_gm / (two * energy);
+		let inclination = Float::acos(Float::max(-one, Float::min(one, h_dir.y)));
+		let long_of_ascending_node = Float::atan2(-node_dir.z, node_dir.x);
+		let (arg_of_periapsis, true_anomaly) = if e < epsilon {
+			(zero, Float::atan2(h_dir.dot(&node_dir.cross(&position)), node_dir.dot(&position)))
+		} else {
+			(
+				Float::atan2(h_dir.dot(&node_dir.cross(&e_vec)), node_dir.dot(&e_vec)),
+				Float::atan2(h_dir.dot(&e_vec.cross(&position)), e_vec.dot(&position)),
+			)
+		};
+		let wrap = |angle: T| if angle < zero { angle + tau } else { angle };
+		let mut elements = Self {
+			semimajor_axis,
+			eccentricity: e,
+			inclination,
+			arg_of_periapsis: wrap(arg_of_periapsis),
+			time_of_periapsis_passage: zero,
+			long_of_ascending_node: wrap(long_of_ascending_node),
+			element_type: ElementType::Osculating,
+		};
+		let n = mean_motion(parent_gm, semimajor_axis);
+		let mean_anomaly = elements.mean_anomaly_at_true_anomaly(true_anomaly);
+		elements.time_of_periapsis_passage = time - mean_anomaly / n;
+		elements
+	}
+	/// Estimates an orbit from three position observations relative to the parent body
+	/// 
+	/// Positions are in meters (m) and must be given in chronological order. Uses the Gibbs method
+	/// to find the velocity at the second observation, falling back to the Herrick-Gibbs
+	/// approximation when the observations are too closely spaced for Gibbs to be accurate, then
+	/// derives the orbit from the resulting state vectors. Returns `None` if the observations
+	/// aren't coplanar or are otherwise degenerate.
+	/// 
+	/// This needs full position vectors, like ranged radar or tracking data. Angles-only
+	/// observations from a telescope need Gauss's method instead, which isn't implemented.
+	/// 
+	/// See chapter 7 of *Fundamentals of Astrodynamics and Applications* by David A. Vallado
+	pub fn gibbs_iod(r1: Vector3<T>, t1: T, r2: Vector3<T>, t2: T, r3: Vector3<T>, t3: T, parent_gm: T) -> Option<Self> {
+		let zero = T::from_f32(0.0).unwrap();
+		let twelve = T::from_f32(12.0).unwrap();
+		let coplanar_tolerance = T::from_f64(2.0 * CONVERT_DEG_TO_RAD).unwrap();
+		let gibbs_min_angle = T::from_f64(5.0 * CONVERT_DEG_TO_RAD).unwrap();
+		if !(t1 < t2 && t2 < t3) {
+			return None;
+		}
+		let (r1_mag, r2_mag, r3_mag) = (r1.norm(), r2.norm(), r3.norm());
+		if r1_mag <= zero || r2_mag <= zero || r3_mag <= zero {
+			return None;
+		}
+		let c12 = r1.cross(&r2);
+		let c23 = r2.cross(&r3);
+		let c31 = r3.cross(&r1);
+		if c23.norm() <= zero || Float::abs(c23.normalize().dot(&(r1 / r1_mag))) > Float::sin(coplanar_tolerance) {
+			return None;
+		}
+		let angle_12 = Float::atan2(c12.norm(), r1.dot(&r2));
+		let angle_23 = Float::atan2(c23.norm(), r2.dot(&r3));
+		let v2 = if angle_12 > gibbs_min_angle && angle_23 > gibbs_min_angle {
+			// Gibbs method
+			let n = c23 * r1_mag + c31 * r2_mag + c12 * r3_mag;
+			let d = c12 + c23 + c31;
+			let s = r1 * (r2_mag - r3_mag) + r2 * (r3_mag - r1_mag) + r3 * (r1_mag - r2_mag);
+			let nd = n.norm() * d.norm();
+			if nd <= zero {
+				return None;
+			}
+			(d.cross(&r2) / r2_mag + s) * Float::sqrt(parent_gm / nd)
+		} else {
+			// Herrick-Gibbs approximation
+			let dt21 = t2 - t1;
+			let dt32 = t3 - t2;
+			let dt31 = t3 - t1;
+			let one = T::from_f32(1.0).unwrap();
+			r1 * (-dt32 * (one / (dt21 * dt31) + parent_gm / (twelve * Float::powi(r1_mag, 3))))
+				+ r2 * ((dt32 - dt21) * (one / (dt21 * dt32) + parent_gm / (twelve * Float::powi(r2_mag, 3))))
+				+ r3 * (dt21 * (one / (dt32 * dt31) + parent_gm / (twelve * Float::powi(r3_mag, 3))))
+		};
+		let elements = Self::from_state_vectors(r2, v2, parent_gm, t2);
+		if Float::is_finite(elements.semimajor_axis) && Float::is_finite(elements.eccentricity) {
+			Some(elements)
+		} else {
+			None
+		}
+	}
+	/// Converts osculating elements at the given time to mean elements, removing the first order
+	/// short-periodic effect of the parent `body`'s *J2* oblateness
+	/// 
+	/// Only the short-periodic term in the semimajor axis is applied, since that's by far the
+	/// largest contribution to position errors, and the mean anomaly at `time` is kept the same.
+	/// Elements that are already mean elements are returned unchanged. Use
+	/// [`to_osculating_elements`](Self::to_osculating_elements) to go the other way.
+	/// 
+	/// See Kozai, *The Motion of a Close Earth Satellite* (1959)
+	pub fn to_mean_elements(&self, body: &Body<T>, j2: T, time: T) -> Self {
+		if self.element_type == ElementType::MeanJ2 {
+			return *self;
+		}
+		let correction = self.j2_semimajor_axis_correction(body, j2, time);
+		self.with_j2_correction(body, -correction, time, ElementType::MeanJ2)
+	}
+	/// Converts mean elements at the given time to osculating elements, adding back the first
+	/// order short-periodic effect of the parent `body`'s *J2* oblateness
+	/// 
+	/// This is the inverse of [`to_mean_elements`](Self::to_mean_elements). Elements that are
+	/// already osculating elements are returned unchanged.
+	pub fn to_osculating_elements(&self, body: &Body<T>, j2: T, time: T) -> Self {
+		if self.element_type == ElementType::Osculating {
+			return *self;
+		}
+		let correction = self.j2_semimajor_axis_correction(body, j2, time);
+		self.with_j2_correction(body, correction, time, ElementType::Osculating)
+	}
+	/// Short-periodic difference between the osculating and mean semimajor axis from *J2*,
+	/// *Δa = (J2·R²/a)·[(1 - 3/2·sin²i)·((a/r)³ - (1-e²)^(-3/2)) + 3/2·sin²i·(a/r)³·cos(2ω + 2ν)]*
+	fn j2_semimajor_axis_correction(&self, body: &Body<T>, j2: T, time: T) -> T {
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let three_halves = T::from_f64(1.5).unwrap();
+		let a = self.semimajor_axis;
+		let e = self.eccentricity;
+		let n = mean_motion(body.gm(), a);
+		let true_anomaly = self.true_anomaly_at_mean_anomaly(n * (time - self.time_of_periapsis_passage));
+		let a_over_r_cubed = Float::powi((one + e * Float::cos(true_anomaly)) / (one - e * e), 3);
+		let sin_squared_i = Float::powi(Float::sin(self.inclination), 2);
+		let radius = body.radius_equator_m();
+		j2 * radius * radius / a * (
+			(one - three_halves * sin_squared_i) * (a_over_r_cubed - Float::powf(one - e * e, -three_halves))
+			+ three_halves * sin_squared_i * a_over_r_cubed * Float::cos(two * (self.arg_of_periapsis + true_anomaly))
+		)
+	}
+	/// Applies a change to the semimajor axis, keeping the mean anomaly at `time` the same
+	fn with_j2_correction(&self, body: &Body<T>, semimajor_axis_change: T, time: T, element_type: ElementType) -> Self {
+		let n = mean_motion(body.gm(), self.semimajor_axis);
+		let mean_anomaly = n * (time - self.time_of_periapsis_passage);
+		let mut elements = self.with_semimajor_axis_m(self.semimajor_axis + semimajor_axis_change).with_element_type(element_type);
+		let corrected_n = mean_motion(body.gm(), elements.semimajor_axis);
+		elements.time_of_periapsis_passage = time - mean_anomaly / corrected_n;
+		elements
+	}
+	/// Gets the position relative to the parent body at the given true anomaly *ν*, in meters (m)
+	/// 
+	/// The returned position is in the parent's reference plane, before any rotation from the
+	/// parent's axial tilt is applied.
+	pub fn position_at_true_anomaly(&self, true_anomaly: T) -> Vector3<T> {
+		let one = T::from_f32(1.0).unwrap();
+		let radius = self.semimajor_axis * (one - Float::powi(self.eccentricity, 2)) / (one + self.eccentricity * Float::cos(true_anomaly));
+		self.direction_at_true_anomaly(true_anomaly) * radius
+	}
+	/// Gets the velocity relative to the parent body at the given true anomaly *ν*, in meters per
+	/// second (m/s)
+	/// 
+	/// Like [`position_at_true_anomaly`](Self::position_at_true_anomaly), this is in the parent's
+	/// reference plane before any rotation from the parent's axial tilt is applied.
+	pub fn velocity_at_true_anomaly(&self, true_anomaly: T, parent_gm: T) -> Vector3<T> {
+		let one = T::from_f32(1.0).unwrap();
+		let half_pi = T::from_f64(std::f64::consts::FRAC_PI_2).unwrap();
+		let semilatus_rectum = self.semimajor_axis * (one - Float::powi(self.eccentricity, 2));
+		let dir_periapsis = self.direction_at_true_anomaly(T::from_f32(0.0).unwrap());
+		let dir_semilatus_rectum = self.direction_at_true_anomaly(half_pi);
+		let speed_factor = Float::sqrt(parent_gm / semilatus_rectum);
+		(dir_periapsis * -Float::sin(true_anomaly) + dir_semilatus_rectum * (self.eccentricity + Float::cos(true_anomaly))) * speed_factor
+	}
+	/// Gets a transform that turns a unit circle into this orbit's ellipse, for drawing every orbit
+	/// with a single shared circle mesh
+	/// 
+	/// The unit circle is expected in the X/Z plane around the origin, with the point at +X
+	/// becoming the periapsis and the circle running counter-clockwise around +Y in the direction
+	/// of motion. The circle is scaled to the semi-major and semi-minor axes, shifted so the parent
+	/// body sits at the focus, and then rotated by *Ω*, *ω* and *i*. The Y axis isn't scaled.
+	pub fn to_orbit_matrix(&self) -> Matrix4<T> {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let a = self.semimajor_axis;
+		let b = a * Float::sqrt(one - Float::powi(self.eccentricity, 2));
+		let scale = Matrix4::new_nonuniform_scaling(&Vector3::new(a, one, b));
+		let focus_offset = Matrix4::new_translation(&Vector3::new(-a * self.eccentricity, zero, zero));
+		self.orientation().to_homogeneous() * focus_offset * scale
+	}
+	/// Unit vector pointing from the parent body towards the point on the orbit at the given true
+	/// anomaly *ν*
+	fn direction_at_true_anomaly(&self, true_anomaly: T) -> Vector3<T> {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let x_axis = Vector3::new(one, zero, zero);
+		let y_axis = Vector3::new(zero, one, zero);
+		self.orientation() * Rotation3::new(y_axis * true_anomaly) * x_axis
+	}
+	/// Rotation from the orbit's own frame, with the periapsis along +X and the orbit normal along
+	/// +Y, to the parent's reference plane
+	fn orientation(&self) -> Rotation3<T> {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		let x_axis = Vector3::new(one, zero, zero);
+		let y_axis = Vector3::new(zero, one, zero);
+		let rot_long_of_ascending_node = Rotation3::new(y_axis * self.long_of_ascending_node);
+		let dir_ascending_node = rot_long_of_ascending_node * x_axis;
+		let rot_inclination = Rotation3::new(dir_ascending_node * self.inclination);
+		let rot_in_plane = Rotation3::new(y_axis * (self.long_of_ascending_node + self.arg_of_periapsis));
+		rot_inclination * rot_in_plane
+	}
+	/// Converts a mean anomaly *M* to the corresponding true anomaly *ν* for this orbit
+	/// 
+	/// Solves Kepler's equation iteratively with Newton's method, so unlike the series
+	/// approximation this stays accurate for highly eccentric and hyperbolic orbits.
+	pub fn true_anomaly_at_mean_anomaly(&self, mean_anomaly: T) -> T {
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let tolerance = T::from_f64(1e-12).unwrap();
+		let e = self.eccentricity;
+		if e < one {
+			let mut eccentric_anomaly = if e > T::from_f64(0.8).unwrap() { T::from_f64(std::f64::consts::PI).unwrap() } else { mean_anomaly };
+			for _ in 0..50 {
+				let delta = (eccentric_anomaly - e * Float::sin(eccentric_anomaly) - mean_anomaly) / (one - e * Float::cos(eccentric_anomaly));
+				eccentric_anomaly -= delta;
+				if Float::abs(delta) < tolerance {
+					break;
+				}
+			}
+			two * Float::atan2(Float::sqrt(one + e) * Float::sin(eccentric_anomaly / two), Float::sqrt(one - e) * Float::cos(eccentric_anomaly / two))
+		} else {
+			let mut hyperbolic_anomaly = Float::asinh(mean_anomaly / e);
+			for _ in 0..50 {
+				let delta = (e * Float::sinh(hyperbolic_anomaly) - hyperbolic_anomaly - mean_anomaly) / (e * Float::cosh(hyperbolic_anomaly) - one);
+				hyperbolic_anomaly -= delta;
+				if Float::abs(delta) < tolerance {
+					break;
+				}
+			}
+			two * Float::atan(Float::sqrt((e + one) / (e - one)) * Float::tanh(hyperbolic_anomaly / two))
+		}
+	}
+	/// Converts a true anomaly *ν* to the corresponding mean anomaly *M* for this orbit
+	/// 
+	/// Handles both elliptic and hyperbolic orbits.
+	pub fn mean_anomaly_at_true_anomaly(&self, true_anomaly: T) -> T {
+		let one = T::from_f32(1.0).unwrap();
+		let two = T::from_f32(2.0).unwrap();
+		let e = self.eccentricity;
+		if e < one {
+			let eccentric_anomaly = two * Float::atan2(Float::sqrt(one - e) * Float::sin(true_anomaly / two), Float::sqrt(one + e) * Float::cos(true_anomaly / two));
+			eccentric_anomaly - e * Float::sin(eccentric_anomaly)
+		} else {
+			let hyperbolic_anomaly = two * Float::atanh(Float::sqrt((e - one) / (e + one)) * Float::tan(true_anomaly / two));
+			e * Float::sinh(hyperbolic_anomaly) - hyperbolic_anomaly
+		}
+	}
 }
 impl<T> Default for OrbitalElements<T> where T: Copy + FromPrimitive {
 	fn default() -> Self {
@@ -75,6 +512,237 @@ impl<T> Default for OrbitalElements<T> where T: Copy + FromPrimitive {
 			arg_of_periapsis: zero,
 			time_of_periapsis_passage: zero,
 			long_of_ascending_node: zero,
+			element_type: ElementType::Osculating,
+		}
+	}
+}
+
+/// Calculates the mean motion *n = sqrt(GM/|a|³)* in radians per second of an orbit with the
+/// given semimajor axis in meters around a parent with the given *GM* in m³/s²
+/// 
+/// Takes the absolute value of the semimajor axis, so the negative semimajor axes of hyperbolic
+/// orbits give the rate of their hyperbolic mean anomaly.
+pub fn mean_motion<T: Float>(gm: T, semimajor_axis: T) -> T {
+	Float::sqrt(gm / Float::powi(Float::abs(semimajor_axis), 3))
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use approx::assert_relative_eq;
+	use nalgebra::Point3;
+
+	#[test]
+	fn with_period_s() {
+		let gm = 3.986004418e14;
+		let orbit = OrbitalElements::default().with_period_s(5400.0, gm);
+		assert_relative_eq!(5400.0, std::f64::consts::TAU / mean_motion(gm, orbit.semimajor_axis), max_relative=1e-12);
+	}
+
+	#[test]
+	fn from_degrees_km() {
+		let built = OrbitalElements::from_degrees_km(24_000.0, 0.2, 28.5, 270.0, 45.0, 1234.0);
+		let chained = OrbitalElements::default()
+			.with_semimajor_axis_km(24_000.0)
+			.with_eccentricity(0.2)
+			.with_inclination_deg(28.5)
+			.with_arg_of_periapsis_deg(270.0)
+			.with_long_of_ascending_node_deg(45.0);
+		assert_eq!(chained.semimajor_axis, built.semimajor_axis);
+		assert_eq!(chained.eccentricity, built.eccentricity);
+		assert_eq!(chained.inclination, built.inclination);
+		assert_eq!(chained.arg_of_periapsis, built.arg_of_periapsis);
+		assert_eq!(chained.long_of_ascending_node, built.long_of_ascending_node);
+		assert_eq!(1234.0, built.time_of_periapsis_passage);
+		assert_relative_eq!(0.497418836818384, built.inclination, max_relative=1e-12);
+	}
+
+	#[test]
+	fn as_degrees() {
+		let orbit = OrbitalElements::from_degrees_km(24_000.0, 0.2, 28.5, 270.0, 45.0, 0.0);
+		let (a_km, e, i_deg, arg_peri_deg, lan_deg) = orbit.as_degrees();
+		assert_relative_eq!(24_000.0, a_km, max_relative=1e-12);
+		assert_eq!(0.2, e);
+		assert_relative_eq!(28.5, i_deg, max_relative=1e-12);
+		assert_relative_eq!(270.0, arg_peri_deg, max_relative=1e-12);
+		assert_relative_eq!(45.0, lan_deg, max_relative=1e-12);
+		// negative and oversized angles come back wrapped, and build the same orbit again
+		let orbit = OrbitalElements::default().with_inclination_deg(-18.294).with_arg_of_periapsis_deg(400.0).with_long_of_ascending_node_deg(-11.26);
+		let (a_km, e, i_deg, arg_peri_deg, lan_deg) = orbit.as_degrees();
+		assert_relative_eq!(341.706, i_deg, max_relative=1e-12);
+		assert_relative_eq!(40.0, arg_peri_deg, max_relative=1e-12);
+		assert_relative_eq!(348.74, lan_deg, max_relative=1e-12);
+		let rebuilt = OrbitalElements::from_degrees_km(a_km, e, i_deg, arg_peri_deg, lan_deg, 0.0);
+		for true_anomaly in [0.0, 1.0, 2.0, 4.0] {
+			assert_relative_eq!(orbit.position_at_true_anomaly(true_anomaly), rebuilt.position_at_true_anomaly(true_anomaly), epsilon=1e-9);
 		}
 	}
-}
\ No newline at end of file
+
+	#[test]
+	fn lerp() {
+		let start = OrbitalElements::from_degrees_km(10_000.0, 0.1, 20.0, 350.0, 100.0, 0.0);
+		let end = OrbitalElements::from_degrees_km(30_000.0, 0.5, 40.0, 10.0, 300.0, 600.0).with_element_type(ElementType::MeanJ2);
+		for (expected, blended) in [(start, start.lerp(&end, 0.0)), (end, start.lerp(&end, 1.0))] {
+			assert_eq!(expected.semimajor_axis, blended.semimajor_axis);
+			assert_eq!(expected.eccentricity, blended.eccentricity);
+			assert_eq!(expected.inclination, blended.inclination);
+			assert_eq!(expected.arg_of_periapsis, blended.arg_of_periapsis);
+			assert_eq!(expected.long_of_ascending_node, blended.long_of_ascending_node);
+			assert_eq!(expected.time_of_periapsis_passage, blended.time_of_periapsis_passage);
+			assert_eq!(expected.element_type, blended.element_type);
+		}
+		// the angles always come back wrapped, even part way across 0°
+		for t in [0.1, 0.4, 0.5, 0.6, 0.9] {
+			let blended = start.lerp(&end, t);
+			assert!((0.0..TAU).contains(&blended.arg_of_periapsis), "ω = {} at t = {}", blended.arg_of_periapsis, t);
+			assert!((0.0..TAU).contains(&blended.long_of_ascending_node), "Ω = {} at t = {}", blended.long_of_ascending_node, t);
+		}
+		let halfway = start.lerp(&end, 0.5);
+		assert_relative_eq!(20_000.0, halfway.semimajor_axis_km(), max_relative=1e-12);
+		assert_relative_eq!(30.0_f64.to_radians(), halfway.inclination, max_relative=1e-12);
+		assert_eq!(ElementType::MeanJ2, halfway.element_type);
+		// ω crosses 0° on the way from 350° to 10°, and Ω goes from 100° to 300° through 20°
+		assert_relative_eq!(0.0, halfway.arg_of_periapsis.sin(), epsilon=1e-9);
+		assert_relative_eq!(1.0, halfway.arg_of_periapsis.cos(), epsilon=1e-9);
+		assert_relative_eq!(5.0_f64.to_radians(), start.lerp(&end, 0.75).arg_of_periapsis, max_relative=1e-9);
+		assert_relative_eq!(20.0_f64.to_radians(), halfway.long_of_ascending_node, max_relative=1e-9);
+		assert_eq!(ElementType::Osculating, start.lerp(&end, 0.25).element_type);
+	}
+
+	#[test]
+	fn mean_motion_hyperbolic() {
+		let gm = 3.986004418e14;
+		assert_relative_eq!(mean_motion(gm, 7_500_000.0), mean_motion(gm, -7_500_000.0));
+	}
+
+	#[test]
+	fn gibbs_iod() {
+		let earth: Body<f64> = Body::new_earth();
+		let gm = earth.gm();
+		let orbit: OrbitalElements<f64> = OrbitalElements::default()
+			.with_semimajor_axis_km(9_000.0)
+			.with_eccentricity(0.1)
+			.with_inclination_deg(30.0)
+			.with_arg_of_periapsis_deg(60.0)
+			.with_long_of_ascending_node_deg(45.0);
+		let n = (gm / orbit.semimajor_axis.powi(3)).sqrt();
+		let observations: Vec<(f64, Vector3<f64>)> = [0.3_f64, 0.9, 1.6].iter().map(|&true_anomaly| {
+			let time = orbit.mean_anomaly_at_true_anomaly(true_anomaly) / n;
+			(time, orbit.position_at_true_anomaly(true_anomaly))
+		}).collect();
+		let estimate = OrbitalElements::gibbs_iod(
+			observations[0].1, observations[0].0,
+			observations[1].1, observations[1].0,
+			observations[2].1, observations[2].0,
+			gm,
+		).unwrap();
+		let period = std::f64::consts::TAU / n;
+		assert_relative_eq!(orbit.semimajor_axis, estimate.semimajor_axis, max_relative=0.01);
+		assert_relative_eq!(orbit.eccentricity, estimate.eccentricity, max_relative=0.01);
+		assert_relative_eq!(orbit.inclination, estimate.inclination, max_relative=0.01);
+		assert_relative_eq!(orbit.arg_of_periapsis, estimate.arg_of_periapsis, max_relative=0.01);
+		assert_relative_eq!(orbit.long_of_ascending_node, estimate.long_of_ascending_node, max_relative=0.01);
+		assert_relative_eq!(orbit.time_of_periapsis_passage, estimate.time_of_periapsis_passage, epsilon=0.01 * period);
+	}
+
+	#[test]
+	fn semimajor_axis_units() {
+		let by_au: OrbitalElements<f64> = OrbitalElements::default().with_semimajor_axis_au(0.01);
+		let by_km: OrbitalElements<f64> = OrbitalElements::default().with_semimajor_axis_km(0.01 * CONVERT_AU_TO_KM);
+		assert_relative_eq!(by_km.semimajor_axis, by_au.semimajor_axis, max_relative=0.01);
+		assert_relative_eq!(0.01, by_au.semimajor_axis_au(), max_relative=1e-9);
+		assert_relative_eq!(0.01 * CONVERT_AU_TO_KM, by_au.semimajor_axis_km(), max_relative=1e-9);
+		// sunlight takes about 499 seconds to reach earth
+		let by_light_seconds: OrbitalElements<f64> = OrbitalElements::default().with_semimajor_axis_light_seconds(499.0);
+		assert_relative_eq!(1.0, by_light_seconds.semimajor_axis_au(), max_relative=0.001);
+		let by_light_years: OrbitalElements<f64> = OrbitalElements::default().with_semimajor_axis_light_years(1.0);
+		assert_relative_eq!(63_241.0, by_light_years.semimajor_axis_au(), max_relative=0.001);
+	}
+
+	#[test]
+	fn elements_table_row() {
+		let orbit: OrbitalElements<f64> = OrbitalElements::default()
+			.with_semimajor_axis_au(1.00000011)
+			.with_eccentricity(0.0167)
+			.with_inclination_deg(0.5)
+			.with_arg_of_periapsis_deg(114.2)
+			.with_long_of_ascending_node_deg(348.7);
+		let row = orbit.elements_table_row("Earth", "Sol");
+		assert_eq!("| Earth                | Sol          |   1.0000 AU | e=0.0167 | i=  0.50° | ω=114.20° | Ω=348.70° |", row);
+	}
+
+	#[test]
+	fn true_anomaly_at_radius() {
+		let circular: OrbitalElements<f64> = OrbitalElements::default().with_semimajor_axis_km(7_000.0);
+		assert_eq!(Some((0.0, TAU)), circular.true_anomaly_at_radius(7_000_000.0));
+		assert!(circular.true_anomaly_at_radius(6_999_000.0).is_none());
+		assert!(circular.true_anomaly_at_radius(7_001_000.0).is_none());
+		let eccentric = circular.with_eccentricity(0.2);
+		let periapsis = eccentric.semimajor_axis * 0.8;
+		let apoapsis = eccentric.semimajor_axis * 1.2;
+		assert!(eccentric.true_anomaly_at_radius(periapsis * 0.99).is_none());
+		assert!(eccentric.true_anomaly_at_radius(apoapsis * 1.01).is_none());
+		let radius = (periapsis + apoapsis) / 2.0;
+		let (outbound, inbound) = eccentric.true_anomaly_at_radius(radius).unwrap();
+		assert!(0.0 < outbound && outbound < std::f64::consts::PI);
+		assert_relative_eq!(TAU - outbound, inbound);
+		assert_relative_eq!(radius, eccentric.position_at_true_anomaly(outbound).norm(), max_relative=1e-9);
+		assert_relative_eq!(radius, eccentric.position_at_true_anomaly(inbound).norm(), max_relative=1e-9);
+	}
+
+	#[test]
+	fn to_orbit_matrix() {
+		let orbit: OrbitalElements<f64> = OrbitalElements::default()
+			.with_semimajor_axis_km(20_000.0)
+			.with_eccentricity(0.3)
+			.with_inclination_deg(25.0)
+			.with_arg_of_periapsis_deg(40.0)
+			.with_long_of_ascending_node_deg(110.0);
+		let matrix = orbit.to_orbit_matrix();
+		let periapsis = matrix.transform_point(&Point3::new(1.0, 0.0, 0.0));
+		assert_relative_eq!(orbit.position_at_true_anomaly(0.0), periapsis.coords, max_relative=1e-9);
+		let apoapsis = matrix.transform_point(&Point3::new(-1.0, 0.0, 0.0));
+		assert_relative_eq!(orbit.position_at_true_anomaly(std::f64::consts::PI), apoapsis.coords, max_relative=1e-9);
+		// a quarter of the way around the circle is where the eccentric anomaly is 90°
+		let eccentric_anomaly = std::f64::consts::FRAC_PI_2;
+		let true_anomaly = 2.0 * ((1.3_f64).sqrt() * (eccentric_anomaly / 2.0).sin()).atan2((0.7_f64).sqrt() * (eccentric_anomaly / 2.0).cos());
+		let point = matrix.transform_point(&Point3::new(0.0, 0.0, -1.0));
+		assert_relative_eq!(orbit.position_at_true_anomaly(true_anomaly), point.coords, max_relative=1e-9);
+	}
+
+	#[test]
+	fn mean_elements_round_trip() {
+		let earth: Body<f64> = Body::new_earth();
+		let gm = earth.gm();
+		let orbit: OrbitalElements<f64> = OrbitalElements::default()
+			.with_semimajor_axis_km(6_800.0)
+			.with_eccentricity(0.001)
+			.with_inclination_deg(51.6)
+			.with_arg_of_periapsis_deg(30.0)
+			.with_long_of_ascending_node_deg(80.0);
+		let time = 1_000.0;
+		let position = orbit.position_at_true_anomaly(0.7);
+		let velocity = orbit.velocity_at_true_anomaly(0.7, gm);
+		let osculating = OrbitalElements::from_state_vectors(position, velocity, gm, time);
+		let mean = osculating.to_mean_elements(&earth, J2_EARTH, time);
+		assert_eq!(ElementType::MeanJ2, mean.element_type);
+		let difference_km = (mean.semimajor_axis - osculating.semimajor_axis).abs() / 1000.0;
+		assert!(difference_km > 1.0, "Expected J2 to shift the semimajor axis by a few km, got {:.3} km", difference_km);
+		let round_trip = mean.to_osculating_elements(&earth, J2_EARTH, time);
+		assert_eq!(ElementType::Osculating, round_trip.element_type);
+		let n = (gm / round_trip.semimajor_axis.powi(3)).sqrt();
+		let true_anomaly = round_trip.true_anomaly_at_mean_anomaly(n * (time - round_trip.time_of_periapsis_passage));
+		let error_km = (round_trip.position_at_true_anomaly(true_anomaly) - position).norm() / 1000.0;
+		assert!(error_km < 1.0, "Expected the round trip to be within 1 km, was off by {:.3} km", error_km);
+	}
+
+	#[test]
+	fn gibbs_iod_rejects_unordered_observations() {
+		let orbit: OrbitalElements<f64> = OrbitalElements::default().with_semimajor_axis_km(9_000.0);
+		let r1 = orbit.position_at_true_anomaly(0.0);
+		let r2 = orbit.position_at_true_anomaly(1.0);
+		let r3 = orbit.position_at_true_anomaly(2.0);
+		assert!(OrbitalElements::gibbs_iod(r1, 2.0, r2, 1.0, r3, 3.0, 3.986e14).is_none());
+	}
+}
@@ -0,0 +1,56 @@
+//! Errors returned by the library
+use std::fmt::{Debug, Display, Formatter, Result};
+
+
+/// Errors from operations that add bodies to or change the orbits of bodies in a
+/// [`Database`](crate::Database)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrbitError<H> {
+	/// There's no body in the database with the given handle
+	UnknownBody(H),
+	/// The body with the given handle doesn't orbit anything
+	NoOrbit(H),
+	/// The resulting orbit would pass below the surface of the body's parent, given by its handle
+	OrbitIntersectsParentBody(H),
+	/// There's already a body in the database with the given handle
+	DuplicateHandle(H),
+	/// The body with the given handle isn't on a closed orbit
+	UnboundOrbit(H),
+	/// The body with the given handle leaves its parent's sphere of influence
+	OutsideParentSphereOfInfluence(H),
+	/// The spheres of influence of the two bodies with the given handles overlap
+	OverlappingSpheresOfInfluence(H, H),
+}
+impl<H> Display for OrbitError<H> where H: Debug {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+		match self {
+			Self::UnknownBody(handle) => write!(f, "No body in database with ID {:?}", handle),
+			Self::NoOrbit(handle) => write!(f, "Body {:?} doesn't orbit anything", handle),
+			Self::OrbitIntersectsParentBody(parent) => write!(f, "Orbit passes below the surface of parent body {:?}", parent),
+			Self::DuplicateHandle(handle) => write!(f, "Body with ID {:?} is already in the database", handle),
+			Self::UnboundOrbit(handle) => write!(f, "Body {:?} isn't on a closed orbit", handle),
+			Self::OutsideParentSphereOfInfluence(handle) => write!(f, "Body {:?} leaves its parent's sphere of influence", handle),
+			Self::OverlappingSpheresOfInfluence(a, b) => write!(f, "Spheres of influence of bodies {:?} and {:?} overlap", a, b),
+		}
+	}
+}
+impl<H> std::error::Error for OrbitError<H> where H: Debug {}
+
+/// Reasons [`Database::try_relative_position`](crate::Database::try_relative_position) can fail
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RelativePositionError<H> {
+	/// There's no body in the database with the given handle
+	UnknownHandle(H),
+	/// The two bodies with the given handles don't share a common ancestor, so there's no way to
+	/// measure one's position from the other
+	DifferentSystems(H, H),
+}
+impl<H> Display for RelativePositionError<H> where H: Debug {
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+		match self {
+			Self::UnknownHandle(handle) => write!(f, "No body in database with ID {:?}", handle),
+			Self::DifferentSystems(origin, relative) => write!(f, "Bodies {:?} and {:?} aren't part of the same system", origin, relative),
+		}
+	}
+}
+impl<H> std::error::Error for RelativePositionError<H> where H: Debug {}
@@ -1,8 +1,8 @@
-use std::{collections::hash_map::Iter, fmt::{Debug, Display}, hash::Hash};
+use std::{collections::{hash_map::Iter, HashMap}, fmt::{Debug, Display}, hash::Hash};
 use bevy::prelude::*;
-use nalgebra::Vector3;
-use num_traits::FromPrimitive;
-use crate::{Database, DatabaseEntry};
+use nalgebra::{Rotation3, UnitQuaternion, Vector3};
+use num_traits::{Float, FromPrimitive};
+use crate::{BodyClass, Database, DatabaseEntry, RelativePositionError};
 
 
 #[derive(Default, Resource)]
@@ -19,12 +19,18 @@ impl<H> BevyPlanetDatabase<H> where H: Clone + Debug + Display + Eq + Hash + Fro
 	pub fn get_parents(&self, handle: &H) -> Vec<H> {
 		self.database.get_parents(handle)
 	}
+	pub fn siblings(&self, handle: &H) -> Vec<H> {
+		self.database.siblings(handle)
+	}
 	pub fn position_at_mean_anomaly(&self, handle: &H, mean_anomaly: f32) -> Vec3 {
 		vec_nalgebra_to_bevy(self.database.position_at_mean_anomaly(handle, mean_anomaly))
 	}
     pub fn position_at_time(&self, handle: &H, time: f32) -> Vec3 {
         vec_nalgebra_to_bevy(self.database.position_at_time(handle, time))
     }
+	pub fn position_at_time_plus_anomaly(&self, handle: &H, time: f32, extra_anomaly: f32) -> Vec3 {
+		vec_nalgebra_to_bevy(self.database.position_at_time_plus_anomaly(handle, time, extra_anomaly))
+	}
     pub fn absolute_position_at_time(&self, handle: &H, time: f32) -> Vec3 {
         vec_nalgebra_to_bevy(self.database.absolute_position_at_time(handle, time))
     }
@@ -34,6 +40,20 @@ impl<H> BevyPlanetDatabase<H> where H: Clone + Debug + Display + Eq + Hash + Fro
 			None => None,
 		}
 	}
+	pub fn try_relative_position(&self, origin: &H, relative: &H, time: f32) -> Result<Vec3, RelativePositionError<H>> {
+		self.database.try_relative_position(origin, relative, time).map(vec_nalgebra_to_bevy)
+	}
+	pub fn all_positions_relative_to(&self, origin: &H, time: f32) -> HashMap<H, Vec3> {
+		self.database.all_positions_relative_to(origin, time).into_iter().map(|(handle, position)| (handle, vec_nalgebra_to_bevy(position))).collect()
+	}
+	pub fn frame_update(&self, origin: &H, time: f32) -> Vec<(H, Vec3, Quat)> {
+		self.database.frame_update(origin, time).into_iter()
+			.map(|(handle, position, orientation)| (handle, vec_nalgebra_to_bevy(position), rotation_nalgebra_to_bevy(orientation)))
+			.collect()
+	}
+	pub fn parent_position_relative_to(&self, handle: &H, origin: &H, time: f32) -> Option<Vec3> {
+		self.database.parent_position_relative_to(handle, origin, time).map(vec_nalgebra_to_bevy)
+	}
     pub fn radius_soi(&self, handle: &H) -> f32 {
         self.database.radius_soi(handle)
     }
@@ -44,6 +64,19 @@ impl<H> BevyPlanetDatabase<H> where H: Clone + Debug + Display + Eq + Hash + Fro
 	pub fn mean_anomaly_at_time(&self, handle: &H, time: f32) -> f32 {
 		self.database.mean_anomaly_at_time(handle, time)
 	}
+	pub fn orbital_period_s(&self, handle: &H) -> Option<f32> {
+		self.database.orbital_period_s(handle)
+	}
+	pub fn radius_hill_sphere(&self, handle: &H) -> f32 {
+		self.database.radius_hill_sphere(handle)
+	}
+	pub fn common_ancestor(&self, a: &H, b: &H) -> Option<H> {
+		self.database.common_ancestor(a, b)
+	}
+	/// Gets the wrapped database, for anything that isn't exposed by the wrapper directly
+	pub fn inner(&self) -> &Database<H, f32> {
+		&self.database
+	}
     pub fn iter(&self) -> Iter<'_, H, DatabaseEntry<H, f32>> {
         self.database.iter()
     }
@@ -52,3 +85,205 @@ impl<H> BevyPlanetDatabase<H> where H: Clone + Debug + Display + Eq + Hash + Fro
 pub fn vec_nalgebra_to_bevy(input: Vector3<f32>) -> Vec3 {
     Vec3::new(input.x, input.y, input.z)
 }
+
+pub fn rotation_nalgebra_to_bevy(input: Rotation3<f32>) -> Quat {
+	let quaternion = UnitQuaternion::from_rotation_matrix(&input);
+	Quat::from_xyzw(quaternion.i, quaternion.j, quaternion.k, quaternion.w)
+}
+
+
+impl<H, T> DatabaseEntry<H, T> where T: Float + FromPrimitive {
+	/// Suggests a color for drawing this body and its orbit, based on its
+	/// [`BodyClass`](crate::BodyClass)
+	pub fn suggested_color(&self) -> Color {
+		match self.info.body_class() {
+			BodyClass::Star => Color::srgb(1.0, 0.85, 0.3),
+			BodyClass::GasGiant => Color::srgb(0.95, 0.6, 0.3),
+			BodyClass::Terrestrial => Color::srgb(0.3, 0.65, 1.0),
+			BodyClass::Moon => Color::srgb(0.7, 0.7, 0.7),
+			BodyClass::SmallBody => Color::srgb(0.6, 0.45, 0.3),
+		}
+	}
+}
+
+
+/// Largest angle in radians an [`OrbitalCamera`] can pitch up or down, just short of looking
+/// straight down on its target
+const ORBITAL_CAMERA_MAX_PITCH: f32 = 1.55;
+
+/// Plugin that keeps entities with an [`OrbitalCamera`] pointed at a body in a
+/// [`BevyPlanetDatabase`] resource, and moves them around that body in response to
+/// [`CameraOrbitInput`] and [`CameraZoomInput`] events
+/// 
+/// The plugin doesn't read any input itself, so the events can be sent from a keyboard, mouse,
+/// gamepad or anything else. Bodies are placed relative to the origin body in the
+/// [`OrbitalCameraSettings`] resource, which the app should keep up to date with its current
+/// time.
+pub struct OrbitalCameraPlugin<H> {
+	settings: OrbitalCameraSettings<H>,
+}
+impl<H> OrbitalCameraPlugin<H> {
+	/// Creates the plugin with the given body at the world origin and the given number of world
+	/// units per meter
+	pub fn new(origin: H, scale: f32) -> Self {
+		Self{ settings: OrbitalCameraSettings{ origin, time_s: 0.0, scale, min_distance: 0.0, max_distance: f32::INFINITY } }
+	}
+	/// Limits how close to and far from their targets the cameras can zoom, in world units
+	pub fn with_distance_limits(mut self, min_distance: f32, max_distance: f32) -> Self {
+		self.settings.min_distance = min_distance;
+		self.settings.max_distance = max_distance;
+		self
+	}
+}
+impl<H> Plugin for OrbitalCameraPlugin<H> where H: Clone + Debug + Display + Eq + Hash + FromPrimitive + Ord + Send + Sync + 'static {
+	fn build(&self, app: &mut App) {
+		app.insert_resource(self.settings.clone())
+			.add_event::<CameraOrbitInput>()
+			.add_event::<CameraZoomInput>()
+			.add_systems(Update, (apply_orbital_camera_input::<H>, update_orbital_camera::<H>).chain());
+	}
+}
+
+/// Shared settings for every [`OrbitalCamera`], see [`OrbitalCameraPlugin`]
+#[derive(Clone, Resource)]
+pub struct OrbitalCameraSettings<H> {
+	/// Body at the world origin that the positions of other bodies are measured from
+	pub origin: H,
+	/// Time since epoch in seconds that bodies are placed at
+	pub time_s: f32,
+	/// World units per meter
+	pub scale: f32,
+	/// Closest a camera can zoom in to its target, in world units
+	pub min_distance: f32,
+	/// Furthest a camera can zoom out from its target, in world units
+	pub max_distance: f32,
+}
+
+/// Camera that circles around a body in the database, looking at it from `distance` world units
+/// away
+/// 
+/// `yaw` turns the camera around the +Y axis and `pitch` tilts it up or down from the X/Z plane,
+/// both in radians.
+#[derive(Clone, Component, Debug)]
+pub struct OrbitalCamera<H> {
+	pub target_handle: H,
+	pub distance: f32,
+	pub yaw: f32,
+	pub pitch: f32,
+}
+impl<H> OrbitalCamera<H> {
+	pub fn new(target_handle: H, distance: f32) -> Self {
+		Self{ target_handle, distance, yaw: 0.0, pitch: 0.0 }
+	}
+}
+
+/// Turns every [`OrbitalCamera`] around its target by the given angles in radians
+#[derive(Clone, Copy, Debug, Event)]
+pub struct CameraOrbitInput {
+	pub yaw: f32,
+	pub pitch: f32,
+}
+
+/// Zooms every [`OrbitalCamera`] in or out, multiplying its distance by *e* to the power of
+/// `amount`, so positive amounts zoom out and negative amounts zoom in
+#[derive(Clone, Copy, Debug, Event)]
+pub struct CameraZoomInput {
+	pub amount: f32,
+}
+
+/// Applies the [`CameraOrbitInput`] and [`CameraZoomInput`] events sent this frame to every
+/// [`OrbitalCamera`]
+pub fn apply_orbital_camera_input<H>(
+	mut orbit_inputs: EventReader<CameraOrbitInput>,
+	mut zoom_inputs: EventReader<CameraZoomInput>,
+	settings: Res<OrbitalCameraSettings<H>>,
+	mut cameras: Query<&mut OrbitalCamera<H>>,
+) where H: Send + Sync + 'static {
+	let (mut yaw, mut pitch) = (0.0, 0.0);
+	for input in orbit_inputs.read() {
+		yaw += input.yaw;
+		pitch += input.pitch;
+	}
+	let zoom: f32 = zoom_inputs.read().map(|input| input.amount).sum();
+	for mut camera in &mut cameras {
+		camera.yaw += yaw;
+		camera.pitch = (camera.pitch + pitch).clamp(-ORBITAL_CAMERA_MAX_PITCH, ORBITAL_CAMERA_MAX_PITCH);
+		camera.distance = (camera.distance * zoom.exp()).clamp(settings.min_distance, settings.max_distance);
+	}
+}
+
+/// Moves every [`OrbitalCamera`] to its place around its target and points it at the target
+pub fn update_orbital_camera<H>(
+	database: Res<BevyPlanetDatabase<H>>,
+	settings: Res<OrbitalCameraSettings<H>>,
+	mut cameras: Query<(&mut Transform, &OrbitalCamera<H>)>,
+) where H: Clone + Debug + Display + Eq + Hash + FromPrimitive + Ord + Send + Sync + 'static {
+	for (mut transform, camera) in &mut cameras {
+		let Some(target) = database.relative_position(&settings.origin, &camera.target_handle, settings.time_s) else {
+			continue;
+		};
+		let target = target * settings.scale;
+		let rotation = Quat::from_axis_angle(Vec3::Y, camera.yaw) * Quat::from_axis_angle(Vec3::X, camera.pitch);
+		transform.translation = target + rotation * -Vec3::Z * camera.distance;
+		transform.look_at(target, Vec3::Y);
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::handles::*;
+
+	#[test]
+	fn delegates_to_database() {
+		let database = BevyPlanetDatabase::<u16>::default().with_solar_system();
+		let inner = database.inner();
+		for handle in [HANDLE_EARTH, HANDLE_LUNA, HANDLE_PHOBOS] {
+			assert_eq!(inner.mean_anomaly_at_time(&handle, 1000.0), database.mean_anomaly_at_time(&handle, 1000.0));
+			assert_eq!(inner.orbital_period_s(&handle), database.orbital_period_s(&handle));
+			assert_eq!(inner.radius_hill_sphere(&handle), database.radius_hill_sphere(&handle));
+			assert_eq!(inner.get_parents(&handle), database.get_parents(&handle));
+		}
+		assert_eq!(inner.common_ancestor(&HANDLE_LUNA, &HANDLE_PHOBOS), database.common_ancestor(&HANDLE_LUNA, &HANDLE_PHOBOS));
+		assert_eq!(Some(HANDLE_SOL), database.common_ancestor(&HANDLE_LUNA, &HANDLE_PHOBOS));
+	}
+
+	#[test]
+	fn orbital_camera() {
+		let mut app = App::new();
+		app.insert_resource(BevyPlanetDatabase::<u16>::default().with_solar_system())
+			.add_plugins(OrbitalCameraPlugin::new(HANDLE_SOL, 1e-9).with_distance_limits(1.0, 100.0));
+		let camera = app.world_mut().spawn((Transform::default(), OrbitalCamera::new(HANDLE_EARTH, 10.0))).id();
+		app.update();
+		let earth = app.world().resource::<BevyPlanetDatabase<u16>>().relative_position(&HANDLE_SOL, &HANDLE_EARTH, 0.0).unwrap() * 1e-9;
+		let transform = *app.world().get::<Transform>(camera).unwrap();
+		assert!((transform.translation.distance(earth) - 10.0).abs() < 1e-3);
+		assert!(transform.forward().dot((earth - transform.translation).normalize()) > 0.9999);
+		// switching targets moves the camera over to the new target
+		app.world_mut().get_mut::<OrbitalCamera<u16>>(camera).unwrap().target_handle = HANDLE_MARS;
+		app.update();
+		let mars = app.world().resource::<BevyPlanetDatabase<u16>>().relative_position(&HANDLE_SOL, &HANDLE_MARS, 0.0).unwrap() * 1e-9;
+		let moved = *app.world().get::<Transform>(camera).unwrap();
+		assert_ne!(transform.translation, moved.translation);
+		assert!((moved.translation.distance(mars) - 10.0).abs() < 1e-3);
+		// input events turn and zoom the camera, within its limits
+		app.world_mut().send_event(CameraZoomInput{ amount: 10.0 });
+		app.world_mut().send_event(CameraOrbitInput{ yaw: 0.5, pitch: 3.0 });
+		app.update();
+		let orbital_camera = app.world().get::<OrbitalCamera<u16>>(camera).unwrap();
+		assert_eq!(100.0, orbital_camera.distance);
+		assert_eq!(0.5, orbital_camera.yaw);
+		assert_eq!(ORBITAL_CAMERA_MAX_PITCH, orbital_camera.pitch);
+	}
+
+	#[test]
+	fn suggested_color() {
+		let database = BevyPlanetDatabase::<u16>::default().with_solar_system();
+		let color = |handle| database.get_entry(&handle).suggested_color();
+		assert_eq!(Color::srgb(1.0, 0.85, 0.3), color(HANDLE_SOL));
+		assert_eq!(Color::srgb(0.7, 0.7, 0.7), color(HANDLE_LUNA));
+		assert_eq!(color(HANDLE_JUPITER), color(HANDLE_NEPTUNE));
+		assert_ne!(color(HANDLE_EARTH), color(HANDLE_JUPITER));
+	}
+}
@@ -0,0 +1,85 @@
+//! Debug inspector for a [`Database`] drawn with [egui](https://github.com/emilk/egui), for
+//! looking through the contents of a database without setting up a full game scene
+
+use std::{fmt::{Debug, Display, LowerExp}, hash::Hash, ops::SubAssign};
+use egui::{CollapsingHeader, Grid, Ui};
+use num_traits::{Float, FromPrimitive};
+use crate::{constants::f64::CONVERT_M_TO_KM, Database};
+
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+
+/// Draws the bodies of the database as a collapsible tree following their hierarchy, with each
+/// body's orbital elements and derived values like its period, sphere of influence and apsides
+/// 
+/// Distances are shown in kilometers, angles in degrees and periods in days. Root bodies and the
+/// satellites of each body are sorted by handle so the tree doesn't reorder itself every frame.
+pub fn inspect_database<H, T>(ui: &mut Ui, database: &Database<H, T>) where H: Clone + Debug + Display + Eq + Hash + FromPrimitive + Ord, T: Clone + Display + Float + FromPrimitive + LowerExp + SubAssign {
+	let mut roots: Vec<H> = database.iter()
+		.filter(|(_, entry)| entry.parent.is_none())
+		.map(|(handle, _)| handle.clone())
+		.collect();
+	roots.sort();
+	for handle in &roots {
+		inspect_body(ui, database, handle);
+	}
+}
+
+fn inspect_body<H, T>(ui: &mut Ui, database: &Database<H, T>, handle: &H) where H: Clone + Debug + Display + Eq + Hash + FromPrimitive + Ord, T: Clone + Display + Float + FromPrimitive + LowerExp + SubAssign {
+	let to_km = T::from_f64(CONVERT_M_TO_KM).unwrap();
+	let entry = database.get_entry(handle);
+	let satellites = database.get_satellites(handle);
+	let title = format!("{} ({}) - {} satellites", entry.name, handle, satellites.len());
+	CollapsingHeader::new(title).id_salt(handle.to_string()).show(ui, |ui| {
+		Grid::new(("game_orbits_body", handle.to_string())).num_columns(2).striped(true).show(ui, |ui| {
+			ui.label("Mass");
+			ui.label(format!("{:.4e} kg", entry.info.mass_kg()));
+			ui.end_row();
+			ui.label("Equatorial radius");
+			ui.label(format!("{:.1} km", entry.info.radius_equator_km()));
+			ui.end_row();
+			ui.label("Sphere of influence");
+			ui.label(format!("{:.0} km", database.radius_soi(handle) * to_km));
+			ui.end_row();
+			if let Some(orbit) = &entry.orbit {
+				let one = T::from_f32(1.0).unwrap();
+				let (a_km, e, i_deg, arg_peri_deg, lan_deg) = orbit.as_degrees();
+				ui.label("Semimajor axis a");
+				ui.label(format!("{:.1} km", a_km));
+				ui.end_row();
+				ui.label("Eccentricity e");
+				ui.label(format!("{:.6}", e));
+				ui.end_row();
+				ui.label("Inclination i");
+				ui.label(format!("{:.3}°", i_deg));
+				ui.end_row();
+				ui.label("Argument of periapsis ω");
+				ui.label(format!("{:.3}°", arg_peri_deg));
+				ui.end_row();
+				ui.label("Longitude of ascending node Ω");
+				ui.label(format!("{:.3}°", lan_deg));
+				ui.end_row();
+				ui.label("Periapsis");
+				ui.label(format!("{:.1} km", a_km * (one - e)));
+				ui.end_row();
+				ui.label("Apoapsis");
+				if e < one {
+					ui.label(format!("{:.1} km", a_km * (one + e)));
+				} else {
+					ui.label("none, escaping");
+				}
+				ui.end_row();
+				ui.label("Orbital period");
+				match database.orbital_period_s(handle) {
+					Some(period) => ui.label(format!("{:.3} days", period / T::from_f64(SECONDS_PER_DAY).unwrap())),
+					None => ui.label("none"),
+				};
+				ui.end_row();
+			}
+		});
+		for satellite in &satellites {
+			inspect_body(ui, database, satellite);
+		}
+	});
+}
@@ -1,5 +1,5 @@
 use godot::{prelude::*, builtin::{Array, Vector3}, classes::{INode, Node}};
-use crate::{Body, Database, DatabaseEntry, OrbitalElements};
+use crate::{Body, Database, DatabaseEntry, LagrangePoint, OrbitalElements};
 
 pub struct OrbitExtension;
 #[gdextension]
@@ -10,6 +10,18 @@ fn vec_nalgebra_to_godot(input: nalgebra::Vector3<f32>) -> godot::builtin::Vecto
 	godot::builtin::Vector3::new(input.x, input.y, input.z)
 }
 
+/// Maps the numbers 1 to 5 onto the Lagrange points L1 to L5
+fn lagrange_point_from_index(point_index: i64) -> Option<LagrangePoint> {
+	match point_index {
+		1 => Some(LagrangePoint::L1),
+		2 => Some(LagrangePoint::L2),
+		3 => Some(LagrangePoint::L3),
+		4 => Some(LagrangePoint::L4),
+		5 => Some(LagrangePoint::L5),
+		_ => None,
+	}
+}
+
 
 #[derive(GodotClass)]
 #[class(base=Node)]
@@ -28,25 +40,92 @@ impl GodotPlanetDatabase {
 	pub fn add_solar_system(&mut self) {
 		self.database.add_solar_system();
 	}
+	/// Returns a zero vector and pushes an error if either body is missing or they aren't part of
+	/// the same system
 	#[func]
 	pub fn relative_position(&self, origin: i64, relative: i64, time: f32) -> Vector3 {
-		vec_nalgebra_to_godot(self.database.relative_position(&origin, &relative, time).unwrap())
+		match self.database.try_relative_position(&origin, &relative, time) {
+			Ok(position) => vec_nalgebra_to_godot(position),
+			Err(error) => {
+				godot_error!("Failed to find relative position between origin body {} and relative body {}: {}", origin, relative, error);
+				Vector3::ZERO
+			},
+		}
 	}
 	#[func]
 	pub fn axial_tilt_rad(&self, handle: i64) -> f32 {
-		self.database.get_entry(&handle).info.axial_tilt_rad()
+		match self.database.try_get_entry(&handle) {
+			Some(entry) => entry.info.axial_tilt_rad(),
+			None => {
+				godot_error!("No body in database with ID {}", handle);
+				0.0
+			},
+		}
+	}
+	/// Suggested color for drawing the body's orbit, see
+	/// [`Database::orbit_color_suggestion`](crate::Database::orbit_color_suggestion)
+	#[func]
+	pub fn orbit_color(&self, handle: i64) -> Color {
+		match self.database.try_get_entry(&handle) {
+			Some(_) => {
+				let (r, g, b) = self.database.orbit_color_suggestion(&handle);
+				Color::from_rgb(r, g, b)
+			},
+			None => {
+				godot_error!("No body in database with ID {}", handle);
+				Color::WHITE
+			},
+		}
 	}
 	#[func]
 	pub fn radius_soi(&self, handle: i64) -> f32 {
-		self.database.radius_soi(&handle)
+		match self.database.try_get_entry(&handle) {
+			Some(_) => self.database.radius_soi(&handle),
+			None => {
+				godot_error!("No body in database with ID {}", handle);
+				0.0
+			},
+		}
 	}
 	#[func]
 	pub fn add_satellite(&mut self, handle: i64, parent: i64, name: String, mass_kg: f32, radius_km: f32, orbit_radius_km: f32, mean_anomaly: f32){
+		if self.database.try_get_entry(&parent).is_none() {
+			godot_error!("Can't add satellite {}, no parent body in database with ID {}", name, parent);
+			return;
+		}
 		let info = Body::new(mass_kg, radius_km, radius_km, 0.0);
 		let orbit = OrbitalElements::default().with_semimajor_axis_km(orbit_radius_km);
 		let entry = DatabaseEntry::new(info, name).with_parent(parent, orbit).with_mean_anomaly_deg(mean_anomaly);
 		self.database.add_entry(handle, entry);
 	}
+	/// Adds a virtual body at Lagrange point L1 to L5 of `secondary` and its parent `primary`,
+	/// with `point_index` 1 to 5
+	#[func]
+	pub fn add_lagrange_point(&mut self, handle: i64, primary: i64, secondary: i64, point_index: i64, name: String) {
+		let Some(point) = lagrange_point_from_index(point_index) else {
+			godot_error!("Can't add Lagrange point {}, {} isn't a Lagrange point between 1 and 5", name, point_index);
+			return;
+		};
+		if self.database.lagrange_point_position(&primary, &secondary, point, 0.0).is_none() {
+			godot_error!("Can't add Lagrange point {}, body {} doesn't orbit body {}", name, secondary, primary);
+			return;
+		}
+		self.database.add_lagrange_point(handle, &primary, &secondary, point, &name);
+	}
+	/// Returns the position of Lagrange point L1 to L5 relative to `primary`, with `point_index` 1
+	/// to 5, or a zero vector and pushes an error if there's no such point
+	#[func]
+	pub fn lagrange_point_position(&self, primary: i64, secondary: i64, point_index: i64, time: f32) -> Vector3 {
+		let position = lagrange_point_from_index(point_index)
+			.and_then(|point| self.database.lagrange_point_position(&primary, &secondary, point, time));
+		match position {
+			Some(position) => vec_nalgebra_to_godot(position),
+			None => {
+				godot_error!("Failed to find Lagrange point {} of body {} orbiting body {}", point_index, secondary, primary);
+				Vector3::ZERO
+			},
+		}
+	}
 	#[func]
 	pub fn get_satellites(&self, handle: i64) -> Array<i64> {
 		let satellites = self.database.get_satellites(&handle);
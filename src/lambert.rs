@@ -0,0 +1,140 @@
+//! Solver for Lambert's problem, finding the orbit that connects two positions in a given time
+use nalgebra::{RealField, Vector3};
+use num_traits::{Float, FromPrimitive};
+
+
+/// Finds the velocities at both ends of the orbit that travels from position `r1` to position `r2`
+/// around a central body with the given *GM* in `time_of_flight` seconds
+///
+/// Positions are relative to the central body in meters (m), and the returned `(departure,
+/// arrival)` velocities are in meters per second (m/s). `prograde` chooses between the two
+/// possible directions of travel, with prograde orbits circling counter-clockwise around the +Y
+/// axis like the rest of the library. Only single revolution transfers are considered.
+///
+/// Returns `None` if the positions are collinear with the central body, in which case the
+/// transfer plane is undefined, or if the solver fails to converge.
+///
+/// Uses the universal variable formulation from chapter 5 of *Orbital Mechanics for Engineering
+/// Students* by Howard D. Curtis, solved with bisection.
+pub fn solve_lambert<T>(r1: Vector3<T>, r2: Vector3<T>, time_of_flight: T, central_gm: T, prograde: bool) -> Option<(Vector3<T>, Vector3<T>)> where T: Float + FromPrimitive + RealField {
+	let zero = T::from_f32(0.0).unwrap();
+	let one = T::from_f32(1.0).unwrap();
+	let tau = T::from_f64(std::f64::consts::TAU).unwrap();
+	if time_of_flight <= zero {
+		return None;
+	}
+	let r1_mag = r1.norm();
+	let r2_mag = r2.norm();
+	let cos_delta = Float::max(-one, Float::min(one, r1.dot(&r2) / (r1_mag * r2_mag)));
+	let mut delta = Float::acos(cos_delta);
+	let normal_y = r1.cross(&r2).y;
+	if (prograde && normal_y < zero) || (!prograde && normal_y >= zero) {
+		delta = tau - delta;
+	}
+	let a = Float::sin(delta) * Float::sqrt(r1_mag * r2_mag / (one - cos_delta));
+	if !Float::is_finite(a) || Float::abs(a) < T::from_f64(1e-9).unwrap() * (r1_mag + r2_mag) {
+		return None;
+	}
+	let y = |z: T| r1_mag + r2_mag + a * (z * stumpff_s(z) - one) / Float::sqrt(stumpff_c(z));
+	let sqrt_gm = Float::sqrt(central_gm);
+	let flight_time = |z: T| {
+		let y = y(z);
+		(Float::powf(y / stumpff_c(z), T::from_f64(1.5).unwrap()) * stumpff_s(z) + a * Float::sqrt(y)) / sqrt_gm
+	};
+	// time of flight increases monotonically with z, so bracket the solution and bisect
+	let mut z_low = -tau * T::from_f32(2.0).unwrap();
+	let mut z_high = tau * tau;
+	let tolerance = T::from_f64(1e-9).unwrap() * time_of_flight;
+	let mut z = zero;
+	let mut converged = false;
+	for _ in 0..200 {
+		z = (z_low + z_high) / T::from_f32(2.0).unwrap();
+		if y(z) < zero {
+			z_low = z;
+			continue;
+		}
+		let t = flight_time(z);
+		if Float::abs(t - time_of_flight) < tolerance {
+			converged = true;
+			break;
+		}
+		if t < time_of_flight {
+			z_low = z;
+		} else {
+			z_high = z;
+		}
+	}
+	if !converged {
+		return None;
+	}
+	let y = y(z);
+	let f = one - y / r1_mag;
+	let g = a * Float::sqrt(y / central_gm);
+	let g_dot = one - y / r2_mag;
+	let v1 = (r2 - r1 * f) / g;
+	let v2 = (r2 * g_dot - r1) / g;
+	Some((v1, v2))
+}
+
+/// Stumpff function *C(z)*
+fn stumpff_c<T>(z: T) -> T where T: Float + FromPrimitive {
+	let zero = T::from_f32(0.0).unwrap();
+	let one = T::from_f32(1.0).unwrap();
+	if z > zero {
+		(one - Float::cos(Float::sqrt(z))) / z
+	} else if z < zero {
+		(Float::cosh(Float::sqrt(-z)) - one) / -z
+	} else {
+		T::from_f64(0.5).unwrap()
+	}
+}
+
+/// Stumpff function *S(z)*
+fn stumpff_s<T>(z: T) -> T where T: Float + FromPrimitive {
+	let zero = T::from_f32(0.0).unwrap();
+	if z > zero {
+		let sqrt_z = Float::sqrt(z);
+		(sqrt_z - Float::sin(sqrt_z)) / Float::powi(sqrt_z, 3)
+	} else if z < zero {
+		let sqrt_z = Float::sqrt(-z);
+		(Float::sinh(sqrt_z) - sqrt_z) / Float::powi(sqrt_z, 3)
+	} else {
+		T::from_f64(1.0 / 6.0).unwrap()
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use approx::assert_relative_eq;
+	use crate::{Body, OrbitalElements};
+
+	#[test]
+	fn matches_known_orbit() {
+		let earth: Body<f64> = Body::new_earth();
+		let gm = earth.gm();
+		let orbit: OrbitalElements<f64> = OrbitalElements::default()
+			.with_semimajor_axis_km(12_000.0)
+			.with_eccentricity(0.2)
+			.with_inclination_deg(20.0)
+			.with_long_of_ascending_node_deg(30.0);
+		let (nu_1, nu_2) = (0.4, 2.1);
+		let n = (gm / orbit.semimajor_axis.powi(3)).sqrt();
+		let time_of_flight = (orbit.mean_anomaly_at_true_anomaly(nu_2) - orbit.mean_anomaly_at_true_anomaly(nu_1)) / n;
+		let r1 = orbit.position_at_true_anomaly(nu_1);
+		let r2 = orbit.position_at_true_anomaly(nu_2);
+		let (v1, v2) = solve_lambert(r1, r2, time_of_flight, gm, true).unwrap();
+		let expected_v1 = orbit.velocity_at_true_anomaly(nu_1, gm);
+		let expected_v2 = orbit.velocity_at_true_anomaly(nu_2, gm);
+		assert_relative_eq!(expected_v1, v1, max_relative=1e-6);
+		assert_relative_eq!(expected_v2, v2, max_relative=1e-6);
+	}
+
+	#[test]
+	fn collinear_positions() {
+		let r1 = Vector3::new(7_000_000.0, 0.0, 0.0);
+		let r2 = Vector3::new(-8_000_000.0, 0.0, 0.0);
+		assert!(solve_lambert(r1, r2, 3_000.0, 3.986e14, true).is_none());
+	}
+}
@@ -16,12 +16,22 @@ pub mod constants;
 mod body; pub use body::*;
 mod database; pub use database::*;
 mod elements; pub use elements::*;
+mod error; pub use error::*;
+mod lambert; pub use lambert::*;
+mod spatial;
+mod spec; pub use spec::*;
 #[cfg(test)]
 mod problems;
+#[cfg(test)]
+mod data_sanity;
 
 #[cfg(feature="bevy")]
 mod feat_bevy;
 #[cfg(feature="bevy")]
 pub use feat_bevy::*;
+#[cfg(feature="egui")]
+mod feat_egui;
+#[cfg(feature="egui")]
+pub use feat_egui::*;
 #[cfg(feature="godot")]
 pub mod feat_godot;
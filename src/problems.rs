@@ -16,7 +16,7 @@
 
 use std::f32::consts::PI;
 use approx::assert_ulps_eq;
-use crate::{Body, constants::f32::*};
+use crate::{Body, constants::f32::*, mean_motion};
 
 
 /// Illustrates the difference in precision between the numbers used in
@@ -35,6 +35,9 @@ fn gm() {
     let earth = Body::new_earth();
     let gm = earth.gm();
     assert_ulps_eq!(problem_gm, gm, epsilon=2.0e11);
+    // storing the canonical GM avoids the precision lost multiplying the mass by G
+    let earth: Body<f32> = Body::new_earth().with_gm_m3_s2(problem_gm);
+    assert_eq!(problem_gm, earth.gm());
 }
 
 /// [Problem 4.1](http://www.braeunig.us/space/problem.htm#4.1)
@@ -258,7 +261,7 @@ mod launch
         assert_ulps_eq!(0.42978, mean_anomaly_0, epsilon=epsilon);
         let mean_anomaly: f32 = eccentric_anomaly - e * eccentric_anomaly.sin();
         assert_ulps_eq!(1.37113, mean_anomaly, epsilon=epsilon);
-        let n = (earth.gm() / a.powi(3)).sqrt();
+        let n = mean_motion(earth.gm(), a);
         assert_ulps_eq!(0.00097202, n, epsilon=0.000001);
         let t = t_0 + (mean_anomaly - mean_anomaly_0) / n;
         assert_ulps_eq!(968.4, t, epsilon=10.0);
@@ -0,0 +1,147 @@
+//! Uniform grid over body positions at a snapshot time, for speeding up proximity queries on
+//! databases with lots of bodies
+use std::{collections::HashMap, hash::Hash};
+use nalgebra::{RealField, Vector3};
+use num_traits::{Float, FromPrimitive};
+
+
+/// Integer coordinates of a cell in the grid
+type Cell = (i64, i64, i64);
+
+/// Buckets the absolute positions of every body at one point in time into cubic cells
+pub(crate) struct SpatialIndex<H, T> {
+	/// Time since epoch in seconds that the positions were taken at
+	pub time: T,
+	cell_size: T,
+	cells: HashMap<Cell, Vec<(H, Vector3<T>)>>,
+	len: usize,
+}
+impl<H, T> SpatialIndex<H, T> where H: Clone + Eq + Hash, T: Float + FromPrimitive + RealField {
+	pub fn new(time: T, cell_size: T, positions: impl IntoIterator<Item = (H, Vector3<T>)>) -> Self {
+		let mut index = Self{ time, cell_size, cells: HashMap::new(), len: 0 };
+		for (handle, position) in positions {
+			index.cells.entry(index.cell_of(&position)).or_default().push((handle, position));
+			index.len += 1;
+		}
+		index
+	}
+	fn cell_of(&self, position: &Vector3<T>) -> Cell {
+		let cell = |value: T| Float::floor(value / self.cell_size).to_i64().unwrap_or(0);
+		(cell(position.x), cell(position.y), cell(position.z))
+	}
+	/// Calls `visit` with every body in the cells at exactly `ring` cells from the cell containing
+	/// `point`, measuring distance along whichever axis is furthest
+	/// 
+	/// Only the cells on the surface of the ring's cube are looked up, so visiting ring *r* costs
+	/// *O(r²)* rather than *O(r³)*.
+	fn visit_ring(&self, point: &Vector3<T>, ring: i64, mut visit: impl FnMut(&H, &Vector3<T>)) {
+		let (cx, cy, cz) = self.cell_of(point);
+		let mut visit_cell = |dx: i64, dy: i64, dz: i64| {
+			if let Some(bodies) = self.cells.get(&(cx + dx, cy + dy, cz + dz)) {
+				for (handle, position) in bodies {
+					visit(handle, position);
+				}
+			}
+		};
+		for dx in -ring..=ring {
+			for dy in -ring..=ring {
+				if dx.abs() == ring || dy.abs() == ring {
+					// on the sides of the cube, so the whole column along Z is on the shell
+					for dz in -ring..=ring {
+						visit_cell(dx, dy, dz);
+					}
+				} else {
+					// inside the cube's cross section, so only the top and bottom faces are
+					visit_cell(dx, dy, -ring);
+					visit_cell(dx, dy, ring);
+				}
+			}
+		}
+	}
+	/// Whether visiting every cell out to the given ring would cost more than checking every body
+	fn ring_too_large(&self, ring: i64) -> bool {
+		ring.saturating_mul(2).saturating_add(1).checked_pow(3).is_none_or(|cells| cells as usize > self.len)
+	}
+	/// Gets every body in the index as `(handle, distance from point)` pairs
+	fn all_distances(&self, point: &Vector3<T>) -> Vec<(H, T)> {
+		self.cells.values().flatten().map(|(handle, position)| (handle.clone(), (position - point).norm())).collect()
+	}
+	/// Finds every body within `radius` of `point`, as `(handle, distance)` pairs in no particular
+	/// order
+	pub fn within(&self, point: &Vector3<T>, radius: T) -> Vec<(H, T)> {
+		let rings = Float::ceil(radius / self.cell_size).to_i64().unwrap_or(i64::MAX);
+		if self.ring_too_large(rings) {
+			let mut found = self.all_distances(point);
+			found.retain(|(_, distance)| *distance <= radius);
+			return found;
+		}
+		let mut found = Vec::new();
+		for ring in 0..=rings {
+			self.visit_ring(point, ring, |handle, position| {
+				let distance = (position - point).norm();
+				if distance <= radius {
+					found.push((handle.clone(), distance));
+				}
+			});
+		}
+		found
+	}
+	/// Finds the `count` bodies closest to `point` as `(handle, distance)` pairs, nearest first
+	pub fn nearest(&self, point: &Vector3<T>, count: usize) -> Vec<(H, T)> {
+		let by_distance = |a: &(H, T), b: &(H, T)| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal);
+		let mut found: Vec<(H, T)> = Vec::new();
+		let mut visited = 0;
+		let mut ring = 0;
+		while visited < self.len && count > 0 {
+			// queries far outside the grid fall back to checking every body
+			if self.ring_too_large(ring) {
+				found = self.all_distances(point);
+				break;
+			}
+			self.visit_ring(point, ring, |handle, position| {
+				found.push((handle.clone(), (position - point).norm()));
+				visited += 1;
+			});
+			// every body outside the rings visited so far is at least this far away
+			let unvisited_distance = self.cell_size * T::from_i64(ring).unwrap();
+			if found.len() >= count {
+				found.sort_by(by_distance);
+				if found[count - 1].1 <= unvisited_distance {
+					break;
+				}
+			}
+			ring += 1;
+		}
+		found.sort_by(by_distance);
+		found.truncate(count);
+		found
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn visit_ring() {
+		// one body at the center of every cell in a 9×9×9 block
+		let positions = (-4..=4).flat_map(|x| (-4..=4).flat_map(move |y| (-4..=4).map(move |z| ((x, y, z), Vector3::new(x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5)))));
+		let index = SpatialIndex::new(0.0, 1.0, positions);
+		let mut seen = HashMap::new();
+		for ring in 0..=4 {
+			let mut count = 0;
+			index.visit_ring(&Vector3::new(0.5, 0.5, 0.5), ring, |&(x, y, z): &Cell, _| {
+				assert_eq!(ring, i64::max(x.abs(), i64::max(y.abs(), z.abs())));
+				*seen.entry((x, y, z)).or_insert(0) += 1;
+				count += 1;
+			});
+			let side = 2 * ring + 1;
+			let inner = (2 * ring - 1).max(0);
+			assert_eq!(side * side * side - inner * inner * inner, count);
+		}
+		// every cell of the block is visited exactly once
+		assert_eq!(9 * 9 * 9, seen.len());
+		assert!(seen.values().all(|visits| *visits == 1));
+	}
+}
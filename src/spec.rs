@@ -0,0 +1,299 @@
+//! Specifications for building an entire planetary system in one go
+use std::{fmt::Debug, hash::Hash, ops::SubAssign};
+use nalgebra::RealField;
+use num_traits::{Float, FromPrimitive};
+use crate::{Body, Database, DatabaseEntry, OrbitError, OrbitalElements};
+
+#[cfg(feature="rand")]
+use rand::Rng;
+#[cfg(feature="rand")]
+use crate::constants::f64::{CONVERT_AU_TO_KM, MASS_EARTH_KG, MASS_SUN_KG, RADIUS_SUN_M};
+
+
+/// A single body in a [`SystemSpec`], with the handle and name it will have in the database
+#[derive(Clone)]
+pub struct BodySpec<H, T> {
+	pub handle: H,
+	pub name: String,
+	pub info: Body<T>,
+}
+impl<H, T> BodySpec<H, T> {
+	pub fn new<S>(handle: H, info: Body<T>, name: S) -> Self where S: Into<String> {
+		Self{ handle, name: name.into(), info }
+	}
+}
+
+/// A moon orbiting one of the planets in a [`SystemSpec`]
+#[derive(Clone)]
+pub struct MoonSpec<H, T> {
+	pub body: BodySpec<H, T>,
+	pub orbit: OrbitalElements<T>,
+	pub mean_anomaly_deg: T,
+}
+impl<H, T> MoonSpec<H, T> where T: FromPrimitive {
+	pub fn new(body: BodySpec<H, T>, orbit: OrbitalElements<T>) -> Self {
+		Self{ body, orbit, mean_anomaly_deg: T::from_f32(0.0).unwrap() }
+	}
+	pub fn with_mean_anomaly_deg(mut self, mean_anomaly: T) -> Self {
+		self.mean_anomaly_deg = mean_anomaly;
+		self
+	}
+}
+
+/// A planet orbiting the star of a [`SystemSpec`], along with its moons
+#[derive(Clone)]
+pub struct PlanetSpec<H, T> {
+	pub body: BodySpec<H, T>,
+	pub orbit: OrbitalElements<T>,
+	pub mean_anomaly_deg: T,
+	pub moons: Vec<MoonSpec<H, T>>,
+}
+impl<H, T> PlanetSpec<H, T> where T: FromPrimitive {
+	pub fn new(body: BodySpec<H, T>, orbit: OrbitalElements<T>) -> Self {
+		Self{ body, orbit, mean_anomaly_deg: T::from_f32(0.0).unwrap(), moons: Vec::new() }
+	}
+	pub fn with_mean_anomaly_deg(mut self, mean_anomaly: T) -> Self {
+		self.mean_anomaly_deg = mean_anomaly;
+		self
+	}
+	pub fn with_moon(mut self, moon: MoonSpec<H, T>) -> Self {
+		self.moons.push(moon);
+		self
+	}
+}
+
+/// Description of a whole star system, a star with planets that can have moons of their own, to
+/// be built with [`Database::from_system_spec`]
+#[derive(Clone)]
+pub struct SystemSpec<H, T> {
+	pub star: BodySpec<H, T>,
+	pub planets: Vec<PlanetSpec<H, T>>,
+}
+impl<H, T> SystemSpec<H, T> {
+	pub fn new(star: BodySpec<H, T>) -> Self {
+		Self{ star, planets: Vec::new() }
+	}
+	pub fn with_planet(mut self, planet: PlanetSpec<H, T>) -> Self {
+		self.planets.push(planet);
+		self
+	}
+}
+#[cfg(feature="rand")]
+impl<H, T> SystemSpec<H, T> where H: FromPrimitive, T: Float + FromPrimitive + SubAssign {
+	/// Generates a random star system with the given number of planets, each with up to three
+	/// moons
+	///
+	/// Planets and moons are placed on nearly circular, nearly equatorial orbits that are spaced
+	/// out far enough for the result to always pass the checks in [`Database::from_system_spec`].
+	/// Handles are assigned in order starting from zero for the star, with each planet followed by
+	/// its moons.
+	pub fn random(rng: &mut impl Rng, num_planets: usize) -> Self {
+		let to_t = |value: f64| T::from_f64(value).unwrap();
+		let mut next_handle = 0;
+		let mut take_handle = || {
+			next_handle += 1;
+			H::from_usize(next_handle - 1).unwrap()
+		};
+		let radius_from_density_km = |mass_kg: f64, density_kg_m3: f64| {
+			Float::cbrt(3.0 * mass_kg / (4.0 * std::f64::consts::PI * density_kg_m3)) / 1000.0
+		};
+		let random_orbit = |rng: &mut dyn rand::RngCore, semimajor_axis_km: f64| {
+			OrbitalElements::default()
+				.with_semimajor_axis_km(to_t(semimajor_axis_km))
+				.with_eccentricity(to_t(rng.gen_range(0.0..0.05)))
+				.with_inclination_deg(to_t(rng.gen_range(0.0..3.0)))
+				.with_long_of_ascending_node_deg(to_t(rng.gen_range(0.0..360.0)))
+				.with_arg_of_periapsis_deg(to_t(rng.gen_range(0.0..360.0)))
+		};
+		let star_mass_kg = MASS_SUN_KG * rng.gen_range(0.5..2.0);
+		let star_ratio = star_mass_kg / MASS_SUN_KG;
+		let star_info = Body::new_sol()
+			.with_mass_kg(to_t(star_mass_kg))
+			.with_radius_m(to_t(RADIUS_SUN_M * Float::powf(star_ratio, 0.8)))
+			.with_surface_temperature_k(to_t(5772.0 * Float::powf(star_ratio, 0.5)));
+		let mut spec = Self::new(BodySpec::new(take_handle(), star_info, "Star"));
+		let mut planet_semimajor_axis_km = 0.4 * CONVERT_AU_TO_KM;
+		for planet_index in 0..num_planets {
+			let planet_mass_kg = MASS_EARTH_KG * Float::powf(10.0, rng.gen_range(-1.0..2.5));
+			let planet_radius_km = radius_from_density_km(planet_mass_kg, rng.gen_range(1000.0..5500.0));
+			let planet_info = Body::default()
+				.with_mass_kg(to_t(planet_mass_kg))
+				.with_radius_km(to_t(planet_radius_km));
+			let planet_body = BodySpec::new(take_handle(), planet_info, format!("Planet {}", planet_index + 1));
+			let mut planet = PlanetSpec::new(planet_body, random_orbit(rng, planet_semimajor_axis_km))
+				.with_mean_anomaly_deg(to_t(rng.gen_range(0.0..360.0)));
+			// moons stay well inside the inner quarter of the planet's sphere of influence
+			let radius_soi_km = planet_semimajor_axis_km * Float::powf(planet_mass_kg / star_mass_kg, 0.4);
+			let mut moon_semimajor_axis_km = radius_soi_km * 0.08;
+			for moon_index in 0..rng.gen_range(0..=3) {
+				let moon_mass_kg = planet_mass_kg * Float::powf(10.0, rng.gen_range(-6.0..-3.0));
+				let moon_info = Body::default()
+					.with_mass_kg(to_t(moon_mass_kg))
+					.with_radius_km(to_t(radius_from_density_km(moon_mass_kg, 3000.0)));
+				let moon_body = BodySpec::new(take_handle(), moon_info, format!("Planet {} Moon {}", planet_index + 1, moon_index + 1));
+				let moon_semimajor_axis_jittered_km = moon_semimajor_axis_km * rng.gen_range(1.0..1.2);
+				let moon_orbit = random_orbit(rng, moon_semimajor_axis_jittered_km);
+				planet = planet.with_moon(MoonSpec::new(moon_body, moon_orbit).with_mean_anomaly_deg(to_t(rng.gen_range(0.0..360.0))));
+				moon_semimajor_axis_km *= 1.6;
+			}
+			spec = spec.with_planet(planet);
+			planet_semimajor_axis_km *= rng.gen_range(1.6..2.2);
+		}
+		spec
+	}
+}
+
+
+impl<H, T> Database<H, T> where H: Clone + Eq + Hash + FromPrimitive, T: Clone + Float + FromPrimitive + SubAssign {
+	/// Builds a new database containing the star system described by the given spec
+	///
+	/// The spec is checked before it's accepted. Every orbit has to be closed and stay above the
+	/// surface of its parent, every moon has to stay inside its planet's sphere of influence, and
+	/// the spheres of influence of planets around the star, or of moons around the same planet,
+	/// can't overlap anywhere between their periapsis and apoapsis.
+	pub fn from_system_spec(spec: SystemSpec<H, T>) -> Result<Self, OrbitError<H>> where H: Debug + Ord, T: RealField {
+		let one = T::from_f32(1.0).unwrap();
+		let mut database = Self::default();
+		let star_handle = spec.star.handle.clone();
+		database.add_spec_body(spec.star, None, None)?;
+		let mut planet_handles = Vec::new();
+		let mut moon_handles = Vec::new();
+		for planet in spec.planets {
+			let planet_handle = planet.body.handle.clone();
+			database.add_spec_body(planet.body, Some((star_handle.clone(), planet.orbit)), Some(planet.mean_anomaly_deg))?;
+			let mut moons = Vec::new();
+			for moon in planet.moons {
+				moons.push(moon.body.handle.clone());
+				database.add_spec_body(moon.body, Some((planet_handle.clone(), moon.orbit)), Some(moon.mean_anomaly_deg))?;
+			}
+			planet_handles.push(planet_handle.clone());
+			moon_handles.push((planet_handle, moons));
+		}
+		database.check_soi_overlaps(&planet_handles)?;
+		for (planet_handle, moons) in moon_handles {
+			let radius_soi = database.radius_soi(&planet_handle);
+			for moon_handle in &moons {
+				let orbit = database.get_entry(moon_handle).orbit.unwrap();
+				if orbit.semimajor_axis * (one + orbit.eccentricity) + database.radius_soi(moon_handle) > radius_soi {
+					return Err(OrbitError::OutsideParentSphereOfInfluence(moon_handle.clone()));
+				}
+			}
+			database.check_soi_overlaps(&moons)?;
+		}
+		Ok(database)
+	}
+	/// Adds a single body from a [`SystemSpec`], checking that its handle is unique and that its
+	/// orbit is physical
+	fn add_spec_body(&mut self, body: BodySpec<H, T>, orbit: Option<(H, OrbitalElements<T>)>, mean_anomaly_deg: Option<T>) -> Result<(), OrbitError<H>> where H: Debug {
+		let zero = T::from_f32(0.0).unwrap();
+		let one = T::from_f32(1.0).unwrap();
+		if self.try_get_entry(&body.handle).is_some() {
+			return Err(OrbitError::DuplicateHandle(body.handle));
+		}
+		let mut entry = DatabaseEntry::new(body.info, body.name);
+		if let Some((parent_handle, orbit)) = orbit {
+			if orbit.semimajor_axis <= zero || orbit.eccentricity < zero || orbit.eccentricity >= one {
+				return Err(OrbitError::UnboundOrbit(body.handle));
+			}
+			if orbit.semimajor_axis * (one - orbit.eccentricity) < self.get_entry(&parent_handle).info.radius_equator_m() {
+				return Err(OrbitError::OrbitIntersectsParentBody(parent_handle));
+			}
+			entry = entry.with_parent(parent_handle, orbit);
+		}
+		if let Some(mean_anomaly) = mean_anomaly_deg {
+			entry = entry.with_mean_anomaly_deg(mean_anomaly);
+		}
+		self.add_entry(body.handle, entry);
+		Ok(())
+	}
+	/// Checks that the spheres of influence of bodies orbiting the same parent never overlap,
+	/// treating each one as sweeping out the band between its periapsis and apoapsis
+	fn check_soi_overlaps(&self, handles: &[H]) -> Result<(), OrbitError<H>> where H: Debug + Ord {
+		let one = T::from_f32(1.0).unwrap();
+		let bands: Vec<(T, T)> = handles.iter().map(|handle| {
+			let orbit = self.get_entry(handle).orbit.unwrap();
+			let radius_soi = self.radius_soi(handle);
+			(
+				orbit.semimajor_axis * (one - orbit.eccentricity) - radius_soi,
+				orbit.semimajor_axis * (one + orbit.eccentricity) + radius_soi,
+			)
+		}).collect();
+		for i in 0..handles.len() {
+			for j in (i + 1)..handles.len() {
+				if bands[i].0 < bands[j].1 && bands[j].0 < bands[i].1 {
+					return Err(OrbitError::OverlappingSpheresOfInfluence(handles[i].clone(), handles[j].clone()));
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::constants::f64::MASS_EARTH_KG;
+
+	fn test_spec() -> SystemSpec<u16, f64> {
+		let mut spec = SystemSpec::new(BodySpec::new(0, Body::new_sol(), "Star"));
+		for planet_index in 0..3u16 {
+			let planet_handle = 10 * (planet_index + 1);
+			let planet_info = Body::default().with_mass_earths(1.0).with_radius_km(6000.0);
+			let planet_orbit = OrbitalElements::default().with_semimajor_axis_au(1.0 + planet_index as f64);
+			let mut planet = PlanetSpec::new(BodySpec::new(planet_handle, planet_info, format!("Planet {}", planet_index)), planet_orbit);
+			for moon_index in 0..2u16 {
+				let moon_info = Body::default().with_mass_kg(MASS_EARTH_KG * 0.001).with_radius_km(500.0);
+				let moon_orbit = OrbitalElements::default().with_semimajor_axis_km(100_000.0 * (moon_index + 1) as f64);
+				planet = planet.with_moon(MoonSpec::new(BodySpec::new(planet_handle + moon_index + 1, moon_info, "Moon"), moon_orbit));
+			}
+			spec = spec.with_planet(planet);
+		}
+		spec
+	}
+
+	#[test]
+	fn from_system_spec() {
+		let database = Database::from_system_spec(test_spec()).unwrap();
+		assert_eq!(10, database.iter().count());
+		assert_eq!(vec![10, 20, 30], database.get_satellites(&0));
+		for planet_handle in [10, 20, 30] {
+			assert_eq!(Some(0), database.get_entry(&planet_handle).parent);
+			assert_eq!(vec![planet_handle + 1, planet_handle + 2], database.get_satellites(&planet_handle));
+		}
+		assert_eq!(None, database.get_entry(&0).parent);
+	}
+
+	#[test]
+	fn from_system_spec_rejects_bad_specs() {
+		let mut spec = test_spec();
+		spec.planets[1].orbit.semimajor_axis = spec.planets[0].orbit.semimajor_axis * 1.001;
+		assert_eq!(Err(OrbitError::OverlappingSpheresOfInfluence(10, 20)), Database::from_system_spec(spec).map(|_| ()));
+		let mut spec = test_spec();
+		spec.planets[2].moons[1].orbit.semimajor_axis = 1e10;
+		assert_eq!(Err(OrbitError::OutsideParentSphereOfInfluence(32)), Database::from_system_spec(spec).map(|_| ()));
+		let mut spec = test_spec();
+		spec.planets[0].moons[0].orbit.semimajor_axis = 1e6;
+		assert_eq!(Err(OrbitError::OrbitIntersectsParentBody(10)), Database::from_system_spec(spec).map(|_| ()));
+		let mut spec = test_spec();
+		spec.planets[0].orbit.eccentricity = 1.5;
+		assert_eq!(Err(OrbitError::UnboundOrbit(10)), Database::from_system_spec(spec).map(|_| ()));
+		let mut spec = test_spec();
+		spec.planets[1].moons[0].body.handle = 11;
+		assert_eq!(Err(OrbitError::DuplicateHandle(11)), Database::from_system_spec(spec).map(|_| ()));
+	}
+
+	#[cfg(feature="rand")]
+	#[test]
+	fn random_system_spec() {
+		use rand::{rngs::StdRng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(1909);
+		for _ in 0..50 {
+			let spec: SystemSpec<u16, f64> = SystemSpec::random(&mut rng, 6);
+			let num_bodies = 1 + spec.planets.iter().map(|planet| 1 + planet.moons.len()).sum::<usize>();
+			let database = Database::from_system_spec(spec).unwrap();
+			assert_eq!(num_bodies, database.iter().count());
+			assert_eq!(6, database.get_satellites(&0).len());
+		}
+	}
+}